@@ -0,0 +1,482 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use std::fs::{create_dir_all, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use crate::modes::Modes;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Copy, Clone)]
+pub enum Region {
+    Region1,
+    Region2,
+    Region3,
+    FCC,
+}
+
+impl Region {
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Region::Region1),
+            1 => Some(Region::Region2),
+            2 => Some(Region::Region3),
+            3 => Some(Region::FCC),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Copy, Clone)]
+pub enum LicenseClass {
+    Extra,
+    Advanced,
+    General,
+    Technician,
+    Novice,
+}
+
+impl LicenseClass {
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(LicenseClass::Extra),
+            1 => Some(LicenseClass::Advanced),
+            2 => Some(LicenseClass::General),
+            3 => Some(LicenseClass::Technician),
+            4 => Some(LicenseClass::Novice),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Copy, Clone)]
+pub enum SegmentType {
+    CW,
+    Digital,
+    Phone,
+    Beacon,
+}
+
+// beacon segments no wider than this (Hz) refuse TX when it is inhibited
+const BEACON_BLOCK_WIDTH: f32 = 2000.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BandSegment {
+    pub low: f32,
+    pub high: f32,
+    pub segment_type: SegmentType,
+    pub name: String,
+    // overrides the mode picked from the segment type, e.g. USB on 60m
+    pub mode: Option<Modes>,
+    // the license classes allowed to transmit here, empty for everyone
+    pub classes: Vec<LicenseClass>,
+}
+
+impl BandSegment {
+    fn new(low_khz: f32, high_khz: f32, segment_type: SegmentType, name: &str, classes: &[LicenseClass]) -> Self {
+        BandSegment {
+            low: low_khz * 1000.0,
+            high: high_khz * 1000.0,
+            segment_type,
+            name: String::from(name),
+            mode: None,
+            classes: classes.to_vec(),
+        }
+    }
+
+    // a single beacon frequency such as the NCDXF beacons, wider beacon ranges are only a convention
+    pub fn blocks_tx(&self) -> bool {
+        self.segment_type == SegmentType::Beacon && self.high - self.low <= BEACON_BLOCK_WIDTH
+    }
+
+    fn with_mode(mut self, mode: Modes) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    // the mode to use when following the band plan
+    pub fn mode(&self) -> Option<Modes> {
+        if self.mode.is_some() {
+            return self.mode;
+        }
+        let lower_sideband = self.low < 10000000.0;
+        match self.segment_type {
+            SegmentType::CW => if lower_sideband { Some(Modes::CWL) } else { Some(Modes::CWU) },
+            SegmentType::Digital => Some(Modes::DIGU),
+            SegmentType::Phone => if lower_sideband { Some(Modes::LSB) } else { Some(Modes::USB) },
+            SegmentType::Beacon => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegionPlan {
+    pub region: Region,
+    pub segments: Vec<BandSegment>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BandPlan {
+    pub region: Region,
+    pub license: LicenseClass,
+    pub show: bool,
+    pub follow: bool,
+    // refuse to transmit in segments the license does not allow and on beacon frequencies
+    pub inhibit_tx: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub plans: Vec<RegionPlan>,
+}
+
+impl BandPlan {
+
+    pub fn new() -> BandPlan {
+        let region = Region::Region1;
+        let license = LicenseClass::Extra;
+        let show = true;
+        let follow = false;
+        let inhibit_tx = false;
+        let plans: Vec<RegionPlan> = Vec::new();
+        BandPlan {
+            region,
+            license,
+            show,
+            follow,
+            inhibit_tx,
+            plans,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.load();
+    }
+
+    pub fn file_path() -> PathBuf {
+        let app_name = env!("CARGO_PKG_NAME");
+        let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+        config_dir.join(app_name).join("bandplan.json")
+    }
+
+    // read the band plans from the file, creating it from the defaults if it does not exist
+    pub fn load(&mut self) {
+        let path = Self::file_path();
+        if path.exists() {
+            match File::open(&path) {
+                Ok(mut file) => {
+                    let mut s = String::new();
+                    let _ = file.read_to_string(&mut s);
+                    match serde_json::from_str::<Vec<RegionPlan>>(&s) {
+                        Ok(plans) => {
+                            println!("Successfully loaded band plan from {:?}", path);
+                            self.plans = plans;
+                            return;
+                        }
+                        Err(e) => {
+                            eprintln!("Error parsing band plan {:?}: {}", path, e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error opening band plan {:?}: {}", path, e);
+                }
+            }
+            self.plans = default_plans();
+        } else {
+            self.plans = default_plans();
+            self.save();
+        }
+    }
+
+    pub fn save(&self) {
+        let path = Self::file_path();
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                if let Err(_e) = create_dir_all(parent) {
+                    return;
+                }
+            }
+        }
+        match serde_json::to_string_pretty(&self.plans) {
+            Ok(s) => {
+                match File::create(&path) {
+                    Ok(mut file) => {
+                        if let Err(e) = file.write_all(s.as_bytes()) {
+                            eprintln!("Error writing band plan {:?}: {}", path, e);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Error creating band plan: {}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Error serializing band plan: {}", e);
+            }
+        }
+    }
+
+    pub fn segments(&self) -> &[BandSegment] {
+        match self.plans.iter().find(|p| p.region == self.region) {
+            Some(plan) => &plan.segments,
+            None => &[],
+        }
+    }
+
+    // the segment containing the frequency, beacons take priority over the segment around them
+    pub fn find(&self, frequency: f32) -> Option<(usize, &BandSegment)> {
+        let mut found = None;
+        for (i, segment) in self.segments().iter().enumerate() {
+            if frequency >= segment.low && frequency < segment.high {
+                if segment.segment_type == SegmentType::Beacon {
+                    return Some((i, segment));
+                }
+                if found.is_none() {
+                    found = Some((i, segment));
+                }
+            }
+        }
+        found
+    }
+
+    pub fn is_licensed(&self, segment: &BandSegment) -> bool {
+        segment.classes.is_empty() || segment.classes.contains(&self.license)
+    }
+
+    // false over a beacon frequency or where the license does not allow, low and high are the edges of the signal
+    pub fn may_transmit(&self, low: f32, high: f32) -> bool {
+        if !self.inhibit_tx || self.segments().is_empty() {
+            return true;
+        }
+        let segments = self.segments();
+        if segments.iter().any(|segment| segment.blocks_tx() && segment.low <= high && segment.high >= low) {
+            return false;
+        }
+        // the license is checked against the segment around a beacon, not the beacon,
+        // outside the planned bands (e.g. a transverter) is left to the operator
+        [low, high].iter().all(|frequency| {
+            let mut enclosing = segments.iter().filter(|segment| segment.segment_type != SegmentType::Beacon
+                && *frequency >= segment.low && *frequency <= segment.high).peekable();
+            enclosing.peek().is_none() || enclosing.any(|segment| self.is_licensed(segment))
+        })
+    }
+}
+
+fn default_plans() -> Vec<RegionPlan> {
+    use LicenseClass::*;
+    use SegmentType::*;
+
+    let region1 = vec![
+        BandSegment::new(135.7, 137.8, CW, "CW", &[]),
+        BandSegment::new(472.0, 479.0, CW, "CW", &[]),
+        BandSegment::new(1810.0, 1838.0, CW, "CW", &[]),
+        BandSegment::new(1838.0, 1843.0, Digital, "Digital", &[]),
+        BandSegment::new(1843.0, 2000.0, Phone, "Phone", &[]),
+        BandSegment::new(3500.0, 3570.0, CW, "CW", &[]),
+        BandSegment::new(3570.0, 3600.0, Digital, "Digital", &[]),
+        BandSegment::new(3600.0, 3800.0, Phone, "Phone", &[]),
+        BandSegment::new(5351.5, 5354.0, CW, "CW", &[]),
+        BandSegment::new(5354.0, 5366.0, Phone, "Phone", &[]).with_mode(Modes::USB),
+        BandSegment::new(5366.0, 5366.5, Digital, "Weak Signal", &[]),
+        BandSegment::new(7000.0, 7040.0, CW, "CW", &[]),
+        BandSegment::new(7040.0, 7060.0, Digital, "Digital", &[]),
+        BandSegment::new(7060.0, 7200.0, Phone, "Phone", &[]),
+        BandSegment::new(10100.0, 10130.0, CW, "CW", &[]),
+        BandSegment::new(10130.0, 10150.0, Digital, "Digital", &[]),
+        BandSegment::new(14000.0, 14070.0, CW, "CW", &[]),
+        BandSegment::new(14070.0, 14112.0, Digital, "Digital", &[]),
+        BandSegment::new(14099.5, 14100.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(14112.0, 14350.0, Phone, "Phone", &[]),
+        BandSegment::new(18068.0, 18095.0, CW, "CW", &[]),
+        BandSegment::new(18095.0, 18111.0, Digital, "Digital", &[]),
+        BandSegment::new(18109.5, 18110.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(18111.0, 18168.0, Phone, "Phone", &[]),
+        BandSegment::new(21000.0, 21070.0, CW, "CW", &[]),
+        BandSegment::new(21070.0, 21151.0, Digital, "Digital", &[]),
+        BandSegment::new(21149.5, 21150.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(21151.0, 21450.0, Phone, "Phone", &[]),
+        BandSegment::new(24890.0, 24915.0, CW, "CW", &[]),
+        BandSegment::new(24915.0, 24931.0, Digital, "Digital", &[]),
+        BandSegment::new(24929.5, 24930.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(24931.0, 24990.0, Phone, "Phone", &[]),
+        BandSegment::new(28000.0, 28070.0, CW, "CW", &[]),
+        BandSegment::new(28070.0, 28190.0, Digital, "Digital", &[]),
+        BandSegment::new(28190.0, 28225.0, Beacon, "Beacons", &[]),
+        BandSegment::new(28225.0, 29510.0, Phone, "Phone", &[]),
+        BandSegment::new(29510.0, 29700.0, Phone, "FM", &[]).with_mode(Modes::FMN),
+        BandSegment::new(50000.0, 50100.0, CW, "CW", &[]),
+        BandSegment::new(50100.0, 50300.0, Phone, "Phone", &[]),
+        BandSegment::new(50300.0, 50400.0, Digital, "Digital", &[]),
+        BandSegment::new(50400.0, 50500.0, Beacon, "Beacons", &[]),
+        BandSegment::new(50500.0, 52000.0, Phone, "Phone", &[]),
+    ];
+
+    let region2 = vec![
+        BandSegment::new(135.7, 137.8, CW, "CW", &[]),
+        BandSegment::new(472.0, 479.0, CW, "CW", &[]),
+        BandSegment::new(1800.0, 1840.0, CW, "CW", &[]),
+        BandSegment::new(1840.0, 1850.0, Digital, "Digital", &[]),
+        BandSegment::new(1850.0, 2000.0, Phone, "Phone", &[]),
+        BandSegment::new(3500.0, 3580.0, CW, "CW", &[]),
+        BandSegment::new(3580.0, 3600.0, Digital, "Digital", &[]),
+        BandSegment::new(3600.0, 4000.0, Phone, "Phone", &[]),
+        BandSegment::new(7000.0, 7040.0, CW, "CW", &[]),
+        BandSegment::new(7040.0, 7050.0, Digital, "Digital", &[]),
+        BandSegment::new(7050.0, 7300.0, Phone, "Phone", &[]),
+        BandSegment::new(10100.0, 10130.0, CW, "CW", &[]),
+        BandSegment::new(10130.0, 10150.0, Digital, "Digital", &[]),
+        BandSegment::new(14000.0, 14070.0, CW, "CW", &[]),
+        BandSegment::new(14070.0, 14112.0, Digital, "Digital", &[]),
+        BandSegment::new(14099.5, 14100.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(14112.0, 14350.0, Phone, "Phone", &[]),
+        BandSegment::new(18068.0, 18095.0, CW, "CW", &[]),
+        BandSegment::new(18095.0, 18111.0, Digital, "Digital", &[]),
+        BandSegment::new(18109.5, 18110.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(18111.0, 18168.0, Phone, "Phone", &[]),
+        BandSegment::new(21000.0, 21070.0, CW, "CW", &[]),
+        BandSegment::new(21070.0, 21151.0, Digital, "Digital", &[]),
+        BandSegment::new(21149.5, 21150.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(21151.0, 21450.0, Phone, "Phone", &[]),
+        BandSegment::new(24890.0, 24915.0, CW, "CW", &[]),
+        BandSegment::new(24915.0, 24931.0, Digital, "Digital", &[]),
+        BandSegment::new(24929.5, 24930.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(24931.0, 24990.0, Phone, "Phone", &[]),
+        BandSegment::new(28000.0, 28070.0, CW, "CW", &[]),
+        BandSegment::new(28070.0, 28190.0, Digital, "Digital", &[]),
+        BandSegment::new(28190.0, 28300.0, Beacon, "Beacons", &[]),
+        BandSegment::new(28300.0, 29510.0, Phone, "Phone", &[]),
+        BandSegment::new(29510.0, 29700.0, Phone, "FM", &[]).with_mode(Modes::FMN),
+        BandSegment::new(50000.0, 50100.0, CW, "CW", &[]),
+        BandSegment::new(50100.0, 50300.0, Phone, "Phone", &[]),
+        BandSegment::new(50300.0, 50400.0, Digital, "Digital", &[]),
+        BandSegment::new(50400.0, 50500.0, Beacon, "Beacons", &[]),
+        BandSegment::new(50500.0, 54000.0, Phone, "Phone", &[]),
+    ];
+
+    let region3 = vec![
+        BandSegment::new(135.7, 137.8, CW, "CW", &[]),
+        BandSegment::new(472.0, 479.0, CW, "CW", &[]),
+        BandSegment::new(1800.0, 1830.0, CW, "CW", &[]),
+        BandSegment::new(1830.0, 1840.0, Digital, "Digital", &[]),
+        BandSegment::new(1840.0, 2000.0, Phone, "Phone", &[]),
+        BandSegment::new(3500.0, 3535.0, CW, "CW", &[]),
+        BandSegment::new(3535.0, 3600.0, Digital, "Digital", &[]),
+        BandSegment::new(3600.0, 3900.0, Phone, "Phone", &[]),
+        BandSegment::new(7000.0, 7025.0, CW, "CW", &[]),
+        BandSegment::new(7025.0, 7040.0, Digital, "Digital", &[]),
+        BandSegment::new(7040.0, 7300.0, Phone, "Phone", &[]),
+        BandSegment::new(10100.0, 10130.0, CW, "CW", &[]),
+        BandSegment::new(10130.0, 10150.0, Digital, "Digital", &[]),
+        BandSegment::new(14000.0, 14070.0, CW, "CW", &[]),
+        BandSegment::new(14070.0, 14112.0, Digital, "Digital", &[]),
+        BandSegment::new(14099.5, 14100.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(14112.0, 14350.0, Phone, "Phone", &[]),
+        BandSegment::new(18068.0, 18095.0, CW, "CW", &[]),
+        BandSegment::new(18095.0, 18111.0, Digital, "Digital", &[]),
+        BandSegment::new(18109.5, 18110.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(18111.0, 18168.0, Phone, "Phone", &[]),
+        BandSegment::new(21000.0, 21070.0, CW, "CW", &[]),
+        BandSegment::new(21070.0, 21151.0, Digital, "Digital", &[]),
+        BandSegment::new(21149.5, 21150.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(21151.0, 21450.0, Phone, "Phone", &[]),
+        BandSegment::new(24890.0, 24915.0, CW, "CW", &[]),
+        BandSegment::new(24915.0, 24931.0, Digital, "Digital", &[]),
+        BandSegment::new(24929.5, 24930.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(24931.0, 24990.0, Phone, "Phone", &[]),
+        BandSegment::new(28000.0, 28070.0, CW, "CW", &[]),
+        BandSegment::new(28070.0, 28190.0, Digital, "Digital", &[]),
+        BandSegment::new(28190.0, 28225.0, Beacon, "Beacons", &[]),
+        BandSegment::new(28225.0, 29510.0, Phone, "Phone", &[]),
+        BandSegment::new(29510.0, 29700.0, Phone, "FM", &[]).with_mode(Modes::FMN),
+        BandSegment::new(50000.0, 50100.0, CW, "CW", &[]),
+        BandSegment::new(50100.0, 50500.0, Phone, "Phone", &[]),
+        BandSegment::new(50500.0, 54000.0, Phone, "Phone", &[]),
+    ];
+
+    let fcc = vec![
+        BandSegment::new(135.7, 137.8, CW, "CW", &[Extra, Advanced, General]),
+        BandSegment::new(472.0, 479.0, CW, "CW", &[Extra, Advanced, General]),
+        BandSegment::new(1800.0, 1840.0, CW, "CW", &[Extra, Advanced, General]),
+        BandSegment::new(1840.0, 2000.0, Phone, "Phone", &[Extra, Advanced, General]),
+        BandSegment::new(3500.0, 3525.0, CW, "Extra CW/Data", &[Extra]),
+        BandSegment::new(3525.0, 3570.0, CW, "CW", &[Extra, Advanced, General, Technician, Novice]),
+        BandSegment::new(3570.0, 3600.0, Digital, "Digital", &[Extra, Advanced, General]),
+        BandSegment::new(3600.0, 3700.0, Phone, "Extra Phone", &[Extra]),
+        BandSegment::new(3700.0, 3800.0, Phone, "Advanced Phone", &[Extra, Advanced]),
+        BandSegment::new(3800.0, 4000.0, Phone, "Phone", &[Extra, Advanced, General]),
+        BandSegment::new(5330.5, 5333.3, Phone, "Channel 1", &[Extra, Advanced, General]).with_mode(Modes::USB),
+        BandSegment::new(5346.5, 5349.3, Phone, "Channel 2", &[Extra, Advanced, General]).with_mode(Modes::USB),
+        BandSegment::new(5351.5, 5366.5, Digital, "CW/Data", &[Extra, Advanced, General]),
+        BandSegment::new(5371.5, 5374.3, Phone, "Channel 4", &[Extra, Advanced, General]).with_mode(Modes::USB),
+        BandSegment::new(5403.5, 5406.3, Phone, "Channel 5", &[Extra, Advanced, General]).with_mode(Modes::USB),
+        BandSegment::new(7000.0, 7025.0, CW, "Extra CW/Data", &[Extra]),
+        BandSegment::new(7025.0, 7070.0, CW, "CW", &[Extra, Advanced, General, Technician, Novice]),
+        BandSegment::new(7070.0, 7125.0, Digital, "Digital", &[Extra, Advanced, General]),
+        BandSegment::new(7125.0, 7175.0, Phone, "Advanced Phone", &[Extra, Advanced]),
+        BandSegment::new(7175.0, 7300.0, Phone, "Phone", &[Extra, Advanced, General]),
+        BandSegment::new(10100.0, 10130.0, CW, "CW", &[Extra, Advanced, General]),
+        BandSegment::new(10130.0, 10150.0, Digital, "Digital", &[Extra, Advanced, General]),
+        BandSegment::new(14000.0, 14025.0, CW, "Extra CW/Data", &[Extra]),
+        BandSegment::new(14025.0, 14070.0, CW, "CW", &[Extra, Advanced, General]),
+        BandSegment::new(14070.0, 14150.0, Digital, "Digital", &[Extra, Advanced, General]),
+        BandSegment::new(14099.5, 14100.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(14150.0, 14175.0, Phone, "Extra Phone", &[Extra]),
+        BandSegment::new(14175.0, 14225.0, Phone, "Advanced Phone", &[Extra, Advanced]),
+        BandSegment::new(14225.0, 14350.0, Phone, "Phone", &[Extra, Advanced, General]),
+        BandSegment::new(18068.0, 18095.0, CW, "CW", &[Extra, Advanced, General]),
+        BandSegment::new(18095.0, 18110.0, Digital, "Digital", &[Extra, Advanced, General]),
+        BandSegment::new(18109.5, 18110.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(18110.0, 18168.0, Phone, "Phone", &[Extra, Advanced, General]),
+        BandSegment::new(21000.0, 21025.0, CW, "Extra CW/Data", &[Extra]),
+        BandSegment::new(21025.0, 21070.0, CW, "CW", &[Extra, Advanced, General, Technician, Novice]),
+        BandSegment::new(21070.0, 21200.0, Digital, "Digital", &[Extra, Advanced, General]),
+        BandSegment::new(21149.5, 21150.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(21200.0, 21225.0, Phone, "Extra Phone", &[Extra]),
+        BandSegment::new(21225.0, 21275.0, Phone, "Advanced Phone", &[Extra, Advanced]),
+        BandSegment::new(21275.0, 21450.0, Phone, "Phone", &[Extra, Advanced, General]),
+        BandSegment::new(24890.0, 24915.0, CW, "CW", &[Extra, Advanced, General]),
+        BandSegment::new(24915.0, 24930.0, Digital, "Digital", &[Extra, Advanced, General]),
+        BandSegment::new(24929.5, 24930.5, Beacon, "NCDXF Beacon", &[]),
+        BandSegment::new(24930.0, 24990.0, Phone, "Phone", &[Extra, Advanced, General]),
+        BandSegment::new(28000.0, 28070.0, CW, "CW", &[Extra, Advanced, General, Technician, Novice]),
+        BandSegment::new(28070.0, 28200.0, Digital, "Digital", &[Extra, Advanced, General, Technician, Novice]),
+        BandSegment::new(28200.0, 28300.0, Beacon, "Beacons", &[Extra, Advanced, General, Technician, Novice]),
+        BandSegment::new(28300.0, 28500.0, Phone, "Novice Phone", &[Extra, Advanced, General, Technician, Novice]),
+        BandSegment::new(28500.0, 29510.0, Phone, "Phone", &[Extra, Advanced, General]),
+        BandSegment::new(29510.0, 29700.0, Phone, "FM", &[Extra, Advanced, General]).with_mode(Modes::FMN),
+        BandSegment::new(50000.0, 50100.0, CW, "CW", &[Extra, Advanced, General, Technician]),
+        BandSegment::new(50100.0, 50300.0, Phone, "Phone", &[Extra, Advanced, General, Technician]),
+        BandSegment::new(50300.0, 50400.0, Digital, "Digital", &[Extra, Advanced, General, Technician]),
+        BandSegment::new(50400.0, 50500.0, Beacon, "Beacons", &[Extra, Advanced, General, Technician]),
+        BandSegment::new(50500.0, 54000.0, Phone, "Phone", &[Extra, Advanced, General, Technician]),
+    ];
+
+    vec![
+        RegionPlan { region: Region::Region1, segments: region1 },
+        RegionPlan { region: Region::Region2, segments: region2 },
+        RegionPlan { region: Region::Region3, segments: region3 },
+        RegionPlan { region: Region::FCC, segments: fcc },
+    ]
+}
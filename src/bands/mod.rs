@@ -25,6 +25,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::modes::Modes;
 use crate::filters::Filters;
+use crate::noise::NoiseSettings;
+use crate::notches::Notch;
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -74,6 +76,65 @@ impl Bands {
 }
 
 
+pub const BAND_STACK_MIN: usize = 3;
+pub const BAND_STACK_MAX: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct BandStack {
+    pub frequency: f32,
+    pub mode: Modes,
+    pub filter: Filters,
+    pub ctun: bool,
+    pub ctun_frequency: f32,
+    pub zoom: i32,
+    pub pan: i32,
+    pub spectrum_low: f32,
+    pub spectrum_high: f32,
+    pub waterfall_low: f32,
+    pub waterfall_high: f32,
+}
+
+impl BandStack {
+    fn new(info: &BandInfo, frequency: f32, mode: Modes) -> Self {
+        BandStack {
+            frequency,
+            mode,
+            filter: info.filter,
+            ctun: false,
+            ctun_frequency: 0.0,
+            zoom: 1,
+            pan: 0,
+            spectrum_low: info.spectrum_low,
+            spectrum_high: info.spectrum_high,
+            waterfall_low: info.waterfall_low,
+            waterfall_high: info.waterfall_high,
+        }
+    }
+
+    // the band default followed by typical CW and digital starting points
+    pub fn defaults(info: &BandInfo) -> Vec<BandStack> {
+        let mut entries = vec![(info.current, info.mode)];
+        match info.band {
+            Bands::Band2200 => entries.extend([(135750.0, Modes::CWL), (136000.0, Modes::DIGU)]),
+            Bands::Band630 => entries.extend([(472500.0, Modes::CWL), (474200.0, Modes::DIGU)]),
+            Bands::Band160 => entries.extend([(1825000.0, Modes::CWL), (1840000.0, Modes::DIGU)]),
+            Bands::Band80 => entries.extend([(3525000.0, Modes::CWL), (3573000.0, Modes::DIGU)]),
+            Bands::Band60 => entries.extend([(5351500.0, Modes::CWU), (5357000.0, Modes::DIGU)]),
+            Bands::Band40 => entries.extend([(7025000.0, Modes::CWL), (7074000.0, Modes::DIGU)]),
+            Bands::Band30 => entries.extend([(10110000.0, Modes::CWU), (10136000.0, Modes::DIGU)]),
+            Bands::Band20 => entries.extend([(14025000.0, Modes::CWU), (14074000.0, Modes::DIGU)]),
+            Bands::Band17 => entries.extend([(18080000.0, Modes::CWU), (18100000.0, Modes::DIGU)]),
+            Bands::Band15 => entries.extend([(21025000.0, Modes::CWU), (21074000.0, Modes::DIGU)]),
+            Bands::Band12 => entries.extend([(24900000.0, Modes::CWU), (24915000.0, Modes::DIGU)]),
+            Bands::Band10 => entries.extend([(28025000.0, Modes::CWU), (28074000.0, Modes::DIGU)]),
+            Bands::Band6 => entries.extend([(50090000.0, Modes::CWU), (50313000.0, Modes::DIGU)]),
+            Bands::BandGEN => entries.extend([(6000000.0, Modes::AM), (9500000.0, Modes::AM)]),
+            Bands::BandWWV => entries.extend([(5000000.0, Modes::SAM), (15000000.0, Modes::SAM)]),
+        }
+        entries.iter().map(|(frequency, mode)| BandStack::new(info, *frequency, *mode)).collect()
+    }
+}
+
 // Def Serialize, Deserialize,ine a type for our callback function
 pub type BandClickCallback = Box<dyn Fn(usize)>;
 
@@ -91,30 +152,94 @@ pub struct BandInfo {
     pub waterfall_high: f32,
     pub mode: Modes,
     pub filter: Filters,
+    pub stack: Vec<BandStack>,
+    pub stack_index: usize,
+    pub squelch: bool,
+    pub squelch_level: f32,
+    pub noise: NoiseSettings,
+    pub notches: Vec<Notch>,
 }
 
 impl BandInfo {
     pub fn new() -> Vec<BandInfo> {
-        let data = vec![
-            BandInfo{ band: Bands::Band2200, label: String::from("2200"), low: 135700.0, high: 137800.0, current: 135750.0, filters: 0x00001000, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band630, label: String::from("630"), low: 472000.0, high: 479000.0, current: 472500.0, filters: 0x00001000, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band160, label: String::from("160"), low: 1800000.0, high: 2000000.0, current: 1900000.0, filters: 0x01800040, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band80, label: String::from("80"), low: 3500000.0, high: 3800000.0, current: 3750000.0, filters: 0x01400020, spectrum_low: -100.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band60, label: String::from("60"), low: 5330500.0, high: 5403500.0, current: 5365500.0,  filters: 0x01200020, spectrum_low: -110.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band40, label: String::from("40"), low: 7000000.0, high: 7300000.0, current: 7150000.0, filters: 0x01200010, spectrum_low: -110.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band30, label: String::from("30"), low: 10100000.0, high: 10150000.0, current: 10125000.0, filters: 0x01200010, spectrum_low: -110.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band20, label: String::from("20"), low: 14000000.0, high: 14350000.0, current: 14175000.0, filters: 0x01100002, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band17, label: String::from("17"), low: 18068000.0, high: 18168000.0, current: 18118000.0, filters: 0x81000002, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band15, label: String::from("15"), low: 21000000.0, high: 21450000.0, current: 21215000.0, filters: 0x81000002, spectrum_low: -130.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band12, label: String::from("12"), low: 24890000.0, high: 24990000.0, current: 24940000.0, filters: 0x41000004, spectrum_low: -130.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band10, label: String::from("10"), low: 28000000.0, high: 29700000.0, current: 28300000.0, filters: 0x41000004, spectrum_low: -130.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5},
-            BandInfo{ band: Bands::Band6, label: String::from("6"), low: 50000000.0, high: 54000000.0, current: 52000000.0, filters: 0x21000008, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5},
-            BandInfo{ band: Bands::BandGEN, label: String::from("GEN"), low: 100000.0, high: 62000000.0, current: 11700000.0, filters: 0x20001000, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::AM, filter: Filters::F3},
-            BandInfo{ band: Bands::BandWWV, label: String::from("WWV"), low: 10000000.0, high: 10000000.0, current: 10000000.0, filters: 0x20001000, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::SAM, filter: Filters::F3},
+        let mut data = vec![
+            BandInfo{ band: Bands::Band2200, label: String::from("2200"), low: 135700.0, high: 137800.0, current: 135750.0, filters: 0x00001000, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band630, label: String::from("630"), low: 472000.0, high: 479000.0, current: 472500.0, filters: 0x00001000, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band160, label: String::from("160"), low: 1800000.0, high: 2000000.0, current: 1900000.0, filters: 0x01800040, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band80, label: String::from("80"), low: 3500000.0, high: 3800000.0, current: 3750000.0, filters: 0x01400020, spectrum_low: -100.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band60, label: String::from("60"), low: 5330500.0, high: 5403500.0, current: 5365500.0,  filters: 0x01200020, spectrum_low: -110.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band40, label: String::from("40"), low: 7000000.0, high: 7300000.0, current: 7150000.0, filters: 0x01200010, spectrum_low: -110.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::LSB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band30, label: String::from("30"), low: 10100000.0, high: 10150000.0, current: 10125000.0, filters: 0x01200010, spectrum_low: -110.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band20, label: String::from("20"), low: 14000000.0, high: 14350000.0, current: 14175000.0, filters: 0x01100002, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band17, label: String::from("17"), low: 18068000.0, high: 18168000.0, current: 18118000.0, filters: 0x81000002, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band15, label: String::from("15"), low: 21000000.0, high: 21450000.0, current: 21215000.0, filters: 0x81000002, spectrum_low: -130.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band12, label: String::from("12"), low: 24890000.0, high: 24990000.0, current: 24940000.0, filters: 0x41000004, spectrum_low: -130.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band10, label: String::from("10"), low: 28000000.0, high: 29700000.0, current: 28300000.0, filters: 0x41000004, spectrum_low: -130.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::Band6, label: String::from("6"), low: 50000000.0, high: 54000000.0, current: 52000000.0, filters: 0x21000008, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::USB, filter: Filters::F5, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::BandGEN, label: String::from("GEN"), low: 100000.0, high: 62000000.0, current: 11700000.0, filters: 0x20001000, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::AM, filter: Filters::F3, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
+            BandInfo{ band: Bands::BandWWV, label: String::from("WWV"), low: 10000000.0, high: 10000000.0, current: 10000000.0, filters: 0x20001000, spectrum_low: -120.0, spectrum_high: -60.0, waterfall_low: -130.0, waterfall_high: -80.0, mode: Modes::SAM, filter: Filters::F3, stack: Vec::new(), stack_index: 0, squelch: false, squelch_level: 0.0, noise: NoiseSettings::default(), notches: Vec::new()},
         ];
+        for info in data.iter_mut() {
+            info.stack = BandStack::defaults(info);
+        }
         data
     }
 
+    // save a receiver state into the current stacking register, keeping the band defaults in step
+    pub fn save_stack(&mut self, entry: BandStack) {
+        self.current = entry.frequency;
+        self.mode = entry.mode;
+        self.filter = entry.filter;
+        self.spectrum_low = entry.spectrum_low;
+        self.spectrum_high = entry.spectrum_high;
+        self.waterfall_low = entry.waterfall_low;
+        self.waterfall_high = entry.waterfall_high;
+        if self.stack_index < self.stack.len() {
+            self.stack[self.stack_index] = entry;
+        }
+    }
+
+    // make a stacking register the band defaults
+    pub fn select_stack(&mut self, index: usize) {
+        if index >= self.stack.len() {
+            return;
+        }
+        self.stack_index = index;
+        let entry = self.stack[index];
+        self.current = entry.frequency;
+        self.mode = entry.mode;
+        self.filter = entry.filter;
+        self.spectrum_low = entry.spectrum_low;
+        self.spectrum_high = entry.spectrum_high;
+        self.waterfall_low = entry.waterfall_low;
+        self.waterfall_high = entry.waterfall_high;
+    }
+
+    // change the number of stacking registers, new ones copy the last
+    pub fn resize_stack(&mut self, entries: usize) {
+        if self.stack.is_empty() {
+            self.stack = BandStack::defaults(self);
+        }
+        let last = self.stack[self.stack.len() - 1];
+        self.stack.resize(entries, last);
+        if self.stack_index >= entries {
+            self.stack_index = 0;
+        }
+    }
+
+}
+
+// find the amateur band containing the frequency, BandGEN if it is outside all of them
+pub fn band_for_frequency(band_info: &Vec<BandInfo>, frequency: f32) -> Bands {
+    for info in band_info.iter() {
+        if info.band == Bands::BandGEN || info.band == Bands::BandWWV {
+            continue;
+        }
+        if frequency >= info.low && frequency <= info.high {
+            return info.band;
+        }
+    }
+    Bands::BandGEN
 }
 
 #[derive(Clone)]
@@ -15,13 +15,26 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use glib::ControlFlow::{Break, Continue};
+use glib::timeout_add_local;
+use gtk::glib::Propagation;
 use gtk::prelude::*;
-use gtk::{Adjustment, ApplicationWindow, Builder, Button, CheckButton, ComboBoxText, DropDown, Frame, Label, ListBox, ListBoxRow, Orientation, PositionType, Scale, ToggleButton, Window};
+use gtk::{gio, Adjustment, ApplicationWindow, Builder, Button, CheckButton, ComboBoxText, DropDown, Entry, FileDialog, Frame, Grid, Label, ListBox, ListBoxRow, Orientation, PositionType, Scale, SpinButton, ToggleButton, Window};
 
-use crate::bands::Bands;
+use crate::bands::{Bands, BAND_STACK_MIN, BAND_STACK_MAX};
 use crate::radio::{Keyer, RadioModels, RadioMutex};
 use crate::receiver::{AudioOutput};
 use crate::audio::*;
+use crate::midi::*;
+use crate::bandplan::*;
+use crate::modes::Modes;
+use crate::txprofiles::*;
+use crate::transmitter::{SwrProtection, Transmitter};
+
+use std::time::{Duration, Instant};
+
+// the longest the calibration carrier is keyed
+const PA_CALIBRATION_LIMIT: Duration = Duration::from_secs(10);
 
 pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMutex) -> Window {
 
@@ -259,8 +272,42 @@ pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMu
     let mic_ptt = r.mic_ptt;
     let mic_bias_ring = r.mic_bias_ring;
     let mic_bias_enable = r.mic_bias_enable;
+    let vox_hang = r.vox_hang;
+    let anti_vox = r.anti_vox;
+    let anti_vox_gain = r.anti_vox_gain;
     drop(r);
 
+    let vox_hang_adjustment: Adjustment = builder
+            .object("vox_hang_adjustment")
+            .expect("Could not get object `vox_hang_adjustment` from builder.");
+    vox_hang_adjustment.set_value(vox_hang.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    vox_hang_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.vox_hang = adjustment.value() as i32;
+    });
+
+    let anti_vox_check_button: CheckButton = builder
+            .object("anti_vox_check_button")
+            .expect("Could not get object `anti_vox_check_button` from builder.");
+    anti_vox_check_button.set_active(anti_vox);
+    let radio_mutex_clone = radio_mutex.clone();
+    anti_vox_check_button.connect_toggled(move |button| {
+        let is_active = button.is_active();
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.anti_vox = is_active;
+    });
+
+    let anti_vox_gain_adjustment: Adjustment = builder
+            .object("anti_vox_gain_adjustment")
+            .expect("Could not get object `anti_vox_gain_adjustment` from builder.");
+    anti_vox_gain_adjustment.set_value(anti_vox_gain.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    anti_vox_gain_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.anti_vox_gain = adjustment.value() as f32;
+    });
+
     let mic_boost_check_button: CheckButton = builder
             .object("mic_boost_check_button")
             .expect("Could not get object `mic_boost_check_button` from builder.");
@@ -350,6 +397,101 @@ pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMu
         r.receiver[0].update_waterfall_average(r.receiver[0].channel);
     }); 
 
+    let r = radio_mutex.radio.lock().unwrap();
+    let band_stack_entries = r.receiver[0].band_info[0].stack.len();
+    drop(r);
+    let band_stack_adjustment: Adjustment = builder
+            .object("band_stack_adjustment")
+            .expect("Could not get object `band_stack_adjustment` from builder.");
+    band_stack_adjustment.set_lower(BAND_STACK_MIN as f64);
+    band_stack_adjustment.set_upper(BAND_STACK_MAX as f64);
+    band_stack_adjustment.set_value(band_stack_entries as f64);
+    let radio_mutex_clone = radio_mutex.clone();
+    band_stack_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        let entries = adjustment.value() as usize;
+        for rx in 0..r.receiver.len() {
+            for info in r.receiver[rx].band_info.iter_mut() {
+                info.resize_stack(entries);
+            }
+        }
+    });
+
+    let r = radio_mutex.radio.lock().unwrap();
+        let band_plan_region = r.band_plan.region;
+        let band_plan_license = r.band_plan.license;
+        let band_plan_show = r.band_plan.show;
+        let band_plan_follow = r.band_plan.follow;
+        let band_plan_inhibit_tx = r.band_plan.inhibit_tx;
+    drop(r);
+
+    let band_plan_region_dropdown: DropDown = builder
+            .object("band_plan_region_dropdown")
+            .expect("Could not get object `band_plan_region_dropdown` from builder.");
+    band_plan_region_dropdown.set_selected(band_plan_region.to_u32());
+    let radio_mutex_clone = radio_mutex.clone();
+    band_plan_region_dropdown.connect_selected_notify(move |dropdown| {
+        let region = dropdown.selected();
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.band_plan.region = Region::from_u32(region).expect("Invalid Band Plan Region");
+    });
+
+    let band_plan_license_dropdown: DropDown = builder
+            .object("band_plan_license_dropdown")
+            .expect("Could not get object `band_plan_license_dropdown` from builder.");
+    band_plan_license_dropdown.set_selected(band_plan_license.to_u32());
+    let radio_mutex_clone = radio_mutex.clone();
+    band_plan_license_dropdown.connect_selected_notify(move |dropdown| {
+        let license = dropdown.selected();
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.band_plan.license = LicenseClass::from_u32(license).expect("Invalid License Class");
+    });
+
+    let band_plan_reload_button: Button = builder
+            .object("band_plan_reload_button")
+            .expect("Could not get object `band_plan_reload_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    band_plan_reload_button.connect_clicked(move |_| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.band_plan.load();
+    });
+
+    let band_plan_show_check_button: CheckButton = builder
+            .object("band_plan_show_check_button")
+            .expect("Could not get object `band_plan_show_check_button` from builder.");
+    band_plan_show_check_button.set_active(band_plan_show);
+    let radio_mutex_clone = radio_mutex.clone();
+    band_plan_show_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.band_plan.show = button.is_active();
+    });
+
+    let band_plan_follow_check_button: CheckButton = builder
+            .object("band_plan_follow_check_button")
+            .expect("Could not get object `band_plan_follow_check_button` from builder.");
+    band_plan_follow_check_button.set_active(band_plan_follow);
+    let radio_mutex_clone = radio_mutex.clone();
+    band_plan_follow_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.band_plan.follow = button.is_active();
+    });
+
+    let band_plan_inhibit_tx_check_button: CheckButton = builder
+            .object("band_plan_inhibit_tx_check_button")
+            .expect("Could not get object `band_plan_inhibit_tx_check_button` from builder.");
+    band_plan_inhibit_tx_check_button.set_active(band_plan_inhibit_tx);
+    let radio_mutex_clone = radio_mutex.clone();
+    band_plan_inhibit_tx_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.band_plan.inhibit_tx = button.is_active();
+    });
+
+    let band_plan_file_label: Label = builder
+            .object("band_plan_file_label")
+            .expect("Could not get object `band_plan_file_label` from builder.");
+    let file = format!("Band plan file: {}", BandPlan::file_path().display());
+    band_plan_file_label.set_label(&file);
+
 
     // PA Calibration
 
@@ -522,6 +664,257 @@ pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMu
         r.transmitter.pa_calibration[Bands::Band6.to_usize()] = adjustment.value() as f32;
     }); 
 
+    // PA calibration wizard
+    let pa_adjustments = vec![
+        pa_2200_adjustment, pa_630_adjustment, pa_160_adjustment, pa_80_adjustment,
+        pa_60_adjustment, pa_40_adjustment, pa_30_adjustment, pa_20_adjustment,
+        pa_17_adjustment, pa_15_adjustment, pa_12_adjustment, pa_10_adjustment,
+        pa_6_adjustment,
+    ];
+
+    let r = radio_mutex.radio.lock().unwrap();
+        let max_power = r.transmitter.max_power;
+    drop(r);
+    let max_power_adjustment: Adjustment = builder
+            .object("max_power_adjustment")
+            .expect("Could not get object `max_power_adjustment` from builder.");
+    max_power_adjustment.set_value(max_power.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    max_power_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.max_power = adjustment.value() as f32;
+    });
+
+    let pa_wizard_drive_adjustment: Adjustment = builder
+            .object("pa_wizard_drive_adjustment")
+            .expect("Could not get object `pa_wizard_drive_adjustment` from builder.");
+    let pa_wizard_band_label: Label = builder
+            .object("pa_wizard_band_label")
+            .expect("Could not get object `pa_wizard_band_label` from builder.");
+    let pa_wizard_power_label: Label = builder
+            .object("pa_wizard_power_label")
+            .expect("Could not get object `pa_wizard_power_label` from builder.");
+
+    let pa_wizard_key_button: ToggleButton = builder
+            .object("pa_wizard_key_button")
+            .expect("Could not get object `pa_wizard_key_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let pa_wizard_drive_adjustment_clone = pa_wizard_drive_adjustment.clone();
+    pa_wizard_key_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if button.is_active() {
+            let level = (pa_wizard_drive_adjustment_clone.value() * 255.0 / 100.0) as u8;
+            if !r.start_pa_calibration(level) {
+                drop(r);
+                button.set_active(false);
+            }
+        } else if r.pa_calibration_level.is_some() {
+            r.stop_pa_calibration();
+        }
+    });
+
+    let pa_wizard_measure_button: Button = builder
+            .object("pa_wizard_measure_button")
+            .expect("Could not get object `pa_wizard_measure_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let pa_wizard_key_button_clone = pa_wizard_key_button.clone();
+    let pa_wizard_power_label_clone = pa_wizard_power_label.clone();
+    pa_wizard_measure_button.connect_clicked(move |_| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        let level = match r.pa_calibration_level {
+            Some(level) => level,
+            None => return,
+        };
+        let band = r.tx_band();
+        let power = r.transmitter.forward_power();
+        r.stop_pa_calibration();
+        drop(r);
+        if power <= 0.0 {
+            pa_wizard_power_label_clone.set_label("No forward power measured");
+        } else if band < pa_adjustments.len() {
+            // the adjustment handler saves the new calibration
+            let gain = Transmitter::pa_gain(level, power) as f64;
+            let adjustment = &pa_adjustments[band];
+            if gain < adjustment.lower() || gain > adjustment.upper() {
+                pa_wizard_power_label_clone.set_label(&format!("Measured {:.1}W, calibration {:.2} is out of range, not saved", power, gain));
+            } else {
+                adjustment.set_value(gain);
+                pa_wizard_power_label_clone.set_label(&format!("Measured {:.1}W, calibration {:.2}", power, adjustment.value()));
+            }
+        }
+        pa_wizard_key_button_clone.set_active(false);
+    });
+
+    // never leave the carrier keyed without the dialog
+    let radio_mutex_clone = radio_mutex.clone();
+    window.connect_close_request(move |_| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if r.pa_calibration_level.is_some() {
+            r.stop_pa_calibration();
+        }
+        Propagation::Proceed
+    });
+
+    let window_clone = window.clone();
+    let radio_mutex_clone = radio_mutex.clone();
+    let mut keyed: Option<Instant> = None;
+    timeout_add_local(std::time::Duration::from_millis(200), move || {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if !window_clone.is_visible() {
+            if r.pa_calibration_level.is_some() {
+                r.stop_pa_calibration();
+            }
+            return Break;
+        }
+        if r.pa_calibration_level.is_some() {
+            let started = *keyed.get_or_insert_with(Instant::now);
+            if started.elapsed() >= PA_CALIBRATION_LIMIT {
+                r.stop_pa_calibration();
+            }
+        }
+        let calibrating = r.pa_calibration_level.is_some();
+        if !calibrating {
+            keyed = None;
+        }
+        let band = r.tx_band();
+        let band_label = r.receiver[0].band_info[band].label.clone();
+        let power = r.transmitter.forward_power();
+        drop(r);
+        pa_wizard_band_label.set_label(&format!("Band: {}", band_label));
+        if calibrating {
+            pa_wizard_power_label.set_label(&format!("Forward: {:.1}W", power));
+        } else if pa_wizard_key_button.is_active() {
+            pa_wizard_key_button.set_active(false);
+        }
+        Continue
+    });
+
+    // Tune
+    let r = radio_mutex.radio.lock().unwrap();
+        let tune_pulsed = r.transmitter.tune_pulsed;
+        let tune_timeout = r.transmitter.tune_timeout;
+        let tune_atu = r.transmitter.tune_atu;
+        let tune_drive = r.transmitter.tune_drive.clone();
+        let max_power = r.transmitter.max_power;
+        let band_labels: Vec<String> = r.receiver[0].band_info.iter().map(|info| info.label.clone()).collect();
+    drop(r);
+
+    let tune_pulsed_check_button: CheckButton = builder
+            .object("tune_pulsed_check_button")
+            .expect("Could not get object `tune_pulsed_check_button` from builder.");
+    tune_pulsed_check_button.set_active(tune_pulsed);
+    let radio_mutex_clone = radio_mutex.clone();
+    tune_pulsed_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.tune_pulsed = button.is_active();
+    });
+
+    let tune_timeout_adjustment: Adjustment = builder
+            .object("tune_timeout_adjustment")
+            .expect("Could not get object `tune_timeout_adjustment` from builder.");
+    tune_timeout_adjustment.set_value(tune_timeout.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    tune_timeout_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.tune_timeout = adjustment.value() as u32;
+    });
+
+    let tune_atu_check_button: CheckButton = builder
+            .object("tune_atu_check_button")
+            .expect("Could not get object `tune_atu_check_button` from builder.");
+    tune_atu_check_button.set_active(tune_atu);
+    let radio_mutex_clone = radio_mutex.clone();
+    tune_atu_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.tune_atu = button.is_active();
+        r.updated = true;
+    });
+
+    let tune_drive_grid: Grid = builder
+            .object("tune_drive_grid")
+            .expect("Could not get object `tune_drive_grid` from builder.");
+    for band in 0..=Bands::Band6.to_usize() {
+        let label = Label::new(Some(&format!("{}: ", band_labels[band])));
+        label.set_xalign(1.0);
+        tune_drive_grid.attach(&label, ((band % 5) * 2) as i32, (band / 5) as i32, 1, 1);
+        let spin_button = SpinButton::with_range(0.0, max_power.into(), 1.0);
+        spin_button.set_value(tune_drive[band].into());
+        let radio_mutex_clone = radio_mutex.clone();
+        spin_button.connect_value_changed(move |spin_button| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.transmitter.tune_drive[band] = spin_button.value() as f32;
+        });
+        tune_drive_grid.attach(&spin_button, ((band % 5) * 2 + 1) as i32, (band / 5) as i32, 1, 1);
+    }
+
+    // Protection
+    let r = radio_mutex.radio.lock().unwrap();
+        let tx_timeout = r.tx_timeout;
+        let swr_protection = r.transmitter.swr_protection;
+        let swr_reduced_drive = r.transmitter.swr_reduced_drive;
+        let swr_limit = r.transmitter.swr_limit.clone();
+        let reflected_limit = r.transmitter.reflected_limit.clone();
+    drop(r);
+
+    let tx_timeout_adjustment: Adjustment = builder
+            .object("tx_timeout_adjustment")
+            .expect("Could not get object `tx_timeout_adjustment` from builder.");
+    tx_timeout_adjustment.set_value(tx_timeout.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    tx_timeout_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.tx_timeout = adjustment.value() as u32;
+    });
+
+    let swr_protection_dropdown: DropDown = builder
+            .object("swr_protection_dropdown")
+            .expect("Could not get object `swr_protection_dropdown` from builder.");
+    swr_protection_dropdown.set_selected(swr_protection.to_u32());
+    let radio_mutex_clone = radio_mutex.clone();
+    swr_protection_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.swr_protection = SwrProtection::from_u32(dropdown.selected()).unwrap_or(SwrProtection::DropTx);
+    });
+
+    let swr_reduced_drive_adjustment: Adjustment = builder
+            .object("swr_reduced_drive_adjustment")
+            .expect("Could not get object `swr_reduced_drive_adjustment` from builder.");
+    swr_reduced_drive_adjustment.set_value(swr_reduced_drive.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    swr_reduced_drive_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.swr_reduced_drive = adjustment.value() as f32;
+    });
+
+    let swr_limit_grid: Grid = builder
+            .object("swr_limit_grid")
+            .expect("Could not get object `swr_limit_grid` from builder.");
+    for band in 0..=Bands::Band6.to_usize() {
+        let row = (band / 3) as i32;
+        let column = ((band % 3) * 3) as i32;
+        let label = Label::new(Some(&format!("{}: ", band_labels[band])));
+        label.set_xalign(1.0);
+        swr_limit_grid.attach(&label, column, row, 1, 1);
+        let swr_spin_button = SpinButton::with_range(1.5, 10.0, 0.1);
+        swr_spin_button.set_digits(1);
+        swr_spin_button.set_value(swr_limit[band].into());
+        let radio_mutex_clone = radio_mutex.clone();
+        swr_spin_button.connect_value_changed(move |spin_button| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.transmitter.swr_limit[band] = spin_button.value() as f32;
+        });
+        swr_limit_grid.attach(&swr_spin_button, column + 1, row, 1, 1);
+        let reflected_spin_button = SpinButton::with_range(0.1, 500.0, 0.1);
+        reflected_spin_button.set_digits(1);
+        reflected_spin_button.set_value(reflected_limit[band].into());
+        let radio_mutex_clone = radio_mutex.clone();
+        reflected_spin_button.connect_value_changed(move |spin_button| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.transmitter.reflected_limit[band] = spin_button.value() as f32;
+        });
+        swr_limit_grid.attach(&reflected_spin_button, column + 2, row, 1, 1);
+    }
+
     // Radio
 
     let r = radio_mutex.radio.lock().unwrap();
@@ -583,6 +976,16 @@ pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMu
         let cw_keyer_internal = r.cw_keyer_internal;
         let cw_keys_reversed = r.cw_keys_reversed;
         let cw_breakin = r.cw_breakin;
+        let cw_keyer_spacing = r.cw_keyer_spacing;
+        let cw_keyer_speed = r.cw_keyer_speed;
+        let cw_keyer_weight = r.cw_keyer_weight;
+        let cw_keyer_ptt_delay = r.cw_keyer_ptt_delay;
+        let cw_keyer_hang_time = r.cw_keyer_hang_time;
+        let cw_keyer_sidetone_volume = r.cw_keyer_sidetone_volume;
+        let cw_keyer_sidetone_frequency = r.cw_keyer_sidetone_frequency;
+        let cw_keyer_keyboard = r.keyer.keyboard;
+        let cw_keyer_serial = r.keyer.serial;
+        let cw_keyer_serial_device = r.keyer.serial_device.clone();
     drop(r);
 
     let keyer_mode_dropdown: DropDown = builder
@@ -633,6 +1036,125 @@ pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMu
         r.updated = true;
     });
 
+    let cw_keyer_spacing_check_button: CheckButton = builder
+            .object("cw_keyer_spacing_check_button")
+            .expect("Could not get object `cw_keyer_spacing_check_button` from builder.");
+    cw_keyer_spacing_check_button.set_active(cw_keyer_spacing != 0);
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_spacing_check_button.connect_toggled(move |button| {
+        let is_active = button.is_active();
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.cw_keyer_spacing = is_active as i32;
+        r.updated = true;
+    });
+
+    let cw_keyer_speed_adjustment: Adjustment = builder
+            .object("cw_keyer_speed_adjustment")
+            .expect("Could not get object `cw_keyer_speed_adjustment` from builder.");
+    cw_keyer_speed_adjustment.set_value(cw_keyer_speed.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_speed_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.cw_keyer_speed = adjustment.value() as i32;
+        r.updated = true;
+    });
+
+    let cw_keyer_weight_adjustment: Adjustment = builder
+            .object("cw_keyer_weight_adjustment")
+            .expect("Could not get object `cw_keyer_weight_adjustment` from builder.");
+    cw_keyer_weight_adjustment.set_value(cw_keyer_weight.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_weight_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.cw_keyer_weight = adjustment.value() as i32;
+        r.updated = true;
+    });
+
+    let cw_keyer_ptt_delay_adjustment: Adjustment = builder
+            .object("cw_keyer_ptt_delay_adjustment")
+            .expect("Could not get object `cw_keyer_ptt_delay_adjustment` from builder.");
+    cw_keyer_ptt_delay_adjustment.set_value(cw_keyer_ptt_delay.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_ptt_delay_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.cw_keyer_ptt_delay = adjustment.value() as i32;
+        r.updated = true;
+    });
+
+    let cw_keyer_hang_time_adjustment: Adjustment = builder
+            .object("cw_keyer_hang_time_adjustment")
+            .expect("Could not get object `cw_keyer_hang_time_adjustment` from builder.");
+    cw_keyer_hang_time_adjustment.set_value(cw_keyer_hang_time.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_hang_time_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.cw_keyer_hang_time = adjustment.value() as i32;
+        r.updated = true;
+    });
+
+    let cw_keyer_sidetone_volume_adjustment: Adjustment = builder
+            .object("cw_keyer_sidetone_volume_adjustment")
+            .expect("Could not get object `cw_keyer_sidetone_volume_adjustment` from builder.");
+    cw_keyer_sidetone_volume_adjustment.set_value(cw_keyer_sidetone_volume.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_sidetone_volume_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.cw_keyer_sidetone_volume = adjustment.value() as i32;
+        r.updated = true;
+    });
+
+    let cw_keyer_sidetone_frequency_adjustment: Adjustment = builder
+            .object("cw_keyer_sidetone_frequency_adjustment")
+            .expect("Could not get object `cw_keyer_sidetone_frequency_adjustment` from builder.");
+    cw_keyer_sidetone_frequency_adjustment.set_value(cw_keyer_sidetone_frequency.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_sidetone_frequency_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.cw_keyer_sidetone_frequency = adjustment.value() as i32;
+        r.updated = true;
+    });
+
+    let cw_keyer_keyboard_check_button: CheckButton = builder
+            .object("cw_keyer_keyboard_check_button")
+            .expect("Could not get object `cw_keyer_keyboard_check_button` from builder.");
+    cw_keyer_keyboard_check_button.set_active(cw_keyer_keyboard);
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_keyboard_check_button.connect_toggled(move |button| {
+        let is_active = button.is_active();
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.keyer.keyboard = is_active;
+        r.keyer.keyboard_dot = false;
+        r.keyer.keyboard_dash = false;
+    });
+
+    let cw_keyer_serial_device_entry: Entry = builder
+            .object("cw_keyer_serial_device_entry")
+            .expect("Could not get object `cw_keyer_serial_device_entry` from builder.");
+    cw_keyer_serial_device_entry.set_text(&cw_keyer_serial_device);
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_serial_device_entry.connect_changed(move |entry| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.keyer.serial_device = entry.text().to_string();
+    });
+
+    let cw_keyer_serial_check_button: CheckButton = builder
+            .object("cw_keyer_serial_check_button")
+            .expect("Could not get object `cw_keyer_serial_check_button` from builder.");
+    cw_keyer_serial_check_button.set_active(cw_keyer_serial);
+    let radio_mutex_clone = radio_mutex.clone();
+    cw_keyer_serial_check_button.connect_toggled(move |button| {
+        let is_active = button.is_active();
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.keyer.serial = is_active;
+        if is_active {
+            if let Err(e) = r.keyer.open_serial() {
+                eprintln!("keyer::open_serial failed: {}", e);
+            }
+        } else {
+            r.keyer.close_serial();
+        }
+    });
+
     // Noise
     let r = radio_mutex.radio.lock().unwrap();
         let taps = r.receiver[0].nr_taps;
@@ -769,38 +1291,10 @@ pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMu
     });
 
     // Notch
-    let r = radio_mutex.radio.lock().unwrap();
-    //let notch = r.notch;
-    //let notches = &r.notches;
-    //drop(r);
     let notch_list: ListBox = builder
             .object("notch_list")
             .expect("Could not get object `notch_list` from builder.");
-
-    for i in 0..r.notches.len() {
-        let row = ListBoxRow::new();
-        let hbox = gtk::Box::new(Orientation::Horizontal, 10);
-        let id = format!("{:?}", i);
-        let label_id = Label::new(Some(&id));
-        label_id.set_xalign(0.0); // Align text to the left
-        hbox.append(&label_id);
-        let frequency = format!("{:?}", r.notches[i].frequency);
-        let label_frequency = Label::new(Some(&frequency));
-        label_frequency.set_xalign(0.0); // Align text to the left
-        hbox.append(&label_frequency);
-        let width = format!("{:?}", r.notches[i].width);
-        let label_width = Label::new(Some(&width));
-        label_width.set_xalign(0.0); // Align text to the left
-        hbox.append(&label_width);
-        let active = format!("{:?}", r.notches[i].active);
-        let label_active = Label::new(Some(&active));
-        label_active.set_xalign(0.0); // Align text to the left
-        hbox.append(&label_active);
-        row.set_child(Some(&hbox));
-
-        notch_list.append(&row);
-    }
-    drop(r);
+    populate_notch_list(&notch_list, radio_mutex);
 
     // Receiver
     let r = radio_mutex.radio.lock().unwrap();
@@ -1051,6 +1545,397 @@ pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMu
         r.receiver[rx].set_equalizer_values();
     });
 
+    // TX Audio
+    let r = radio_mutex.radio.lock().unwrap();
+    let tx_equalizer_enabled = r.transmitter.equalizer_enabled;
+    let tx_preamp = r.transmitter.equalizer_preamp as f64;
+    let tx_low = r.transmitter.equalizer_low as f64;
+    let tx_mid = r.transmitter.equalizer_mid as f64;
+    let tx_high = r.transmitter.equalizer_high as f64;
+    let compressor = r.transmitter.compressor;
+    let compressor_gain = r.transmitter.compressor_gain as f64;
+    let leveler = r.transmitter.leveler;
+    let leveler_top = r.transmitter.leveler_top as f64;
+    let leveler_decay = r.transmitter.leveler_decay as f64;
+    let alc_attack = r.transmitter.alc_attack as f64;
+    let alc_decay = r.transmitter.alc_decay as f64;
+    let alc_hang = r.transmitter.alc_hang as f64;
+    let cessb = r.transmitter.cessb;
+    let am_carrier_level = r.transmitter.am_carrier_level as f64;
+    drop(r);
+
+    let tx_equalizer_enabled_check_button: CheckButton = builder
+            .object("tx_equalizer_enabled_check_button")
+            .expect("Could not get object `tx_equalizer_enabled_check_button` from builder.");
+    tx_equalizer_enabled_check_button.set_active(tx_equalizer_enabled);
+    let radio_mutex_clone = radio_mutex.clone();
+    tx_equalizer_enabled_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.equalizer_enabled = button.is_active();
+        r.transmitter.enable_equalizer();
+    });
+
+    let tx_preamp_scale: Scale = builder
+            .object("tx_preamp_scale")
+            .expect("Could not get object `tx_preamp_scale` from builder.");
+    tx_preamp_scale.add_mark(-12.0, PositionType::Left, Some("-12dB"));
+    tx_preamp_scale.add_mark(0.0, PositionType::Left, Some("0dB"));
+    tx_preamp_scale.add_mark(15.0, PositionType::Left, Some("15dB"));
+
+    let tx_low_scale: Scale = builder
+            .object("tx_low_scale")
+            .expect("Could not get object `tx_low_scale` from builder.");
+    tx_low_scale.add_mark(-12.0, PositionType::Left, Some("-12dB"));
+    tx_low_scale.add_mark(0.0, PositionType::Left, Some("0dB"));
+    tx_low_scale.add_mark(15.0, PositionType::Left, Some("15dB"));
+
+    let tx_mid_scale: Scale = builder
+            .object("tx_mid_scale")
+            .expect("Could not get object `tx_mid_scale` from builder.");
+    tx_mid_scale.add_mark(-12.0, PositionType::Left, Some("-12dB"));
+    tx_mid_scale.add_mark(0.0, PositionType::Left, Some("0dB"));
+    tx_mid_scale.add_mark(15.0, PositionType::Left, Some("15dB"));
+
+    let tx_high_scale: Scale = builder
+            .object("tx_high_scale")
+            .expect("Could not get object `tx_high_scale` from builder.");
+    tx_high_scale.add_mark(-12.0, PositionType::Left, Some("-12dB"));
+    tx_high_scale.add_mark(0.0, PositionType::Left, Some("0dB"));
+    tx_high_scale.add_mark(15.0, PositionType::Left, Some("15dB"));
+
+    let tx_preamp_adjustment: Adjustment = builder
+            .object("tx_preamp_adjustment")
+            .expect("Could not get object `tx_preamp_adjustment` from builder.");
+    tx_preamp_adjustment.set_value(tx_preamp);
+    let radio_mutex_clone = radio_mutex.clone();
+    tx_preamp_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.equalizer_preamp = adjustment.value() as f32;
+        r.transmitter.set_equalizer_values();
+    });
+
+    let tx_low_adjustment: Adjustment = builder
+            .object("tx_low_adjustment")
+            .expect("Could not get object `tx_low_adjustment` from builder.");
+    tx_low_adjustment.set_value(tx_low);
+    let radio_mutex_clone = radio_mutex.clone();
+    tx_low_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.equalizer_low = adjustment.value() as f32;
+        r.transmitter.set_equalizer_values();
+    });
+
+    let tx_mid_adjustment: Adjustment = builder
+            .object("tx_mid_adjustment")
+            .expect("Could not get object `tx_mid_adjustment` from builder.");
+    tx_mid_adjustment.set_value(tx_mid);
+    let radio_mutex_clone = radio_mutex.clone();
+    tx_mid_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.equalizer_mid = adjustment.value() as f32;
+        r.transmitter.set_equalizer_values();
+    });
+
+    let tx_high_adjustment: Adjustment = builder
+            .object("tx_high_adjustment")
+            .expect("Could not get object `tx_high_adjustment` from builder.");
+    tx_high_adjustment.set_value(tx_high);
+    let radio_mutex_clone = radio_mutex.clone();
+    tx_high_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.equalizer_high = adjustment.value() as f32;
+        r.transmitter.set_equalizer_values();
+    });
+
+    let compressor_check_button: CheckButton = builder
+            .object("compressor_check_button")
+            .expect("Could not get object `compressor_check_button` from builder.");
+    compressor_check_button.set_active(compressor);
+    let radio_mutex_clone = radio_mutex.clone();
+    compressor_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.compressor = button.is_active();
+        r.transmitter.set_compressor();
+    });
+
+    let compressor_gain_adjustment: Adjustment = builder
+            .object("compressor_gain_adjustment")
+            .expect("Could not get object `compressor_gain_adjustment` from builder.");
+    compressor_gain_adjustment.set_value(compressor_gain);
+    let radio_mutex_clone = radio_mutex.clone();
+    compressor_gain_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.compressor_gain = adjustment.value() as f32;
+        r.transmitter.set_compressor();
+    });
+
+    let leveler_check_button: CheckButton = builder
+            .object("leveler_check_button")
+            .expect("Could not get object `leveler_check_button` from builder.");
+    leveler_check_button.set_active(leveler);
+    let radio_mutex_clone = radio_mutex.clone();
+    leveler_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.leveler = button.is_active();
+        r.transmitter.set_leveler();
+    });
+
+    let leveler_top_adjustment: Adjustment = builder
+            .object("leveler_top_adjustment")
+            .expect("Could not get object `leveler_top_adjustment` from builder.");
+    leveler_top_adjustment.set_value(leveler_top);
+    let radio_mutex_clone = radio_mutex.clone();
+    leveler_top_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.leveler_top = adjustment.value() as f32;
+        r.transmitter.set_leveler();
+    });
+
+    let leveler_decay_adjustment: Adjustment = builder
+            .object("leveler_decay_adjustment")
+            .expect("Could not get object `leveler_decay_adjustment` from builder.");
+    leveler_decay_adjustment.set_value(leveler_decay);
+    let radio_mutex_clone = radio_mutex.clone();
+    leveler_decay_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.leveler_decay = adjustment.value() as i32;
+        r.transmitter.set_leveler();
+    });
+
+    let alc_attack_adjustment: Adjustment = builder
+            .object("alc_attack_adjustment")
+            .expect("Could not get object `alc_attack_adjustment` from builder.");
+    alc_attack_adjustment.set_value(alc_attack);
+    let radio_mutex_clone = radio_mutex.clone();
+    alc_attack_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.alc_attack = adjustment.value() as i32;
+        r.transmitter.set_alc();
+    });
+
+    let alc_decay_adjustment: Adjustment = builder
+            .object("alc_decay_adjustment")
+            .expect("Could not get object `alc_decay_adjustment` from builder.");
+    alc_decay_adjustment.set_value(alc_decay);
+    let radio_mutex_clone = radio_mutex.clone();
+    alc_decay_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.alc_decay = adjustment.value() as i32;
+        r.transmitter.set_alc();
+    });
+
+    let alc_hang_adjustment: Adjustment = builder
+            .object("alc_hang_adjustment")
+            .expect("Could not get object `alc_hang_adjustment` from builder.");
+    alc_hang_adjustment.set_value(alc_hang);
+    let radio_mutex_clone = radio_mutex.clone();
+    alc_hang_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.alc_hang = adjustment.value() as i32;
+        r.transmitter.set_alc();
+    });
+
+    let cessb_check_button: CheckButton = builder
+            .object("cessb_check_button")
+            .expect("Could not get object `cessb_check_button` from builder.");
+    cessb_check_button.set_active(cessb);
+    let radio_mutex_clone = radio_mutex.clone();
+    cessb_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.cessb = button.is_active();
+        r.transmitter.set_cessb();
+    });
+
+    let am_carrier_level_adjustment: Adjustment = builder
+            .object("am_carrier_level_adjustment")
+            .expect("Could not get object `am_carrier_level_adjustment` from builder.");
+    am_carrier_level_adjustment.set_value(am_carrier_level);
+    let radio_mutex_clone = radio_mutex.clone();
+    am_carrier_level_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.am_carrier_level = adjustment.value() as f32;
+        r.transmitter.set_am_carrier_level();
+    });
+
+    // TX Profiles
+    let tx_profile_list: ListBox = builder
+            .object("tx_profile_list")
+            .expect("Could not get object `tx_profile_list` from builder.");
+    let tx_profile_mode_grid: Grid = builder
+            .object("tx_profile_mode_grid")
+            .expect("Could not get object `tx_profile_mode_grid` from builder.");
+    populate_tx_profiles(&tx_profile_list, &tx_profile_mode_grid, radio_mutex);
+
+    let tx_profile_name_entry: Entry = builder
+            .object("tx_profile_name_entry")
+            .expect("Could not get object `tx_profile_name_entry` from builder.");
+    let tx_profile_save_button: Button = builder
+            .object("tx_profile_save_button")
+            .expect("Could not get object `tx_profile_save_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let tx_profile_list_clone = tx_profile_list.clone();
+    let tx_profile_mode_grid_clone = tx_profile_mode_grid.clone();
+    tx_profile_save_button.connect_clicked(move |_| {
+        let name = tx_profile_name_entry.text().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        let profile = TxProfile::from_transmitter(&r.transmitter, &name);
+        r.tx_profiles.save(profile);
+        r.select_tx_profile(&name);
+        drop(r);
+        tx_profile_name_entry.set_text("");
+        populate_tx_profiles(&tx_profile_list_clone, &tx_profile_mode_grid_clone, &radio_mutex_clone);
+    });
+
+    let tx_profile_import_button: Button = builder
+            .object("tx_profile_import_button")
+            .expect("Could not get object `tx_profile_import_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    let tx_profile_list_clone = tx_profile_list.clone();
+    let tx_profile_mode_grid_clone = tx_profile_mode_grid.clone();
+    tx_profile_import_button.connect_clicked(move |_| {
+        let dialog = FileDialog::builder().title("Import TX Profiles").build();
+        let radio_mutex_clone = radio_mutex_clone.clone();
+        let tx_profile_list_clone = tx_profile_list_clone.clone();
+        let tx_profile_mode_grid_clone = tx_profile_mode_grid_clone.clone();
+        dialog.open(Some(&window_clone), None::<&gio::Cancellable>, move |result| {
+            if let Ok(file) = result {
+                if let Some(path) = file.path() {
+                    let mut r = radio_mutex_clone.radio.lock().unwrap();
+                    match r.tx_profiles.import_json(&path) {
+                        Ok(count) => println!("Imported {} TX profiles from {:?}", count, path),
+                        Err(e) => eprintln!("Error importing TX profiles from {:?}: {}", path, e),
+                    }
+                    drop(r);
+                    populate_tx_profiles(&tx_profile_list_clone, &tx_profile_mode_grid_clone, &radio_mutex_clone);
+                }
+            }
+        });
+    });
+
+    let tx_profile_export_button: Button = builder
+            .object("tx_profile_export_button")
+            .expect("Could not get object `tx_profile_export_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    tx_profile_export_button.connect_clicked(move |_| {
+        let dialog = FileDialog::builder().title("Export TX Profiles").initial_name("txprofiles.json").build();
+        let radio_mutex_clone = radio_mutex_clone.clone();
+        dialog.save(Some(&window_clone), None::<&gio::Cancellable>, move |result| {
+            if let Ok(file) = result {
+                if let Some(path) = file.path() {
+                    let r = radio_mutex_clone.radio.lock().unwrap();
+                    if let Err(e) = r.tx_profiles.export_json(&path) {
+                        eprintln!("Error exporting TX profiles to {:?}: {}", path, e);
+                    }
+                }
+            }
+        });
+    });
+
+    // MIDI
+    let r = radio_mutex.radio.lock().unwrap();
+        let midi_enabled = r.midi.enabled;
+        let midi_device = r.midi.device.clone();
+    drop(r);
+
+    let midi_devices = Midi::list_devices();
+
+    let midi_enabled_check_button: CheckButton = builder
+            .object("midi_enabled_check_button")
+            .expect("Could not get object `midi_enabled_check_button` from builder.");
+    midi_enabled_check_button.set_active(midi_enabled);
+    let radio_mutex_clone = radio_mutex.clone();
+    midi_enabled_check_button.connect_toggled(move |button| {
+        let is_active = button.is_active();
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.midi.enabled = is_active;
+        if is_active {
+            if let Err(e) = r.midi.open() {
+                eprintln!("midi::open failed: {}", e);
+            }
+        } else {
+            r.midi.close();
+        }
+    });
+
+    let midi_device_combo_box: ComboBoxText = builder
+            .object("midi_device_combo_box")
+            .expect("Could not get object `midi_device_combo_box` from builder.");
+    for i in 0..midi_devices.len() {
+        midi_device_combo_box.append_text(&midi_devices[i]);
+        if midi_devices[i] == midi_device {
+            midi_device_combo_box.set_active(Some(i as u32));
+        }
+    }
+    let radio_mutex_clone = radio_mutex.clone();
+    midi_device_combo_box.connect_changed(move |combo_box| {
+        let device = combo_box.active_text();
+        if let Some(device_string) = device {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.midi.device = device_string.to_string();
+            if r.midi.enabled {
+                if let Err(e) = r.midi.open() {
+                    eprintln!("midi::open failed: {}", e);
+                }
+            }
+        }
+    });
+
+    let midi_list: ListBox = builder
+            .object("midi_list")
+            .expect("Could not get object `midi_list` from builder.");
+    populate_midi_list(&midi_list, radio_mutex);
+
+    let midi_action_dropdown: DropDown = builder
+            .object("midi_action_dropdown")
+            .expect("Could not get object `midi_action_dropdown` from builder.");
+
+    let midi_learn_label: Label = builder
+            .object("midi_learn_label")
+            .expect("Could not get object `midi_learn_label` from builder.");
+
+    let midi_learn_button: Button = builder
+            .object("midi_learn_button")
+            .expect("Could not get object `midi_learn_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    midi_learn_button.connect_clicked(move |_| {
+        let action = MidiAction::from_u32(midi_action_dropdown.selected()).unwrap_or(MidiAction::None);
+        if action == MidiAction::None {
+            return;
+        }
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.midi.learn = Some(action);
+        drop(r);
+        midi_learn_label.set_label(&format!("Move or press a control for {}", action.label()));
+
+        // wait for the main loop to assign the next MIDI event
+        let radio_mutex_clone_clone = radio_mutex_clone.clone();
+        let window_clone_clone = window_clone.clone();
+        let midi_list_clone = midi_list.clone();
+        let midi_learn_label_clone = midi_learn_label.clone();
+        timeout_add_local(std::time::Duration::from_millis(100), move || {
+            let r = radio_mutex_clone_clone.radio.lock().unwrap();
+            let learning = r.midi.learn.is_some();
+            drop(r);
+            if !window_clone_clone.is_visible() {
+                let mut r = radio_mutex_clone_clone.radio.lock().unwrap();
+                r.midi.learn = None;
+                return Break;
+            }
+            if learning {
+                return Continue;
+            }
+            midi_learn_label_clone.set_label("");
+            populate_midi_list(&midi_list_clone, &radio_mutex_clone_clone);
+            Break
+        });
+    });
+
     let ok_button: Button = builder
             .object("ok_button")
             .expect("Could not get object `ok_button` from builder.");
@@ -1062,3 +1947,211 @@ pub fn create_configure_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMu
     window
 }
 
+
+fn populate_midi_list(list: &ListBox, radio_mutex: &RadioMutex) {
+
+    // Remove any existing rows
+    while let Some(row) = list.row_at_index(0) {
+        list.remove(&row);
+    }
+
+    let r = radio_mutex.radio.lock().unwrap();
+    let mappings = r.midi.mappings.clone();
+    drop(r);
+
+    for (i, mapping) in mappings.iter().enumerate() {
+        let row = ListBoxRow::new();
+        let hbox = gtk::Box::new(Orientation::Horizontal, 10);
+        let label_action = Label::new(Some(mapping.action.label()));
+        label_action.set_xalign(0.0); // Align text to the left
+        label_action.set_size_request(100,-1);
+        hbox.append(&label_action);
+        let event = format!("{:?}", mapping.event);
+        let label_event = Label::new(Some(&event));
+        label_event.set_xalign(0.0); // Align text to the left
+        label_event.set_size_request(100,-1);
+        hbox.append(&label_event);
+        let channel = format!("Ch {}", mapping.channel + 1);
+        let label_channel = Label::new(Some(&channel));
+        label_channel.set_xalign(0.0); // Align text to the left
+        label_channel.set_size_request(70,-1);
+        hbox.append(&label_channel);
+        let param = format!("{}", mapping.param);
+        let label_param = Label::new(Some(&param));
+        label_param.set_xalign(0.0); // Align text to the left
+        label_param.set_size_request(70,-1);
+        hbox.append(&label_param);
+        let delete_button = Button::with_label("Delete");
+        let list_clone = list.clone();
+        let radio_mutex_clone = radio_mutex.clone();
+        delete_button.connect_clicked(move |_| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.midi.remove_mapping(i);
+            drop(r);
+            populate_midi_list(&list_clone, &radio_mutex_clone);
+        });
+        hbox.append(&delete_button);
+        row.set_child(Some(&hbox));
+
+        list.append(&row);
+    }
+}
+
+// the notches on the current band of each receiver
+fn populate_notch_list(list: &ListBox, radio_mutex: &RadioMutex) {
+
+    // Remove any existing rows
+    while let Some(row) = list.row_at_index(0) {
+        list.remove(&row);
+    }
+
+    let r = radio_mutex.radio.lock().unwrap();
+    let notches = [r.receiver[0].notches().clone(), r.receiver[1].notches().clone()];
+    drop(r);
+
+    for (rx, rx_notches) in notches.iter().enumerate() {
+        for (i, notch) in rx_notches.iter().enumerate() {
+            let row = ListBoxRow::new();
+            let hbox = gtk::Box::new(Orientation::Horizontal, 10);
+            let id = format!("RX{}", rx + 1);
+            let label_id = Label::new(Some(&id));
+            label_id.set_xalign(0.0); // Align text to the left
+            label_id.set_size_request(50,-1);
+            hbox.append(&label_id);
+            let frequency = format!("{:.0} Hz", notch.frequency);
+            let label_frequency = Label::new(Some(&frequency));
+            label_frequency.set_xalign(0.0); // Align text to the left
+            label_frequency.set_size_request(120,-1);
+            hbox.append(&label_frequency);
+            let width = format!("{:.0} Hz", notch.width);
+            let label_width = Label::new(Some(&width));
+            label_width.set_xalign(0.0); // Align text to the left
+            label_width.set_size_request(70,-1);
+            hbox.append(&label_width);
+            let active_check_button = CheckButton::with_label("Enabled");
+            active_check_button.set_active(notch.is_active());
+            let radio_mutex_clone = radio_mutex.clone();
+            active_check_button.connect_toggled(move |button| {
+                let mut r = radio_mutex_clone.radio.lock().unwrap();
+                if i < r.receiver[rx].notches().len() {
+                    r.receiver[rx].set_notch_active(i, button.is_active());
+                }
+            });
+            hbox.append(&active_check_button);
+            let delete_button = Button::with_label("Delete");
+            let list_clone = list.clone();
+            let radio_mutex_clone = radio_mutex.clone();
+            delete_button.connect_clicked(move |_| {
+                let mut r = radio_mutex_clone.radio.lock().unwrap();
+                if i < r.receiver[rx].notches().len() {
+                    r.receiver[rx].delete_notch(i);
+                }
+                drop(r);
+                populate_notch_list(&list_clone, &radio_mutex_clone);
+            });
+            hbox.append(&delete_button);
+            row.set_child(Some(&hbox));
+
+            list.append(&row);
+        }
+    }
+}
+
+fn populate_tx_profiles(list: &ListBox, mode_grid: &Grid, radio_mutex: &RadioMutex) {
+
+    // Remove any existing rows
+    while let Some(row) = list.row_at_index(0) {
+        list.remove(&row);
+    }
+    while let Some(child) = mode_grid.first_child() {
+        mode_grid.remove(&child);
+    }
+
+    let r = radio_mutex.radio.lock().unwrap();
+    let profiles = r.tx_profiles.profiles.clone();
+    let mode_profiles = r.tx_profiles.mode_profiles.clone();
+    let current = r.tx_profiles.current.clone();
+    drop(r);
+
+    for profile in profiles.iter() {
+        let row = ListBoxRow::new();
+        let hbox = gtk::Box::new(Orientation::Horizontal, 10);
+        let label_name = Label::new(Some(&profile.name));
+        label_name.set_xalign(0.0); // Align text to the left
+        label_name.set_size_request(150,-1);
+        hbox.append(&label_name);
+        let filter = format!("{:.0}-{:.0} Hz", profile.filter_low, profile.filter_high);
+        let label_filter = Label::new(Some(&filter));
+        label_filter.set_xalign(0.0); // Align text to the left
+        label_filter.set_size_request(120,-1);
+        hbox.append(&label_filter);
+        let mut processing = Vec::new();
+        if profile.equalizer_enabled {
+            processing.push(String::from("EQ"));
+        }
+        if profile.compressor {
+            processing.push(format!("Comp {:.0} dB", profile.compressor_gain));
+        }
+        if profile.leveler {
+            processing.push(String::from("Leveler"));
+        }
+        let label_processing = Label::new(Some(&processing.join(", ")));
+        label_processing.set_xalign(0.0); // Align text to the left
+        label_processing.set_size_request(180,-1);
+        hbox.append(&label_processing);
+        let use_button = Button::with_label("Use");
+        use_button.set_sensitive(profile.name != current);
+        let name = profile.name.clone();
+        let list_clone = list.clone();
+        let mode_grid_clone = mode_grid.clone();
+        let radio_mutex_clone = radio_mutex.clone();
+        use_button.connect_clicked(move |_| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.select_tx_profile(&name);
+            drop(r);
+            populate_tx_profiles(&list_clone, &mode_grid_clone, &radio_mutex_clone);
+        });
+        hbox.append(&use_button);
+        let delete_button = Button::with_label("Delete");
+        let name = profile.name.clone();
+        let list_clone = list.clone();
+        let mode_grid_clone = mode_grid.clone();
+        let radio_mutex_clone = radio_mutex.clone();
+        delete_button.connect_clicked(move |_| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.tx_profiles.remove(&name);
+            drop(r);
+            populate_tx_profiles(&list_clone, &mode_grid_clone, &radio_mutex_clone);
+        });
+        hbox.append(&delete_button);
+        row.set_child(Some(&hbox));
+
+        list.append(&row);
+    }
+
+    let mut names = vec![String::from("None")];
+    names.extend(profiles.iter().map(|p| p.name.clone()));
+    let items: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    for mode in 0..=Modes::DRM.to_usize() {
+        let label = Label::new(Some(&format!("{:?}", Modes::from_usize(mode).unwrap())));
+        label.set_xalign(1.0);
+        mode_grid.attach(&label, ((mode % 4) * 2) as i32, (mode / 4) as i32, 1, 1);
+        let dropdown = DropDown::from_strings(&items);
+        let selected = profiles.iter().position(|p| Some(&p.name) == mode_profiles.get(mode)).map(|i| i + 1).unwrap_or(0);
+        dropdown.set_selected(selected as u32);
+        let radio_mutex_clone = radio_mutex.clone();
+        let names_clone = names.clone();
+        dropdown.connect_selected_notify(move |dropdown| {
+            let mut name = String::new();
+            let selected = dropdown.selected() as usize;
+            if selected > 0 && selected < names_clone.len() {
+                name = names_clone[selected].clone();
+            }
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            if mode < r.tx_profiles.mode_profiles.len() {
+                r.tx_profiles.mode_profiles[mode] = name;
+            }
+        });
+        mode_grid.attach(&dropdown, ((mode % 4) * 2 + 1) as i32, (mode / 4) as i32, 1, 1);
+    }
+}
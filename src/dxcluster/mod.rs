@@ -0,0 +1,634 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use glib::ControlFlow::{Break, Continue};
+use glib::timeout_add_local;
+use gtk::prelude::*;
+use gtk::{Adjustment, ApplicationWindow, Builder, CheckButton, Entry, Label, ListBox, ListBoxRow, Orientation, ToggleButton, Window};
+use serde::{Deserialize, Serialize};
+
+use std::cell::{Cell, RefCell};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bands::*;
+use crate::bandplan::BandPlan;
+use crate::modes::Modes;
+use crate::radio::RadioMutex;
+
+const MAX_SPOTS: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct Spot {
+    pub spotter: String,
+    pub frequency: f32,
+    pub call: String,
+    pub comment: String,
+    pub time: String,
+    pub received: Instant,
+}
+
+impl Spot {
+
+    // parse an AR-Cluster/DXSpider spot line:
+    // DX de G0ORX:     14025.0  N6LYT        CW 599                      1234Z
+    pub fn parse(line: &str) -> Option<Spot> {
+        let start = line.find("DX de ")?;
+        let line = line[start + 6..].trim_end();
+        let (spotter, rest) = line.split_once(':')?;
+        let mut fields = rest.split_whitespace();
+        let frequency = fields.next()?.parse::<f32>().ok()? * 1000.0;
+        let call = fields.next()?.to_string();
+        let mut words: Vec<&str> = fields.collect();
+        let mut time = String::from("");
+        // the time is usually last but some clusters append a locator after it
+        for i in (0..words.len()).rev() {
+            let word = words[i];
+            if word.len() == 5 && word.ends_with('Z') && word.bytes().take(4).all(|c| c.is_ascii_digit()) {
+                time = word.to_string();
+                words.truncate(i);
+                break;
+            }
+        }
+        let comment = words.join(" ");
+        Some(Spot {
+            spotter: spotter.trim().to_string(),
+            frequency,
+            call,
+            comment,
+            time,
+            received: Instant::now(),
+        })
+    }
+
+    // the mode to use when tuning to the spot, from the comment if it says, otherwise the band plan
+    pub fn mode(&self, band_info: &Vec<BandInfo>, band_plan: &BandPlan) -> Modes {
+        let band = band_for_frequency(band_info, self.frequency);
+        let default_mode = band_info[band.to_usize()].mode;
+        let lower_sideband = default_mode == Modes::LSB;
+        let comment = self.comment.to_uppercase();
+        for word in comment.split(|c: char| !c.is_ascii_alphanumeric()) {
+            match word {
+                "CW" => return if lower_sideband { Modes::CWL } else { Modes::CWU },
+                "FT8" | "FT4" | "JT65" | "JT9" | "JS8" | "MSK144" | "WSPR" | "PSK" | "PSK31" | "PSK63" | "OLIVIA" | "DIGI" => return Modes::DIGU,
+                "RTTY" => return Modes::DIGL,
+                "LSB" => return Modes::LSB,
+                "USB" => return Modes::USB,
+                "SSB" => return if lower_sideband { Modes::LSB } else { Modes::USB },
+                "AM" => return Modes::AM,
+                "FM" => return Modes::FMN,
+                _ => {},
+            }
+        }
+        if let Some((_, segment)) = band_plan.find(self.frequency) {
+            if let Some(mode) = segment.mode() {
+                return mode;
+            }
+        }
+        default_mode
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpotMode {
+    CW,
+    Phone,
+    Digital,
+}
+
+impl SpotMode {
+    pub fn from_mode(mode: Modes) -> SpotMode {
+        match mode {
+            Modes::CWL | Modes::CWU => SpotMode::CW,
+            Modes::DIGU | Modes::DIGL => SpotMode::Digital,
+            _ => SpotMode::Phone,
+        }
+    }
+}
+
+pub enum DxClusterEvent {
+    Connected,
+    Disconnected(String),
+    Spot(Spot),
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DxCluster {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub callsign: String,
+    pub lifetime: u32,
+    pub bands: Vec<bool>,
+    pub cw: bool,
+    pub phone: bool,
+    pub digital: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub spots: Vec<Spot>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub connected: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub status: String,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub serial: u32,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub qsy: Option<(f32, Modes)>,
+    #[serde(skip_serializing, skip_deserializing)]
+    running: Option<Arc<AtomicBool>>,
+    #[serde(skip_serializing, skip_deserializing)]
+    events: Option<Receiver<DxClusterEvent>>,
+}
+
+impl DxCluster {
+
+    pub fn new() -> DxCluster {
+        let enabled = false;
+        let host = String::from("localhost");
+        let port = 7300;
+        let callsign = String::from("");
+        let lifetime = 15;
+        let bands = vec![true; Bands::BandWWV.to_usize() + 1];
+        let cw = true;
+        let phone = true;
+        let digital = true;
+        let spots: Vec<Spot> = Vec::new();
+        let connected = false;
+        let status = String::from("Disconnected");
+        let serial = 0;
+        let qsy = None;
+        let running = None;
+        let events = None;
+        DxCluster {
+            enabled,
+            host,
+            port,
+            callsign,
+            lifetime,
+            bands,
+            cw,
+            phone,
+            digital,
+            spots,
+            connected,
+            status,
+            serial,
+            qsy,
+            running,
+            events,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.spots = Vec::new();
+        self.connected = false;
+        self.status = String::from("Disconnected");
+        self.serial = 0;
+        self.qsy = None;
+        self.running = None;
+        self.events = None;
+        if self.enabled {
+            self.connect();
+        }
+    }
+
+    pub fn connect(&mut self) {
+        println!("dxcluster::connect {}:{} as {}", self.host, self.port, self.callsign);
+        self.disconnect();
+
+        let running = Arc::new(AtomicBool::new(true));
+        let (events_tx, events_rx) = channel::<DxClusterEvent>();
+        self.running = Some(running.clone());
+        self.events = Some(events_rx);
+        self.status = format!("Connecting to {}:{}", self.host, self.port);
+
+        let host = self.host.clone();
+        let port = self.port;
+        let callsign = self.callsign.clone();
+        thread::spawn(move || {
+            let address = match (host.as_str(), port).to_socket_addrs().map(|mut a| a.next()) {
+                Ok(Some(address)) => address,
+                Ok(None) => {
+                    let _ = events_tx.send(DxClusterEvent::Disconnected(format!("{}: no address", host)));
+                    return;
+                }
+                Err(e) => {
+                    let _ = events_tx.send(DxClusterEvent::Disconnected(format!("{}: {}", host, e)));
+                    return;
+                }
+            };
+            let mut stream = match TcpStream::connect_timeout(&address, Duration::from_secs(5)) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = events_tx.send(DxClusterEvent::Disconnected(format!("{}: {}", host, e)));
+                    return;
+                }
+            };
+            let _ = stream.set_read_timeout(Some(Duration::from_millis(250)));
+            let _ = events_tx.send(DxClusterEvent::Connected);
+
+            let mut logged_in = false;
+            let mut pending = String::new();
+            let mut buffer = [0u8; 1024];
+            while running.load(Ordering::Relaxed) {
+                match stream.read(&mut buffer) {
+                    Ok(0) => {
+                        let _ = events_tx.send(DxClusterEvent::Disconnected(String::from("Closed by cluster")));
+                        return;
+                    }
+                    Ok(n) => {
+                        pending.push_str(&String::from_utf8_lossy(&strip_telnet(&buffer[..n])));
+                        while let Some(index) = pending.find('\n') {
+                            let line: String = pending.drain(..=index).collect();
+                            if let Some(spot) = Spot::parse(&line) {
+                                if events_tx.send(DxClusterEvent::Spot(spot)).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        // the login prompt is not terminated by a newline
+                        if !logged_in {
+                            let prompt = pending.trim_end().to_lowercase();
+                            if prompt.ends_with(':') && (prompt.contains("login") || prompt.contains("call")) {
+                                let _ = stream.write_all(format!("{}\r\n", callsign).as_bytes());
+                                logged_in = true;
+                                pending.clear();
+                            }
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {}
+                    Err(e) => {
+                        let _ = events_tx.send(DxClusterEvent::Disconnected(e.to_string()));
+                        return;
+                    }
+                }
+            }
+            let _ = stream.write_all(b"bye\r\n");
+            let _ = stream.shutdown(Shutdown::Both);
+            println!("dxcluster thread exit");
+        });
+    }
+
+    pub fn disconnect(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.store(false, Ordering::Relaxed);
+        }
+        self.events = None;
+        self.connected = false;
+        self.status = String::from("Disconnected");
+    }
+
+    // collect any new spots and age out the old ones
+    pub fn poll(&mut self) {
+        let mut events = Vec::new();
+        if let Some(rx) = &self.events {
+            while let Ok(event) = rx.try_recv() {
+                events.push(event);
+            }
+        }
+
+        for event in events {
+            match event {
+                DxClusterEvent::Connected => {
+                    self.connected = true;
+                    self.status = format!("Connected to {}:{}", self.host, self.port);
+                }
+                DxClusterEvent::Disconnected(reason) => {
+                    self.running = None;
+                    self.events = None;
+                    self.connected = false;
+                    self.status = format!("Disconnected: {}", reason);
+                }
+                DxClusterEvent::Spot(spot) => {
+                    // a new spot replaces an earlier one for the same station nearby
+                    self.spots.retain(|s| !(s.call == spot.call && (s.frequency - spot.frequency).abs() < 5000.0));
+                    self.spots.push(spot);
+                    if self.spots.len() > MAX_SPOTS {
+                        self.spots.remove(0);
+                    }
+                    self.serial = self.serial.wrapping_add(1);
+                }
+            }
+        }
+
+        let lifetime = Duration::from_secs(self.lifetime as u64 * 60);
+        let count = self.spots.len();
+        self.spots.retain(|s| s.received.elapsed() < lifetime);
+        if self.spots.len() != count {
+            self.serial = self.serial.wrapping_add(1);
+        }
+    }
+
+    // check the spot against the band and mode filters
+    pub fn show_spot(&self, spot: &Spot, band_info: &Vec<BandInfo>, band_plan: &BandPlan) -> bool {
+        let band = band_for_frequency(band_info, spot.frequency).to_usize();
+        if band < self.bands.len() && !self.bands[band] {
+            return false;
+        }
+        match SpotMode::from_mode(spot.mode(band_info, band_plan)) {
+            SpotMode::CW => self.cw,
+            SpotMode::Phone => self.phone,
+            SpotMode::Digital => self.digital,
+        }
+    }
+}
+
+// telnet command bytes
+const IAC: u8 = 255;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const WILL: u8 = 251;
+const DONT: u8 = 254;
+
+// remove any telnet IAC command sequences, an escaped IAC is kept as a single 0xFF
+fn strip_telnet(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] != IAC {
+            result.push(data[i]);
+            i += 1;
+            continue;
+        }
+        match data.get(i + 1) {
+            Some(&IAC) => {
+                result.push(IAC);
+                i += 2;
+            },
+            Some(&SB) => {
+                // skip the option bytes up to IAC SE
+                i += 2;
+                while i < data.len() && !(data[i] == IAC && data.get(i + 1) == Some(&SE)) {
+                    i += 1;
+                }
+                i += 2;
+            },
+            Some(&command) if (WILL..=DONT).contains(&command) => i += 3, // option negotiation
+            Some(_) => i += 2,
+            None => i += 1,
+        }
+    }
+    result
+}
+
+pub fn create_dxcluster_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMutex) -> Window {
+
+    let ui_xml = include_str!("../ui/dxcluster.xml");
+    let builder = Builder::from_string(ui_xml);
+
+    let window: Window = builder
+            .object("dxcluster_window")
+            .expect("Could not get object `dxcluster_window` from builder.");
+
+    window.set_transient_for(Some(parent)); // keeps it on top
+
+    let r = radio_mutex.radio.lock().unwrap();
+        let enabled = r.dxcluster.enabled;
+        let host = r.dxcluster.host.clone();
+        let port = r.dxcluster.port;
+        let callsign = r.dxcluster.callsign.clone();
+        let lifetime = r.dxcluster.lifetime;
+        let bands = r.dxcluster.bands.clone();
+        let cw = r.dxcluster.cw;
+        let phone = r.dxcluster.phone;
+        let digital = r.dxcluster.digital;
+        let connected = r.dxcluster.running.is_some();
+        let band_info = r.receiver[0].band_info.clone();
+    drop(r);
+
+    let host_entry: Entry = builder
+            .object("dxcluster_host_entry")
+            .expect("Could not get object `dxcluster_host_entry` from builder.");
+    host_entry.set_text(&host);
+    let radio_mutex_clone = radio_mutex.clone();
+    host_entry.connect_changed(move |entry| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.dxcluster.host = entry.text().to_string();
+    });
+
+    let port_adjustment: Adjustment = builder
+            .object("dxcluster_port_adjustment")
+            .expect("Could not get object `dxcluster_port_adjustment` from builder.");
+    port_adjustment.set_value(port.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    port_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.dxcluster.port = adjustment.value() as u16;
+    });
+
+    let callsign_entry: Entry = builder
+            .object("dxcluster_callsign_entry")
+            .expect("Could not get object `dxcluster_callsign_entry` from builder.");
+    callsign_entry.set_text(&callsign);
+    let radio_mutex_clone = radio_mutex.clone();
+    callsign_entry.connect_changed(move |entry| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.dxcluster.callsign = entry.text().to_uppercase();
+    });
+
+    let enabled_check_button: CheckButton = builder
+            .object("dxcluster_enabled_check_button")
+            .expect("Could not get object `dxcluster_enabled_check_button` from builder.");
+    enabled_check_button.set_active(enabled);
+    let radio_mutex_clone = radio_mutex.clone();
+    enabled_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.dxcluster.enabled = button.is_active();
+    });
+
+    let connect_button: ToggleButton = builder
+            .object("dxcluster_connect_button")
+            .expect("Could not get object `dxcluster_connect_button` from builder.");
+    connect_button.set_active(connected);
+    let radio_mutex_clone = radio_mutex.clone();
+    connect_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if button.is_active() {
+            if r.dxcluster.running.is_none() {
+                r.dxcluster.connect();
+            }
+        } else {
+            r.dxcluster.disconnect();
+        }
+    });
+
+    let status_label: Label = builder
+            .object("dxcluster_status_label")
+            .expect("Could not get object `dxcluster_status_label` from builder.");
+
+    let lifetime_adjustment: Adjustment = builder
+            .object("dxcluster_lifetime_adjustment")
+            .expect("Could not get object `dxcluster_lifetime_adjustment` from builder.");
+    lifetime_adjustment.set_value(lifetime.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    lifetime_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.dxcluster.lifetime = adjustment.value() as u32;
+    });
+
+    // forces the spot list to be rebuilt when a filter changes
+    let refresh = Rc::new(Cell::new(true));
+
+    let band_box: gtk::Box = builder
+            .object("dxcluster_band_box")
+            .expect("Could not get object `dxcluster_band_box` from builder.");
+    for (i, info) in band_info.iter().enumerate() {
+        let check_button = CheckButton::with_label(&info.label);
+        check_button.set_active(i >= bands.len() || bands[i]);
+        let radio_mutex_clone = radio_mutex.clone();
+        let refresh_clone = refresh.clone();
+        check_button.connect_toggled(move |button| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            if i < r.dxcluster.bands.len() {
+                r.dxcluster.bands[i] = button.is_active();
+            }
+            refresh_clone.set(true);
+        });
+        band_box.append(&check_button);
+    }
+
+    let cw_check_button: CheckButton = builder
+            .object("dxcluster_cw_check_button")
+            .expect("Could not get object `dxcluster_cw_check_button` from builder.");
+    cw_check_button.set_active(cw);
+    let radio_mutex_clone = radio_mutex.clone();
+    let refresh_clone = refresh.clone();
+    cw_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.dxcluster.cw = button.is_active();
+        refresh_clone.set(true);
+    });
+
+    let phone_check_button: CheckButton = builder
+            .object("dxcluster_phone_check_button")
+            .expect("Could not get object `dxcluster_phone_check_button` from builder.");
+    phone_check_button.set_active(phone);
+    let radio_mutex_clone = radio_mutex.clone();
+    let refresh_clone = refresh.clone();
+    phone_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.dxcluster.phone = button.is_active();
+        refresh_clone.set(true);
+    });
+
+    let digital_check_button: CheckButton = builder
+            .object("dxcluster_digital_check_button")
+            .expect("Could not get object `dxcluster_digital_check_button` from builder.");
+    digital_check_button.set_active(digital);
+    let radio_mutex_clone = radio_mutex.clone();
+    let refresh_clone = refresh.clone();
+    digital_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.dxcluster.digital = button.is_active();
+        refresh_clone.set(true);
+    });
+
+    let spot_list: ListBox = builder
+            .object("dxcluster_spot_list")
+            .expect("Could not get object `dxcluster_spot_list` from builder.");
+    let rows: Rc<RefCell<Vec<(f32, Modes)>>> = Rc::new(RefCell::new(Vec::new()));
+    let radio_mutex_clone = radio_mutex.clone();
+    let rows_clone = rows.clone();
+    spot_list.connect_row_activated(move |_, row| {
+        let index = row.index();
+        if index >= 0 {
+            if let Some(qsy) = rows_clone.borrow().get(index as usize) {
+                let mut r = radio_mutex_clone.radio.lock().unwrap();
+                r.dxcluster.qsy = Some(*qsy);
+            }
+        }
+    });
+
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    let serial = Rc::new(Cell::new(0));
+    timeout_add_local(Duration::from_millis(500), move || {
+        if !window_clone.is_visible() {
+            return Break;
+        }
+        let r = radio_mutex_clone.radio.lock().unwrap();
+        let current_serial = r.dxcluster.serial;
+        let status = r.dxcluster.status.clone();
+        let running = r.dxcluster.running.is_some();
+        drop(r);
+        status_label.set_label(&status);
+        if connect_button.is_active() != running {
+            connect_button.set_active(running);
+        }
+        if refresh.get() || serial.get() != current_serial {
+            refresh.set(false);
+            serial.set(current_serial);
+            populate_spot_list(&spot_list, &radio_mutex_clone, &rows);
+        }
+        Continue
+    });
+
+    window
+}
+
+fn populate_spot_list(list: &ListBox, radio_mutex: &RadioMutex, rows: &Rc<RefCell<Vec<(f32, Modes)>>>) {
+
+    // Remove any existing rows
+    while let Some(row) = list.row_at_index(0) {
+        list.remove(&row);
+    }
+
+    let mut qsy = rows.borrow_mut();
+    qsy.clear();
+
+    let r = radio_mutex.radio.lock().unwrap();
+    let band_info = r.receiver[0].band_info.clone();
+    let spots: Vec<(Spot, Modes)> = r.dxcluster.spots.iter().rev()
+        .filter(|spot| r.dxcluster.show_spot(spot, &band_info, &r.band_plan))
+        .map(|spot| (spot.clone(), spot.mode(&band_info, &r.band_plan)))
+        .collect();
+    drop(r);
+
+    for (spot, mode) in spots {
+        let row = ListBoxRow::new();
+        let hbox = gtk::Box::new(Orientation::Horizontal, 10);
+        let label_time = Label::new(Some(&spot.time));
+        label_time.set_xalign(0.0); // Align text to the left
+        label_time.set_size_request(50,-1);
+        hbox.append(&label_time);
+        let frequency = format!("{:.1}", spot.frequency / 1000.0);
+        let label_frequency = Label::new(Some(&frequency));
+        label_frequency.set_xalign(1.0); // Align text to the right
+        label_frequency.set_size_request(80,-1);
+        hbox.append(&label_frequency);
+        let label_call = Label::new(Some(&spot.call));
+        label_call.set_xalign(0.0); // Align text to the left
+        label_call.set_size_request(100,-1);
+        hbox.append(&label_call);
+        let label_spotter = Label::new(Some(&spot.spotter));
+        label_spotter.set_xalign(0.0); // Align text to the left
+        label_spotter.set_size_request(100,-1);
+        hbox.append(&label_spotter);
+        let label_comment = Label::new(Some(&spot.comment));
+        label_comment.set_xalign(0.0); // Align text to the left
+        hbox.append(&label_comment);
+        row.set_child(Some(&hbox));
+
+        list.append(&row);
+        qsy.push((spot.frequency, mode));
+    }
+}
@@ -405,6 +405,26 @@ impl FilterGrid {
         (m[filter].low, m[filter].high)
     }
 
+    pub fn get_filter_label(&self, mode: usize, filter: usize) -> &'static str {
+        let mut m = Self::filterUSB;
+        match Modes::from_usize(mode) {
+           Some(Modes::LSB) => m = Self::filterLSB,
+           Some(Modes::USB) => m = Self::filterUSB,
+           Some(Modes::DSB) => m = Self::filterDSB,
+           Some(Modes::CWL) => m = Self::filterCWL,
+           Some(Modes::CWU) => m = Self::filterCWU,
+           Some(Modes::FMN) => m = Self::filterFMN,
+           Some(Modes::AM) => m = Self::filterAM,
+           Some(Modes::DIGU) => m = Self::filterDIGU,
+           Some(Modes::SPEC) => m = Self::filterSPEC,
+           Some(Modes::DIGL) => m = Self::filterDIGL,
+           Some(Modes::SAM) => m = Self::filterSAM,
+           Some(Modes::DRM) => m = Self::filterDRM,
+           None => m = Self::filterUSB,
+        }
+        m[filter].label
+    }
+
     pub fn update_filter_buttons(&self, mode: usize) {
         let mut filters = Self::filterUSB;
         match Modes::from_usize(mode) {
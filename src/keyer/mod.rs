@@ -0,0 +1,711 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use glib::ControlFlow::{Break, Continue};
+use glib::timeout_add_local;
+use gtk::prelude::*;
+use gtk::{gdk, Adjustment, ApplicationWindow, Builder, Button, Entry, EventControllerKey, Label, Window};
+use gtk::glib::Propagation;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::fs::OpenOptions;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nix::libc;
+
+use crate::morse::char_to_morse;
+use crate::radio::{Keyer, RadioMutex};
+
+// the keyer is stepped once per microphone sample
+pub const KEYER_SAMPLE_RATE: i32 = 48000;
+// raised cosine rise and fall time of 5ms
+const RAMP_SAMPLES: usize = 240;
+// sidetone is written to the local audio in small blocks to keep the latency down
+const SIDETONE_FRAMES: usize = 128;
+// F1 to F8
+pub const CW_MACROS: usize = 8;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Element {
+    Dot,
+    Dash,
+}
+
+#[derive(Default)]
+enum KeyerState {
+    #[default]
+    Idle,
+    Delay(Element, usize),
+    Mark(Element, usize),
+    Space(Element, usize),
+    LetterSpace(usize),
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CwKeyer {
+    pub keyboard: bool,
+    pub serial: bool,
+    pub serial_device: String,
+    pub mycall: String,
+    pub call: String,
+    pub nr: u32,
+    pub macros: Vec<String>,
+#[serde(skip_serializing, skip_deserializing)]
+    text: VecDeque<char>,
+#[serde(skip_serializing, skip_deserializing)]
+    text_elements: VecDeque<(bool, usize)>,
+#[serde(skip_serializing, skip_deserializing)]
+    text_count: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    text_active: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    pub keyboard_dot: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    pub keyboard_dash: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    serial_dot: Arc<AtomicBool>,
+#[serde(skip_serializing, skip_deserializing)]
+    serial_dash: Arc<AtomicBool>,
+#[serde(skip_serializing, skip_deserializing)]
+    running: Option<Arc<AtomicBool>>,
+#[serde(skip_serializing, skip_deserializing)]
+    state: KeyerState,
+#[serde(skip_serializing, skip_deserializing)]
+    dot_memory: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    dash_memory: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    key_down: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    ramp: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    hang: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    mode: Option<Keyer>,
+#[serde(skip_serializing, skip_deserializing)]
+    unit_samples: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    dot_samples: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    dash_samples: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    space_samples: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    spacing: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    delay_samples: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    hang_samples: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    sidetone_phase: f64,
+#[serde(skip_serializing, skip_deserializing)]
+    sidetone_buffer: Vec<i16>,
+#[serde(skip_serializing, skip_deserializing)]
+    sidetone_offset: usize,
+}
+
+impl CwKeyer {
+
+    pub fn new() -> CwKeyer {
+        let keyboard = false;
+        let serial = false;
+        let serial_device = String::from("/dev/ttyUSB0");
+        let mycall = String::from("");
+        let call = String::from("");
+        let nr = 1;
+        let macros = vec![
+            String::from("CQ CQ CQ DE {MYCALL} {MYCALL} K"),
+            String::from("{CALL} 5NN {NR}"),
+            String::from("TU {MYCALL}"),
+            String::from("{MYCALL}"),
+            String::from("{CALL}"),
+            String::from("AGN?"),
+            String::from("QRZ?"),
+            String::from("{CALL} 73 DE {MYCALL} SK"),
+        ];
+        let text = VecDeque::new();
+        let text_elements = VecDeque::new();
+        let text_count = 0;
+        let text_active = false;
+        let keyboard_dot = false;
+        let keyboard_dash = false;
+        let serial_dot = Arc::new(AtomicBool::new(false));
+        let serial_dash = Arc::new(AtomicBool::new(false));
+        let running = None;
+        let state = KeyerState::Idle;
+        let dot_memory = false;
+        let dash_memory = false;
+        let key_down = false;
+        let ramp = 0;
+        let hang = 0;
+        let mode = None;
+        let unit_samples = 0;
+        let dot_samples = 0;
+        let dash_samples = 0;
+        let space_samples = 0;
+        let spacing = false;
+        let delay_samples = 0;
+        let hang_samples = 0;
+        let sidetone_phase = 0.0;
+        let sidetone_buffer = vec![0i16; SIDETONE_FRAMES * 2];
+        let sidetone_offset = 0;
+        CwKeyer {
+            keyboard,
+            serial,
+            serial_device,
+            mycall,
+            call,
+            nr,
+            macros,
+            text,
+            text_elements,
+            text_count,
+            text_active,
+            keyboard_dot,
+            keyboard_dash,
+            serial_dot,
+            serial_dash,
+            running,
+            state,
+            dot_memory,
+            dash_memory,
+            key_down,
+            ramp,
+            hang,
+            mode,
+            unit_samples,
+            dot_samples,
+            dash_samples,
+            space_samples,
+            spacing,
+            delay_samples,
+            hang_samples,
+            sidetone_phase,
+            sidetone_buffer,
+            sidetone_offset,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.keyboard_dot = false;
+        self.keyboard_dash = false;
+        self.serial_dot = Arc::new(AtomicBool::new(false));
+        self.serial_dash = Arc::new(AtomicBool::new(false));
+        self.running = None;
+        self.macros.resize(CW_MACROS, String::from(""));
+        self.reset();
+        self.sidetone_buffer = vec![0i16; SIDETONE_FRAMES * 2];
+        self.sidetone_offset = 0;
+        if self.serial {
+            if let Err(e) = self.open_serial() {
+                eprintln!("keyer::open_serial failed: {}", e);
+            }
+        }
+    }
+
+    // drop any element in progress, the key goes up immediately
+    pub fn reset(&mut self) {
+        self.abort_text();
+        self.state = KeyerState::Idle;
+        self.dot_memory = false;
+        self.dash_memory = false;
+        self.key_down = false;
+        self.ramp = 0;
+        self.hang = 0;
+        self.sidetone_phase = 0.0;
+    }
+
+    // paddles on the serial port modem control lines, CTS is dot and DSR is dash.
+    // DTR and RTS are raised so they can be used as the common for the paddle.
+    pub fn open_serial(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        println!("keyer::open_serial {}", self.serial_device);
+        self.close_serial();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY | libc::O_NONBLOCK)
+            .open(&self.serial_device)?;
+        let fd = file.as_raw_fd();
+        let raise: libc::c_int = libc::TIOCM_DTR | libc::TIOCM_RTS;
+        if unsafe { libc::ioctl(fd, libc::TIOCMBIS, &raise) } < 0 {
+            return Err(Box::new(std::io::Error::last_os_error()));
+        }
+
+        let running = Arc::new(AtomicBool::new(true));
+        self.running = Some(running.clone());
+        let serial_dot = self.serial_dot.clone();
+        let serial_dash = self.serial_dash.clone();
+
+        thread::spawn(move || {
+            while running.load(Ordering::Relaxed) {
+                let mut status: libc::c_int = 0;
+                if unsafe { libc::ioctl(file.as_raw_fd(), libc::TIOCMGET, &mut status) } < 0 {
+                    eprintln!("keyer serial error: {}", std::io::Error::last_os_error());
+                    break;
+                }
+                serial_dot.store(status & libc::TIOCM_CTS != 0, Ordering::Relaxed);
+                serial_dash.store(status & libc::TIOCM_DSR != 0, Ordering::Relaxed);
+                thread::sleep(Duration::from_millis(1));
+            }
+            serial_dot.store(false, Ordering::Relaxed);
+            serial_dash.store(false, Ordering::Relaxed);
+            println!("keyer serial thread exit");
+        });
+
+        println!("keyer::open_serial Ok");
+        Ok(())
+    }
+
+    pub fn close_serial(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.store(false, Ordering::Relaxed);
+        }
+        self.serial_dot.store(false, Ordering::Relaxed);
+        self.serial_dash.store(false, Ordering::Relaxed);
+    }
+
+    // combine the radio key inputs with the keyboard and serial paddles
+    pub fn paddles(&self, radio_dot: bool, radio_dash: bool) -> (bool, bool) {
+        let mut dot = radio_dot;
+        let mut dash = radio_dash;
+        if self.keyboard {
+            dot |= self.keyboard_dot;
+            dash |= self.keyboard_dash;
+        }
+        if self.serial {
+            dot |= self.serial_dot.load(Ordering::Relaxed);
+            dash |= self.serial_dash.load(Ordering::Relaxed);
+        }
+        (dot, dash)
+    }
+
+    // timing follows PARIS, a dot is 1200/wpm ms. Weight 50 is a 1:1 mark/space,
+    // heavier weights lengthen the marks at the expense of the spaces.
+    pub fn configure(&mut self, mode: Keyer, speed: i32, weight: i32, spacing: bool, ptt_delay: i32, hang_time: i32) {
+        let speed = speed.clamp(1, 60);
+        let dot = (KEYER_SAMPLE_RATE * 6 / 5 / speed) as i64;
+        let adjust = dot * (weight.clamp(0, 100) as i64 - 50) / 50;
+        let adjust = adjust.clamp(1 - dot, dot - 1);
+        self.mode = Some(mode);
+        self.unit_samples = dot as usize;
+        self.dot_samples = (dot + adjust) as usize;
+        self.dash_samples = (dot * 3 + adjust) as usize;
+        self.space_samples = (dot - adjust) as usize;
+        self.spacing = spacing;
+        self.delay_samples = (ptt_delay.max(0) * KEYER_SAMPLE_RATE / 1000) as usize;
+        self.hang_samples = (hang_time.max(0) * KEYER_SAMPLE_RATE / 1000) as usize;
+    }
+
+    // queue text to be sent, this is the type ahead buffer
+    pub fn send_text(&mut self, text: &str) {
+        self.text.extend(text.chars());
+    }
+
+    pub fn abort_text(&mut self) {
+        self.text.clear();
+        self.text_elements.clear();
+        self.text_count = 0;
+        if self.text_active {
+            self.key_down = false;
+        }
+        self.text_active = false;
+    }
+
+    // the text waiting to be sent
+    pub fn pending_text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    // true from the first queued character until the hang time after the last one
+    pub fn is_sending(&self) -> bool {
+        self.text_active || !self.text.is_empty()
+    }
+
+    // a macro with {MYCALL}, {CALL} and {NR} filled in
+    pub fn expand_macro(&self, index: usize) -> String {
+        let text = match self.macros.get(index) {
+            Some(text) => text.clone(),
+            None => String::from(""),
+        };
+        text.replace("{MYCALL}", &self.mycall)
+            .replace("{CALL}", &self.call)
+            .replace("{NR}", &format!("{:03}", self.nr))
+            .to_uppercase()
+    }
+
+    // step the keyer one sample and return the shaped envelope, 0.0 to 1.0
+    pub fn process(&mut self, dot: bool, dash: bool) -> f64 {
+        if self.is_sending() && (dot || dash) {
+            // touching the paddle stops the message
+            self.abort_text();
+        }
+        if self.is_sending() {
+            self.text_step();
+        } else {
+            match self.mode {
+                Some(Keyer::Straight) | None => {
+                    self.state = KeyerState::Idle;
+                    self.key_down = dot || dash;
+                }
+                Some(mode) => self.iambic(mode, dot, dash),
+            }
+        }
+
+        if self.key_down {
+            self.hang = self.hang_samples;
+            if self.ramp < RAMP_SAMPLES {
+                self.ramp += 1;
+            }
+        } else {
+            if self.ramp > 0 {
+                self.ramp -= 1;
+            } else if self.hang > 0 {
+                self.hang -= 1;
+            }
+        }
+        self.envelope()
+    }
+
+    fn iambic(&mut self, mode: Keyer, dot: bool, dash: bool) {
+        self.state = match std::mem::take(&mut self.state) {
+            KeyerState::Idle => {
+                if dot || dash {
+                    let element = if dot { Element::Dot } else { Element::Dash };
+                    if self.hang == 0 && self.ramp == 0 && self.delay_samples > 0 {
+                        // give the radio time to switch to transmit
+                        KeyerState::Delay(element, self.delay_samples)
+                    } else {
+                        self.start(element)
+                    }
+                } else {
+                    KeyerState::Idle
+                }
+            }
+            KeyerState::Delay(element, count) => {
+                if count <= 1 {
+                    self.start(element)
+                } else {
+                    KeyerState::Delay(element, count - 1)
+                }
+            }
+            KeyerState::Mark(element, count) => {
+                self.latch(element, dot, dash);
+                if count <= 1 {
+                    self.key_down = false;
+                    if mode == Keyer::ModeA && !dot && !dash {
+                        // mode A stops as soon as both paddles are released
+                        self.dot_memory = false;
+                        self.dash_memory = false;
+                    }
+                    KeyerState::Space(element, self.space_samples)
+                } else {
+                    KeyerState::Mark(element, count - 1)
+                }
+            }
+            KeyerState::Space(element, count) => {
+                if mode == Keyer::ModeB {
+                    self.latch(element, dot, dash);
+                }
+                if count <= 1 {
+                    let next = match element {
+                        Element::Dot if dash || self.dash_memory => Some(Element::Dash),
+                        Element::Dot if dot || self.dot_memory => Some(Element::Dot),
+                        Element::Dash if dot || self.dot_memory => Some(Element::Dot),
+                        Element::Dash if dash || self.dash_memory => Some(Element::Dash),
+                        _ => None,
+                    };
+                    match next {
+                        Some(element) => self.start(element),
+                        None if self.spacing => KeyerState::LetterSpace(self.space_samples * 2),
+                        None => KeyerState::Idle,
+                    }
+                } else {
+                    KeyerState::Space(element, count - 1)
+                }
+            }
+            KeyerState::LetterSpace(count) => {
+                if count <= 1 {
+                    KeyerState::Idle
+                } else {
+                    KeyerState::LetterSpace(count - 1)
+                }
+            }
+        };
+    }
+
+    fn text_step(&mut self) {
+        if !self.text_active {
+            self.text_active = true;
+            self.state = KeyerState::Idle;
+            if self.hang == 0 && self.ramp == 0 && self.delay_samples > 0 {
+                // give the radio time to switch to transmit
+                self.text_elements.push_back((false, self.delay_samples));
+            }
+        }
+        while self.text_count == 0 {
+            if let Some((key_down, count)) = self.text_elements.pop_front() {
+                self.key_down = key_down;
+                self.text_count = count;
+            } else if let Some(ch) = self.text.pop_front() {
+                self.text_character(ch);
+            } else {
+                // all sent, stay active until the hang time runs out
+                self.key_down = false;
+                if self.hang == 0 && self.ramp == 0 {
+                    self.text_active = false;
+                }
+                return;
+            }
+        }
+        self.text_count -= 1;
+    }
+
+    // marks and spaces for a character, the element space is part of each element
+    fn text_character(&mut self, ch: char) {
+        if ch.is_whitespace() {
+            // a word space is 7 units, 3 have already been sent after the last character
+            self.text_elements.push_back((false, self.unit_samples * 4));
+            return;
+        }
+        if let Some(code) = char_to_morse(ch) {
+            for symbol in code.chars() {
+                if symbol == '.' {
+                    self.text_elements.push_back((true, self.dot_samples));
+                } else {
+                    self.text_elements.push_back((true, self.dash_samples));
+                }
+                self.text_elements.push_back((false, self.space_samples));
+            }
+            // character space is 3 units
+            self.text_elements.push_back((false, self.unit_samples * 2));
+        }
+    }
+
+    fn start(&mut self, element: Element) -> KeyerState {
+        self.key_down = true;
+        match element {
+            Element::Dot => {
+                self.dot_memory = false;
+                KeyerState::Mark(element, self.dot_samples)
+            }
+            Element::Dash => {
+                self.dash_memory = false;
+                KeyerState::Mark(element, self.dash_samples)
+            }
+        }
+    }
+
+    // remember the opposite paddle being pressed while sending an element
+    fn latch(&mut self, element: Element, dot: bool, dash: bool) {
+        match element {
+            Element::Dot => self.dash_memory |= dash,
+            Element::Dash => self.dot_memory |= dot,
+        }
+    }
+
+    pub fn envelope(&self) -> f64 {
+        0.5 * (1.0 - (PI * self.ramp as f64 / RAMP_SAMPLES as f64).cos())
+    }
+
+    // true while keying, ramping or within the hang time
+    pub fn is_keyed(&self) -> bool {
+        match self.state {
+            KeyerState::Idle => self.key_down || self.ramp > 0 || self.hang > 0,
+            _ => true,
+        }
+    }
+
+    // add a sidetone sample, returns a block of stereo samples when one is ready for the audio output
+    pub fn sidetone(&mut self, frequency: i32, volume: i32) -> Option<Vec<i16>> {
+        let amplitude = self.envelope() * volume.clamp(0, 127) as f64 / 127.0;
+        let sample = (amplitude * self.sidetone_phase.sin() * 32767.0) as i16;
+        self.sidetone_phase += 2.0 * PI * frequency as f64 / KEYER_SAMPLE_RATE as f64;
+        if self.sidetone_phase > 2.0 * PI {
+            self.sidetone_phase -= 2.0 * PI;
+        }
+        let x = self.sidetone_offset * 2;
+        self.sidetone_buffer[x] = sample;
+        self.sidetone_buffer[x+1] = sample;
+        self.sidetone_offset += 1;
+        if self.sidetone_offset >= SIDETONE_FRAMES {
+            self.sidetone_offset = 0;
+            return Some(self.sidetone_buffer.clone());
+        }
+        None
+    }
+}
+
+// queue text on the software keyer and make sure the radio's internal keyer gets out of the way
+fn cw_send(radio_mutex: &RadioMutex, text: &str) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    let sending = r.keyer.is_sending();
+    r.keyer.send_text(text);
+    if !sending && r.keyer.is_sending() {
+        r.updated = true;
+    }
+}
+
+pub fn create_cw_text_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMutex) -> Window {
+
+    let ui_xml = include_str!("../ui/cwtext.xml");
+    let builder = Builder::from_string(ui_xml);
+
+    let window: Window = builder
+            .object("cw_text_window")
+            .expect("Could not get object `cw_text_window` from builder.");
+
+    window.set_transient_for(Some(parent)); // keeps it on top
+
+    let r = radio_mutex.radio.lock().unwrap();
+        let mycall = r.keyer.mycall.clone();
+        let call = r.keyer.call.clone();
+        let nr = r.keyer.nr;
+        let macros = r.keyer.macros.clone();
+    drop(r);
+
+    let mycall_entry: Entry = builder
+            .object("cw_mycall_entry")
+            .expect("Could not get object `cw_mycall_entry` from builder.");
+    mycall_entry.set_text(&mycall);
+    let radio_mutex_clone = radio_mutex.clone();
+    mycall_entry.connect_changed(move |entry| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.keyer.mycall = entry.text().to_uppercase();
+    });
+
+    let call_entry: Entry = builder
+            .object("cw_call_entry")
+            .expect("Could not get object `cw_call_entry` from builder.");
+    call_entry.set_text(&call);
+    let radio_mutex_clone = radio_mutex.clone();
+    call_entry.connect_changed(move |entry| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.keyer.call = entry.text().to_uppercase();
+    });
+
+    let nr_adjustment: Adjustment = builder
+            .object("cw_nr_adjustment")
+            .expect("Could not get object `cw_nr_adjustment` from builder.");
+    nr_adjustment.set_value(nr as f64);
+    let radio_mutex_clone = radio_mutex.clone();
+    nr_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.keyer.nr = adjustment.value() as u32;
+    });
+
+    for i in 0..CW_MACROS {
+        let macro_entry: Entry = builder
+                .object(format!("cw_macro_{}_entry", i))
+                .expect("Could not get object `cw_macro_entry` from builder.");
+        macro_entry.set_text(&macros[i]);
+        let radio_mutex_clone = radio_mutex.clone();
+        macro_entry.connect_changed(move |entry| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.keyer.macros[i] = entry.text().to_string();
+        });
+
+        let macro_button: Button = builder
+                .object(format!("cw_macro_{}_button", i))
+                .expect("Could not get object `cw_macro_button` from builder.");
+        let radio_mutex_clone = radio_mutex.clone();
+        macro_button.connect_clicked(move |_| {
+            let r = radio_mutex_clone.radio.lock().unwrap();
+            let text = r.keyer.expand_macro(i);
+            drop(r);
+            cw_send(&radio_mutex_clone, &format!("{} ", text));
+        });
+    }
+
+    let text_entry: Entry = builder
+            .object("cw_text_entry")
+            .expect("Could not get object `cw_text_entry` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    text_entry.connect_activate(move |entry| {
+        let text = entry.text().to_uppercase();
+        entry.set_text("");
+        cw_send(&radio_mutex_clone, &format!("{} ", text));
+    });
+
+    let abort_button: Button = builder
+            .object("cw_abort_button")
+            .expect("Could not get object `cw_abort_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    abort_button.connect_clicked(move |_| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.keyer.abort_text();
+    });
+
+    // F1 to F8 send the macros, Escape aborts
+    let key_controller = EventControllerKey::new();
+    let radio_mutex_clone = radio_mutex.clone();
+    key_controller.connect_key_pressed(move |_controller, keyval, _keycode, _state| {
+        let function_keys = [gdk::Key::F1, gdk::Key::F2, gdk::Key::F3, gdk::Key::F4, gdk::Key::F5, gdk::Key::F6, gdk::Key::F7, gdk::Key::F8];
+        if let Some(i) = function_keys.iter().position(|key| *key == keyval) {
+            let r = radio_mutex_clone.radio.lock().unwrap();
+            let text = r.keyer.expand_macro(i);
+            drop(r);
+            cw_send(&radio_mutex_clone, &format!("{} ", text));
+            return Propagation::Stop;
+        }
+        if keyval == gdk::Key::Escape {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            r.keyer.abort_text();
+            return Propagation::Stop;
+        }
+        Propagation::Proceed
+    });
+    window.add_controller(key_controller);
+
+    let sending_label: Label = builder
+            .object("cw_sending_label")
+            .expect("Could not get object `cw_sending_label` from builder.");
+    let pending_label: Label = builder
+            .object("cw_pending_label")
+            .expect("Could not get object `cw_pending_label` from builder.");
+
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    timeout_add_local(Duration::from_millis(100), move || {
+        if !window_clone.is_visible() {
+            return Break;
+        }
+        let r = radio_mutex_clone.radio.lock().unwrap();
+        let sending = r.keyer.is_sending();
+        let pending = r.keyer.pending_text();
+        drop(r);
+        if sending {
+            sending_label.set_label("Sending");
+            sending_label.remove_css_class("cw-idle");
+            sending_label.add_css_class("cw-sending");
+        } else {
+            sending_label.set_label("Idle");
+            sending_label.remove_css_class("cw-sending");
+            sending_label.add_css_class("cw-idle");
+        }
+        pending_label.set_label(&pending);
+        Continue
+    });
+
+    window
+}
@@ -35,4 +35,15 @@ pub mod waterfall;
 pub mod meter;
 pub mod util;
 pub mod notches;
+pub mod midi;
+pub mod dxcluster;
+pub mod bandplan;
+pub mod memories;
+pub mod scanner;
+pub mod repeater;
+pub mod keyer;
+pub mod morse;
+pub mod txprofiles;
+pub mod txtest;
+pub mod noise;
 
@@ -18,8 +18,9 @@
 use glib::ControlFlow::Continue;
 use glib::timeout_add_local;
 use gtk::prelude::*;
-use gtk::{Adjustment, Application, ApplicationWindow, Builder, Button, DrawingArea, DropDown, Frame, Grid, Label, ToggleButton};
-use gtk::{EventController, EventControllerMotion, EventControllerScroll, EventControllerScrollFlags, GestureClick};
+use gtk::{Adjustment, Application, ApplicationWindow, Builder, Button, DrawingArea, DropDown, Frame, Grid, Label, LevelBar, ScrolledWindow, StringList, TextView, ToggleButton};
+use gtk::{EventController, EventControllerKey, EventControllerMotion, EventControllerScroll, EventControllerScrollFlags, GestureClick, GestureDrag};
+use gtk::gdk;
 use gtk::gdk::Cursor;
 use gtk::glib::Propagation;
 
@@ -38,6 +39,7 @@ use std::time::Duration;
 
 use rustyHPSDR::agc::*;
 use rustyHPSDR::bands::*;
+use rustyHPSDR::bandplan::SegmentType;
 use rustyHPSDR::modes::*;
 use rustyHPSDR::filters::*;
 use rustyHPSDR::discovery::create_discovery_dialog;
@@ -53,18 +55,49 @@ use rustyHPSDR::meter::*;
 use rustyHPSDR::util::*;
 use rustyHPSDR::wdsp::*;
 use rustyHPSDR::notches::*;
+use rustyHPSDR::midi::*;
+use rustyHPSDR::morse::*;
+use rustyHPSDR::dxcluster::*;
+use rustyHPSDR::memories::*;
+use rustyHPSDR::scanner::*;
+use rustyHPSDR::keyer::*;
+use rustyHPSDR::txtest::*;
+use rustyHPSDR::repeater::*;
+use rustyHPSDR::noise::*;
+use rustyHPSDR::transmitter::SwrProtection;
 
 struct AppWidgets {
     pub main_window: ApplicationWindow,
     pub configure_button: Button,
+    pub dxcluster_button: Button,
+    pub memories_button: Button,
+    pub scanner_button: Button,
+    pub cw_text_button: Button,
+    pub tx_test_button: Button,
     pub vfo_a_frequency: Label,
     pub vfo_b_frequency: Label,
+    pub vfo_a_segment: Label,
+    pub vfo_b_segment: Label,
     pub a_to_b_button: Button,
     pub b_to_a_button: Button,
     pub a_swap_b_button: Button,
     pub split_button: ToggleButton,
     pub ctun_button: ToggleButton,
+    pub rit_button: ToggleButton,
+    pub xit_button: ToggleButton,
+    pub rit_adjustment: Adjustment,
+    pub xit_adjustment: Adjustment,
+    pub rit_clear_button: Button,
+    pub xit_clear_button: Button,
+    pub up1_button: Button,
+    pub up5_button: Button,
+    pub subrx_button: ToggleButton,
+    pub subrx_balance_adjustment: Adjustment,
+    pub subrx_mode_dropdown: DropDown,
+    pub subrx_filter_dropdown: DropDown,
     pub rx2_button: ToggleButton,
+    pub reverse_button: ToggleButton,
+    pub repeater_button: Button,
     pub step_dropdown: DropDown,
     pub meter_1_display: DrawingArea,
     pub meter_2_display: DrawingArea,
@@ -77,8 +110,17 @@ struct AppWidgets {
     pub pan_adjustment: Adjustment,
     pub nr_button: ToggleButton,
     pub nb_button: ToggleButton,
+    pub nb2_button: ToggleButton,
+    pub noise_button: Button,
     pub anf_button: ToggleButton,
     pub snb_button: ToggleButton,
+    pub auto_notch_button: ToggleButton,
+    pub squelch_button: ToggleButton,
+    pub squelch_adjustment: Adjustment,
+    pub squelch_indicator: Label,
+    pub vox_button: ToggleButton,
+    pub vox_adjustment: Adjustment,
+    pub vox_level_bar: LevelBar,
     pub mox_button: ToggleButton,
     pub tun_button: ToggleButton,
     pub afgain_adjustment: Adjustment,
@@ -94,10 +136,23 @@ struct AppWidgets {
     pub mode_grid: ModeGrid,
     pub filter_grid: FilterGrid,
     pub cwpitch_adjustment: Adjustment,
+    pub cw_decoder_button: ToggleButton,
+    pub cw_decoder_threshold_adjustment: Adjustment,
+    pub cw_decoder_wpm_label: Label,
+    pub cw_decoder_window: ScrolledWindow,
+    pub cw_decoder_text_view: TextView,
+    pub cw_decoder_2_window: ScrolledWindow,
+    pub cw_decoder_2_text_view: TextView,
     pub low_adjustment: Adjustment,
     pub high_adjustment: Adjustment,
     pub tx_power: Label,
     pub tx_swr: Label,
+    pub tx_reflected: Label,
+    pub tx_alc: Label,
+    pub tx_status: Label,
+    pub swr_alarm_button: Button,
+    pub tx_inhibit_button: ToggleButton,
+    pub tx_profile_dropdown: DropDown,
 }
 
 impl AppWidgets {
@@ -110,6 +165,26 @@ impl AppWidgets {
             .object("configure_button")
             .expect("Could not get configure_button from builder");
 
+        let dxcluster_button: Button = builder
+            .object("dxcluster_button")
+            .expect("Could not get dxcluster_button from builder");
+
+        let memories_button: Button = builder
+            .object("memories_button")
+            .expect("Could not get memories_button from builder");
+
+        let scanner_button: Button = builder
+            .object("scanner_button")
+            .expect("Could not get scanner_button from builder");
+
+        let cw_text_button: Button = builder
+            .object("cw_text_button")
+            .expect("Could not get cw_text_button from builder");
+
+        let tx_test_button: Button = builder
+            .object("tx_test_button")
+            .expect("Could not get tx_test_button from builder");
+
         let vfo_a_frequency: Label = builder
             .object("vfo_a_frequency")
             .expect("Could not get vfo_a_frequency from builder");
@@ -118,6 +193,14 @@ impl AppWidgets {
             .object("vfo_b_frequency")
             .expect("Could not get vfo_b_frequency from builder");
 
+        let vfo_a_segment: Label = builder
+            .object("vfo_a_segment")
+            .expect("Could not get vfo_a_segment from builder");
+
+        let vfo_b_segment: Label = builder
+            .object("vfo_b_segment")
+            .expect("Could not get vfo_b_segment from builder");
+
         let a_to_b_button: Button = builder
             .object("a_to_b_button")
             .expect("Could not get a_to_b_button from builder");
@@ -138,10 +221,66 @@ impl AppWidgets {
             .object("ctun_button")
             .expect("Could not get ctun_button from builder");
 
+        let rit_button: ToggleButton = builder
+            .object("rit_button")
+            .expect("Could not get rit_button from builder");
+
+        let xit_button: ToggleButton = builder
+            .object("xit_button")
+            .expect("Could not get xit_button from builder");
+
+        let rit_adjustment: Adjustment = builder
+            .object("rit_adjustment")
+            .expect("Could not get rit_adjustment from builder");
+
+        let xit_adjustment: Adjustment = builder
+            .object("xit_adjustment")
+            .expect("Could not get xit_adjustment from builder");
+
+        let rit_clear_button: Button = builder
+            .object("rit_clear_button")
+            .expect("Could not get rit_clear_button from builder");
+
+        let xit_clear_button: Button = builder
+            .object("xit_clear_button")
+            .expect("Could not get xit_clear_button from builder");
+
+        let up1_button: Button = builder
+            .object("up1_button")
+            .expect("Could not get up1_button from builder");
+
+        let up5_button: Button = builder
+            .object("up5_button")
+            .expect("Could not get up5_button from builder");
+
+        let subrx_button: ToggleButton = builder
+            .object("subrx_button")
+            .expect("Could not get subrx_button from builder");
+
+        let subrx_balance_adjustment: Adjustment = builder
+            .object("subrx_balance_adjustment")
+            .expect("Could not get subrx_balance_adjustment from builder");
+
+        let subrx_mode_dropdown: DropDown = builder
+            .object("subrx_mode_dropdown")
+            .expect("Could not get subrx_mode_dropdown from builder");
+
+        let subrx_filter_dropdown: DropDown = builder
+            .object("subrx_filter_dropdown")
+            .expect("Could not get subrx_filter_dropdown from builder");
+
         let rx2_button: ToggleButton = builder
             .object("rx2_button")
             .expect("Could not get rx2_button from builder");
 
+        let reverse_button: ToggleButton = builder
+            .object("reverse_button")
+            .expect("Could not get reverse_button from builder");
+
+        let repeater_button: Button = builder
+            .object("repeater_button")
+            .expect("Could not get repeater_button from builder");
+
         let step_dropdown = builder
             .object("step_dropdown")
             .expect("Could not get step_dropdown from builder");
@@ -202,6 +341,14 @@ impl AppWidgets {
             .object("nb_button")
             .expect("Could not get nb_button from builder");
 
+        let nb2_button: ToggleButton = builder
+            .object("nb2_button")
+            .expect("Could not get nb2_button from builder");
+
+        let noise_button: Button = builder
+            .object("noise_button")
+            .expect("Could not get noise_button from builder");
+
         let anf_button: ToggleButton = builder
             .object("anf_button")
             .expect("Could not get anf_button from builder");
@@ -210,6 +357,34 @@ impl AppWidgets {
             .object("snb_button")
             .expect("Could not get snb_button from builder");
 
+        let auto_notch_button: ToggleButton = builder
+            .object("auto_notch_button")
+            .expect("Could not get auto_notch_button from builder");
+
+        let squelch_button: ToggleButton = builder
+            .object("squelch_button")
+            .expect("Could not get squelch_button from builder");
+
+        let squelch_adjustment: Adjustment = builder
+            .object("squelch_adjustment")
+            .expect("Could not get squelch_adjustment from builder");
+
+        let squelch_indicator: Label = builder
+            .object("squelch_indicator")
+            .expect("Could not get squelch_indicator from builder");
+
+        let vox_button: ToggleButton = builder
+            .object("vox_button")
+            .expect("Could not get vox_button from builder");
+
+        let vox_adjustment: Adjustment = builder
+            .object("vox_adjustment")
+            .expect("Could not get vox_adjustment from builder");
+
+        let vox_level_bar: LevelBar = builder
+            .object("vox_level_bar")
+            .expect("Could not get vox_level_bar from builder");
+
         let mox_button: ToggleButton = builder
             .object("mox_button")
             .expect("Could not get mox_button from builder");
@@ -246,6 +421,34 @@ impl AppWidgets {
             .object("cwpitch_adjustment")
             .expect("Could not get cwpitch_adjustment from builder");
 
+        let cw_decoder_button: ToggleButton = builder
+            .object("cw_decoder_button")
+            .expect("Could not get cw_decoder_button from builder");
+
+        let cw_decoder_threshold_adjustment: Adjustment = builder
+            .object("cw_decoder_threshold_adjustment")
+            .expect("Could not get cw_decoder_threshold_adjustment from builder");
+
+        let cw_decoder_wpm_label: Label = builder
+            .object("cw_decoder_wpm_label")
+            .expect("Could not get cw_decoder_wpm_label from builder");
+
+        let cw_decoder_window: ScrolledWindow = builder
+            .object("cw_decoder_window")
+            .expect("Could not get cw_decoder_window from builder");
+
+        let cw_decoder_text_view: TextView = builder
+            .object("cw_decoder_text_view")
+            .expect("Could not get cw_decoder_text_view from builder");
+
+        let cw_decoder_2_window: ScrolledWindow = builder
+            .object("cw_decoder_2_window")
+            .expect("Could not get cw_decoder_2_window from builder");
+
+        let cw_decoder_2_text_view: TextView = builder
+            .object("cw_decoder_2_text_view")
+            .expect("Could not get cw_decoder_2_text_view from builder");
+
         let low_adjustment: Adjustment = builder
             .object("low_adjustment")
             .expect("Could not get low_adjustment from builder");
@@ -278,17 +481,62 @@ impl AppWidgets {
             .object("tx_swr")
             .expect("Could not get tx_swr from builder");
 
+        let tx_reflected: Label = builder
+            .object("tx_reflected")
+            .expect("Could not get tx_reflected from builder");
+
+        let tx_alc: Label = builder
+            .object("tx_alc")
+            .expect("Could not get tx_alc from builder");
+
+        let tx_status: Label = builder
+            .object("tx_status")
+            .expect("Could not get tx_status from builder");
+
+        let swr_alarm_button: Button = builder
+            .object("swr_alarm_button")
+            .expect("Could not get swr_alarm_button from builder");
+
+        let tx_inhibit_button: ToggleButton = builder
+            .object("tx_inhibit_button")
+            .expect("Could not get tx_inhibit_button from builder");
+
+        let tx_profile_dropdown: DropDown = builder
+            .object("tx_profile_dropdown")
+            .expect("Could not get tx_profile_dropdown from builder");
+
         AppWidgets {
             main_window,
             configure_button,
+            dxcluster_button,
+            memories_button,
+            scanner_button,
+            cw_text_button,
+            tx_test_button,
             vfo_a_frequency,
             vfo_b_frequency,
+            vfo_a_segment,
+            vfo_b_segment,
             a_to_b_button,
             b_to_a_button,
             a_swap_b_button,
             split_button,
             ctun_button,
+            rit_button,
+            xit_button,
+            rit_adjustment,
+            xit_adjustment,
+            rit_clear_button,
+            xit_clear_button,
+            up1_button,
+            up5_button,
+            subrx_button,
+            subrx_balance_adjustment,
+            subrx_mode_dropdown,
+            subrx_filter_dropdown,
             rx2_button,
+            reverse_button,
+            repeater_button,
             step_dropdown,
             meter_1_display,
             meter_2_display,
@@ -301,8 +549,17 @@ impl AppWidgets {
             pan_adjustment,
             nr_button,
             nb_button,
+            nb2_button,
+            noise_button,
             anf_button,
             snb_button,
+            auto_notch_button,
+            squelch_button,
+            squelch_adjustment,
+            squelch_indicator,
+            vox_button,
+            vox_adjustment,
+            vox_level_bar,
             mox_button,
             tun_button,
             afgain_adjustment,
@@ -312,6 +569,13 @@ impl AppWidgets {
             micgain_adjustment,
             drive_adjustment,
             cwpitch_adjustment,
+            cw_decoder_button,
+            cw_decoder_threshold_adjustment,
+            cw_decoder_wpm_label,
+            cw_decoder_window,
+            cw_decoder_text_view,
+            cw_decoder_2_window,
+            cw_decoder_2_text_view,
             low_adjustment,
             high_adjustment,
             band_frame,
@@ -322,6 +586,12 @@ impl AppWidgets {
             filter_grid,
             tx_power,
             tx_swr,
+            tx_reflected,
+            tx_alc,
+            tx_status,
+            swr_alarm_button,
+            tx_inhibit_button,
+            tx_profile_dropdown,
         }
     }
 }
@@ -536,6 +806,19 @@ fn build_ui(app: &Application) {
                         style_context.add_class("toggle");
                         app_widgets.ctun_button.set_active(r.receiver[rx].ctun);
 
+                        app_widgets.rit_button.add_css_class("toggle");
+                        app_widgets.rit_button.set_active(r.receiver[rx].rit);
+                        app_widgets.rit_adjustment.set_value(r.receiver[rx].rit_offset.into());
+                        app_widgets.xit_button.add_css_class("toggle");
+                        app_widgets.xit_button.set_active(r.receiver[rx].xit);
+                        app_widgets.xit_adjustment.set_value(r.receiver[rx].xit_offset.into());
+
+                        app_widgets.subrx_button.add_css_class("toggle");
+                        app_widgets.subrx_button.set_active(r.receiver[rx].subrx);
+                        app_widgets.subrx_balance_adjustment.set_value((r.receiver[rx].subrx_balance * 100.0).into());
+                        app_widgets.subrx_mode_dropdown.set_selected(r.receiver[rx].subrx_mode as u32);
+                        subrx_filter_list(&app_widgets, r.receiver[rx].subrx_mode, r.receiver[rx].subrx_filter);
+
                         let style_context = app_widgets.split_button.style_context();
                         style_context.add_class("toggle");
                         app_widgets.split_button.set_active(r.split);
@@ -543,6 +826,10 @@ fn build_ui(app: &Application) {
                         let style_context = app_widgets.rx2_button.style_context();
                         style_context.add_class("toggle");
                         app_widgets.rx2_button.set_active(r.rx2_enabled);
+
+                        let style_context = app_widgets.reverse_button.style_context();
+                        style_context.add_class("toggle");
+                        app_widgets.reverse_button.set_active(r.transmitter.reverse);
                         if r.rx2_enabled {
                             app_widgets.spectrum_2_display.set_visible(true);
                             app_widgets.waterfall_2_display.set_visible(true);
@@ -608,15 +895,11 @@ fn build_ui(app: &Application) {
                         
                         let style_context = app_widgets.nb_button.style_context();
                         style_context.add_class("toggle");
-                        app_widgets.nb_button.set_active(r.receiver[rx].nb | r.receiver[rx].nb2);
-                        if r.receiver[rx].nb {
-                            r.receiver[rx].set_nb();
-                            app_widgets.nb_button.set_label("NB");
-                        }
-                        if r.receiver[rx].nb2 {
-                            r.receiver[rx].set_nb2();
-                            app_widgets.nb_button.set_label("NB2");
-                        }
+                        app_widgets.nb_button.set_active(r.receiver[rx].nb);
+                        r.receiver[rx].set_nb();
+                        app_widgets.nb2_button.add_css_class("toggle");
+                        app_widgets.nb2_button.set_active(r.receiver[rx].nb2);
+                        r.receiver[rx].set_nb2();
 
                         let style_context = app_widgets.anf_button.style_context();
                         style_context.add_class("toggle");
@@ -628,6 +911,26 @@ fn build_ui(app: &Application) {
                         app_widgets.snb_button.set_active(r.receiver[rx].snb);
                         r.receiver[rx].set_snb();
 
+                        app_widgets.auto_notch_button.add_css_class("toggle");
+                        app_widgets.auto_notch_button.set_active(r.receiver[rx].auto_notch);
+
+                        let style_context = app_widgets.squelch_button.style_context();
+                        style_context.add_class("toggle");
+                        app_widgets.squelch_button.set_active(r.receiver[rx].squelch);
+                        app_widgets.squelch_adjustment.set_value(r.receiver[rx].squelch_level.into());
+
+                        let style_context = app_widgets.vox_button.style_context();
+                        style_context.add_class("toggle");
+                        app_widgets.vox_button.set_active(r.vox_enabled);
+                        app_widgets.vox_adjustment.set_value(r.vox_threshold.into());
+
+                        let style_context = app_widgets.cw_decoder_button.style_context();
+                        style_context.add_class("toggle");
+                        app_widgets.cw_decoder_button.set_active(r.receiver[rx].cw_decoder);
+                        app_widgets.cw_decoder_threshold_adjustment.set_value(r.receiver[rx].cw_decoder_threshold.into());
+                        app_widgets.cw_decoder_window.set_visible(r.receiver[0].cw_decoder);
+                        app_widgets.cw_decoder_2_window.set_visible(r.rx2_enabled && r.receiver[1].cw_decoder);
+
                         let style_context = app_widgets.mox_button.style_context();
                         style_context.add_class("toggle");
 
@@ -651,6 +954,81 @@ fn build_ui(app: &Application) {
                         });
                     });                         
 
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.dxcluster_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let dxcluster_dialog = create_dxcluster_dialog(&app_widgets.main_window, &radio_mutex_clone);
+                        app_widgets.dxcluster_button.set_sensitive(false);
+                        dxcluster_dialog.present();
+                        let rc_app_widgets = rc_app_widgets_clone_clone.clone();
+                        dxcluster_dialog.connect_close_request(move |_| {
+                            let app_widgets = rc_app_widgets.borrow();
+                            app_widgets.dxcluster_button.set_sensitive(true);
+                            Propagation::Proceed
+                        });
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.memories_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let memories_dialog = create_memories_dialog(&app_widgets.main_window, &radio_mutex_clone);
+                        app_widgets.memories_button.set_sensitive(false);
+                        memories_dialog.present();
+                        let rc_app_widgets = rc_app_widgets_clone_clone.clone();
+                        memories_dialog.connect_close_request(move |_| {
+                            let app_widgets = rc_app_widgets.borrow();
+                            app_widgets.memories_button.set_sensitive(true);
+                            Propagation::Proceed
+                        });
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.scanner_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let scanner_dialog = create_scanner_dialog(&app_widgets.main_window, &radio_mutex_clone);
+                        app_widgets.scanner_button.set_sensitive(false);
+                        scanner_dialog.present();
+                        let rc_app_widgets = rc_app_widgets_clone_clone.clone();
+                        scanner_dialog.connect_close_request(move |_| {
+                            let app_widgets = rc_app_widgets.borrow();
+                            app_widgets.scanner_button.set_sensitive(true);
+                            Propagation::Proceed
+                        });
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.cw_text_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let cw_text_dialog = create_cw_text_dialog(&app_widgets.main_window, &radio_mutex_clone);
+                        app_widgets.cw_text_button.set_sensitive(false);
+                        cw_text_dialog.present();
+                        let rc_app_widgets = rc_app_widgets_clone_clone.clone();
+                        cw_text_dialog.connect_close_request(move |_| {
+                            let app_widgets = rc_app_widgets.borrow();
+                            app_widgets.cw_text_button.set_sensitive(true);
+                            Propagation::Proceed
+                        });
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.tx_test_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let tx_test_dialog = create_tx_test_dialog(&app_widgets.main_window, &radio_mutex_clone);
+                        app_widgets.tx_test_button.set_sensitive(false);
+                        tx_test_dialog.present();
+                        let rc_app_widgets = rc_app_widgets_clone_clone.clone();
+                        tx_test_dialog.connect_close_request(move |_| {
+                            let app_widgets = rc_app_widgets.borrow();
+                            app_widgets.tx_test_button.set_sensitive(true);
+                            Propagation::Proceed
+                        });
+                    });
+
                     let scroll_controller_a = EventControllerScroll::new(
                         EventControllerScrollFlags::VERTICAL
                     );
@@ -673,6 +1051,18 @@ fn build_ui(app: &Application) {
                     });
                     app_widgets.vfo_b_frequency.add_controller(scroll_controller_b);
 
+                    // keyboard paddle for the software keyer, left Ctrl is dot and right Ctrl is dash
+                    let key_controller = EventControllerKey::new();
+                    let radio_mutex_clone = radio_mutex.clone();
+                    key_controller.connect_key_pressed(move |_controller, keyval, _keycode, _state| {
+                        keyboard_paddle(&radio_mutex_clone, keyval, true)
+                    });
+                    let radio_mutex_clone = radio_mutex.clone();
+                    key_controller.connect_key_released(move |_controller, keyval, _keycode, _state| {
+                        keyboard_paddle(&radio_mutex_clone, keyval, false);
+                    });
+                    app_widgets.main_window.add_controller(key_controller);
+
 
                     let radio_mutex_clone = radio_mutex.clone();
                     let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
@@ -687,9 +1077,6 @@ fn build_ui(app: &Application) {
                         r.receiver[1].band = r.receiver[0].band; 
                         let formatted_value = format_u32_with_separators(r.receiver[1].frequency as u32);
                         app_widgets.vfo_b_frequency.set_label(&formatted_value);
-                        unsafe {
-                            RXANBPSetTuneFrequency(1, r.receiver[1].frequency as f64);
-                        }
                     });                         
                     
                     let radio_mutex_clone = radio_mutex.clone();
@@ -706,9 +1093,6 @@ fn build_ui(app: &Application) {
                         r.receiver[0].band = r.receiver[1].band; 
                         let formatted_value = format_u32_with_separators(r.receiver[0].frequency as u32);
                         app_widgets.vfo_a_frequency.set_label(&formatted_value);
-                        unsafe {
-                            RXANBPSetTuneFrequency(0, r.receiver[0].frequency as f64);
-                        }
                     });
 
                     let radio_mutex_clone = radio_mutex.clone();
@@ -734,10 +1118,6 @@ fn build_ui(app: &Application) {
                         r.receiver[0].band = temp_band;
                         let formatted_value = format_u32_with_separators(r.receiver[1].frequency as u32);
                         app_widgets.vfo_b_frequency.set_label(&formatted_value);
-                        unsafe {
-                            RXANBPSetTuneFrequency(0, r.receiver[0].frequency as f64);
-                            RXANBPSetTuneFrequency(1, r.receiver[1].frequency as f64);
-                        }
                     });
 
                     let radio_mutex_clone = radio_mutex.clone();
@@ -770,6 +1150,199 @@ fn build_ui(app: &Application) {
                     app_widgets.split_button.connect_clicked(move |button| {
                         let mut r = radio_mutex_clone.radio.lock().unwrap();
                         r.split = button.is_active();
+                        r.update_tx_vfo();
+                        r.updated = true;
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.rit_button.connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].rit = button.is_active();
+                        r.receiver[rx].set_rit();
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.rit_adjustment.connect_value_changed(move |adjustment| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].rit_offset = adjustment.value() as f32;
+                        r.receiver[rx].set_rit();
+                    });
+
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.rit_clear_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        app_widgets.rit_adjustment.set_value(0.0);
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.xit_button.connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].xit = button.is_active();
+                        r.updated = true;
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.xit_adjustment.connect_value_changed(move |adjustment| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].xit_offset = adjustment.value() as f32;
+                        r.updated = true;
+                    });
+
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.xit_clear_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        app_widgets.xit_adjustment.set_value(0.0);
+                    });
+
+                    // split with VFO B a quick step above VFO A
+                    for (button, offset) in [(app_widgets.up1_button.clone(), 1000.0), (app_widgets.up5_button.clone(), 5000.0)] {
+                        let radio_mutex_clone = radio_mutex.clone();
+                        let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                        button.connect_clicked(move |_| {
+                            let mut r = radio_mutex_clone.radio.lock().unwrap();
+                            r.split_up(offset);
+                            let frequency = r.receiver[1].frequency;
+                            drop(r);
+                            let app_widgets = rc_app_widgets_clone_clone.borrow();
+                            let formatted_value = format_u32_with_separators(frequency as u32);
+                            app_widgets.vfo_b_frequency.set_label(&formatted_value);
+                            app_widgets.split_button.set_active(true);
+                        });
+                    }
+
+                    // the sub-RX starts on the dial frequency
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.subrx_button.connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].subrx = button.is_active();
+                        if r.receiver[rx].subrx {
+                            let mut frequency = r.receiver[rx].frequency;
+                            if r.receiver[rx].ctun {
+                                frequency = r.receiver[rx].ctun_frequency;
+                            }
+                            r.receiver[rx].set_subrx_frequency(frequency);
+                        }
+                        r.receiver[rx].set_subrx();
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.subrx_balance_adjustment.connect_value_changed(move |adjustment| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].subrx_balance = (adjustment.value() / 100.0) as f32;
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.subrx_mode_dropdown.connect_selected_notify(move |dropdown| {
+                        let mode = dropdown.selected() as usize;
+                        if Modes::from_usize(mode).is_none() {
+                            return;
+                        }
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        let filter = r.receiver[rx].subrx_filter;
+                        r.receiver[rx].subrx_mode = mode;
+                        let (low, high) = app_widgets.filter_grid.get_filter_values(mode, filter);
+                        r.receiver[rx].set_subrx_passband(low, high);
+                        r.receiver[rx].set_subrx_mode();
+                        drop(r);
+                        subrx_filter_list(&app_widgets, mode, filter);
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.subrx_filter_dropdown.connect_selected_notify(move |dropdown| {
+                        let filter = dropdown.selected() as usize;
+                        if Filters::from_usize(filter).is_none() {
+                            return;
+                        }
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].subrx_filter = filter;
+                        let (low, high) = app_widgets.filter_grid.get_filter_values(r.receiver[rx].subrx_mode, filter);
+                        r.receiver[rx].set_subrx_passband(low, high);
+                    });
+
+                    // receive on the repeater input and transmit on its output
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.reverse_button.connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        if r.transmitter.reverse == button.is_active() {
+                            return;
+                        }
+                        r.transmitter.reverse = button.is_active();
+                        let mut rx = 0;
+                        if r.split {
+                            rx = 1;
+                        }
+                        let mut shift = r.transmitter.repeater_shift();
+                        if !r.transmitter.reverse {
+                            shift = -shift;
+                        }
+                        let frequency;
+                        if r.receiver[rx].ctun {
+                            r.receiver[rx].ctun_frequency = r.receiver[rx].ctun_frequency + shift;
+                            r.receiver[rx].set_ctun_frequency();
+                            frequency = r.receiver[rx].ctun_frequency;
+                        } else {
+                            r.receiver[rx].frequency = r.receiver[rx].frequency + shift;
+                            frequency = r.receiver[rx].frequency;
+                        }
+                        let formatted_value = format_u32_with_separators(frequency as u32);
+                        if rx == 0 {
+                            app_widgets.vfo_a_frequency.set_label(&formatted_value);
+                        } else {
+                            app_widgets.vfo_b_frequency.set_label(&formatted_value);
+                        }
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.repeater_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let repeater_dialog = create_repeater_dialog(&app_widgets.main_window, &radio_mutex_clone);
+                        app_widgets.repeater_button.set_sensitive(false);
+                        repeater_dialog.present();
+                        let rc_app_widgets = rc_app_widgets_clone_clone.clone();
+                        repeater_dialog.connect_close_request(move |_| {
+                            let app_widgets = rc_app_widgets.borrow();
+                            app_widgets.repeater_button.set_sensitive(true);
+                            Propagation::Proceed
+                        });
                     });
 
                     let radio_mutex_clone = radio_mutex.clone();
@@ -838,25 +1411,26 @@ fn build_ui(app: &Application) {
                     let radio_mutex_clone = radio_mutex.clone();
                     let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
                     let press_state = middle_button_pressed.clone();
-                    spectrum_click_gesture_clone.connect_pressed(move |gesture, controller, x, _y| {
+                    let rc_spectrum_clone2 = rc_spectrum_clone.clone();
+                    spectrum_click_gesture_clone.connect_pressed(move |gesture, controller, x, y| {
                         let da = gesture.widget().unwrap();
                         let width = da.allocated_width();
                         if gesture.current_button() == 2 { // middle button
                             *press_state.borrow_mut() = true;
                         } else if gesture.current_button() == 1 { // left button
-                            if !spectrum_waterfall_clicked(&radio_mutex_clone, &rc_app_widgets_clone_clone, 0, x, width, gesture.current_button()) {
+                            let spot = rc_spectrum_clone2.borrow().spot_at(x, y);
+                            let notch = rc_spectrum_clone2.borrow().notch_at(x);
+                            let active = radio_mutex_clone.radio.lock().unwrap().receiver[0].active;
+                            if active && spot.is_some() {
+                                let spot = spot.unwrap();
+                                qsy(&radio_mutex_clone, &rc_app_widgets_clone_clone, 0, spot.frequency, spot.mode);
+                            } else if notch.is_some() {
+                                // dragged by the notch gesture
+                            } else if !spectrum_waterfall_clicked(&radio_mutex_clone, &rc_app_widgets_clone_clone, 0, x, width, gesture.current_button(), gesture.current_event_state()) {
                                 update_ui(&radio_mutex_clone.clone(), &rc_app_widgets_clone_clone.clone());
                             }
                         } else if gesture.current_button() == 3 { // right button
-                            // add a notch?
-                            let mut r = radio_mutex_clone.radio.lock().unwrap();
-                            let mut rx: usize = 0;
-                            if r.receiver[1].active {
-                                rx = 1;
-                            }
-                            let notch = Notch::new(rx as i32, r.receiver[rx].frequency as f64, 500.0, 1);
-                            r.add_notch_to_vector(notch);
-                            r.add_notch(notch);
+                            spectrum_notch_clicked(&radio_mutex_clone, &rc_spectrum_clone2.borrow(), 0, x, gesture.current_event_state());
                         }
                     });
                     let press_state = middle_button_pressed.clone();
@@ -874,13 +1448,24 @@ fn build_ui(app: &Application) {
                     let radio_mutex_clone = radio_mutex.clone();
                     let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
                     let press_state = middle_button_pressed.clone();
-                    spectrum_2_click_gesture_clone.connect_pressed(move |gesture, controller, x, _y| {
+                    let rc_spectrum_2_clone2 = rc_spectrum_2_clone.clone();
+                    spectrum_2_click_gesture_clone.connect_pressed(move |gesture, controller, x, y| {
                         let da = gesture.widget().unwrap();
                         let width = da.allocated_width();
                         if gesture.current_button() == 2 { // middle button
                             *press_state.borrow_mut() = true;
+                        } else if gesture.current_button() == 3 { // right button
+                            spectrum_notch_clicked(&radio_mutex_clone, &rc_spectrum_2_clone2.borrow(), 1, x, gesture.current_event_state());
                         } else {
-                            if !spectrum_waterfall_clicked(&radio_mutex_clone, &rc_app_widgets_clone_clone, 1, x, width, gesture.current_button()) {
+                            let spot = rc_spectrum_2_clone2.borrow().spot_at(x, y);
+                            let notch = rc_spectrum_2_clone2.borrow().notch_at(x);
+                            let active = radio_mutex_clone.radio.lock().unwrap().receiver[1].active;
+                            if gesture.current_button() == 1 && active && spot.is_some() {
+                                let spot = spot.unwrap();
+                                qsy(&radio_mutex_clone, &rc_app_widgets_clone_clone, 1, spot.frequency, spot.mode);
+                            } else if gesture.current_button() == 1 && notch.is_some() {
+                                // dragged by the notch gesture
+                            } else if !spectrum_waterfall_clicked(&radio_mutex_clone, &rc_app_widgets_clone_clone, 1, x, width, gesture.current_button(), gesture.current_event_state()) {
                                 update_ui(&radio_mutex_clone.clone(), &rc_app_widgets_clone_clone.clone());
                             }
                         }
@@ -893,6 +1478,10 @@ fn build_ui(app: &Application) {
                     });
                     app_widgets.spectrum_2_display.add_controller(<GestureClick as Clone>::clone(&spectrum_2_click_gesture).upcast::<EventController>());
 
+                    // drag a notch to move it or drag its edges to change the width
+                    spectrum_notch_drag(&app_widgets.spectrum_display, &radio_mutex, &rc_spectrum_clone, 0);
+                    spectrum_notch_drag(&app_widgets.spectrum_2_display, &radio_mutex, &rc_spectrum_2_clone, 1);
+
 
                     let last_spectrum_x = Rc::new(Cell::new(0.0));
                     let last_spectrum_y = Rc::new(Cell::new(0.0));
@@ -998,6 +1587,8 @@ fn build_ui(app: &Application) {
                                     r.receiver[0].band_info[b].spectrum_high = r.receiver[0].band_info[b].spectrum_high + increment;
                                 }
                             }
+                        } else if controller.current_event_state().contains(gdk::ModifierType::CONTROL_MASK) {
+                            subrx_scroll(&radio_mutex_clone, 0, dy);
                         } else {
                             spectrum_waterfall_scroll(&radio_mutex_clone, &rc_app_widgets_clone_clone, 0, dy);
                         }
@@ -1045,6 +1636,8 @@ fn build_ui(app: &Application) {
                                     r.receiver[1].band_info[b].spectrum_high = r.receiver[1].band_info[b].spectrum_high + increment;
                                 }
                             }
+                        } else if controller.current_event_state().contains(gdk::ModifierType::CONTROL_MASK) {
+                            subrx_scroll(&radio_mutex_clone, 1, dy);
                         } else {
                             spectrum_waterfall_scroll(&radio_mutex_clone, &rc_app_widgets_clone_clone, 1, dy);
                         }
@@ -1059,7 +1652,11 @@ fn build_ui(app: &Application) {
                     let radio_mutex_clone = radio_mutex.clone();
                     let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
                     scroll_controller_waterfall.connect_scroll(move |controller, _dx, dy| {
-                        spectrum_waterfall_scroll(&radio_mutex_clone, &rc_app_widgets_clone_clone, 0, dy);
+                        if controller.current_event_state().contains(gdk::ModifierType::CONTROL_MASK) {
+                            subrx_scroll(&radio_mutex_clone, 0, dy);
+                        } else {
+                            spectrum_waterfall_scroll(&radio_mutex_clone, &rc_app_widgets_clone_clone, 0, dy);
+                        }
                         Propagation::Proceed
                     });
                     app_widgets.waterfall_display.add_controller(scroll_controller_waterfall.clone());
@@ -1070,7 +1667,11 @@ fn build_ui(app: &Application) {
                     let radio_mutex_clone = radio_mutex.clone();
                     let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
                     scroll_controller_waterfall_2.connect_scroll(move |controller, _dx, dy| {
-                        spectrum_waterfall_scroll(&radio_mutex_clone, &rc_app_widgets_clone_clone, 1, dy);
+                        if controller.current_event_state().contains(gdk::ModifierType::CONTROL_MASK) {
+                            subrx_scroll(&radio_mutex_clone, 1, dy);
+                        } else {
+                            spectrum_waterfall_scroll(&radio_mutex_clone, &rc_app_widgets_clone_clone, 1, dy);
+                        }
                         Propagation::Proceed
                     });
                     app_widgets.waterfall_2_display.add_controller(scroll_controller_waterfall_2.clone());
@@ -1083,7 +1684,7 @@ fn build_ui(app: &Application) {
                     waterfall_click_gesture_clone.connect_pressed(move |gesture, _, x, _y| {
                         let da = gesture.widget().unwrap();
                         let width = da.allocated_width();
-                        if !spectrum_waterfall_clicked(&radio_mutex_clone, &rc_app_widgets_clone_clone, 0, x, width, gesture.current_button()) {
+                        if !spectrum_waterfall_clicked(&radio_mutex_clone, &rc_app_widgets_clone_clone, 0, x, width, gesture.current_button(), gesture.current_event_state()) {
                             update_ui(&radio_mutex_clone, &rc_app_widgets_clone_clone);
                         }
                     });
@@ -1097,7 +1698,7 @@ fn build_ui(app: &Application) {
                     waterfall_2_click_gesture_clone.connect_pressed(move |gesture, _, x, _y| {
                         let da = gesture.widget().unwrap();
                         let width = da.allocated_width();
-                        if !spectrum_waterfall_clicked(&radio_mutex_clone, &rc_app_widgets_clone_clone, 1, x, width, gesture.current_button()) {
+                        if !spectrum_waterfall_clicked(&radio_mutex_clone, &rc_app_widgets_clone_clone, 1, x, width, gesture.current_button(), gesture.current_event_state()) {
                             update_ui(&radio_mutex_clone, &rc_app_widgets_clone_clone);
                         }
                     });
@@ -1178,58 +1779,85 @@ fn build_ui(app: &Application) {
                         }
 
                         let b = r.receiver[rx].band.to_usize();
-                        if b != index { // band has changed
-                            r.receiver[rx].band_info[b].current = r.receiver[rx].frequency;
-
-                            // get new band info
-                            r.receiver[rx].band = Bands::from_usize(index).expect("invalid band index");
-                            r.receiver[rx].frequency = r.receiver[rx].band_info[index].current;
-                            if r.receiver[rx].ctun {
-                                r.receiver[rx].ctun_frequency = r.receiver[rx].frequency;
-                                r.receiver[rx].set_ctun_frequency();
+                        let entry = r.receiver[rx].band_stack();
+                        r.receiver[rx].band_info[b].save_stack(entry);
+
+                        // pressing the same band again moves on to the next stacking register
+                        let mut stack_index = r.receiver[rx].band_info[index].stack_index;
+                        if b == index {
+                            stack_index = (stack_index + 1) % r.receiver[rx].band_info[index].stack.len().max(1);
+                        }
+                        r.receiver[rx].band_info[index].select_stack(stack_index);
+                        let entry = r.receiver[rx].band_info[index].stack.get(stack_index).copied();
+
+                        // get new band info
+                        r.receiver[rx].band = Bands::from_usize(index).expect("invalid band index");
+                        r.receiver[rx].frequency = r.receiver[rx].band_info[index].current;
+                        match entry {
+                            Some(entry) if entry.ctun => {
+                                r.receiver[rx].ctun = true;
+                                r.receiver[rx].ctun_frequency = entry.ctun_frequency;
                             }
-
-                            if !r.receiver[rx].filters_manual {
-                                r.receiver[rx].filters = r.receiver[rx].band_info[index].filters;
+                            Some(_) => {
+                                r.receiver[rx].ctun = false;
+                                r.receiver[rx].ctun_frequency = 0.0;
+                            }
+                            None => {
+                                if r.receiver[rx].ctun {
+                                    r.receiver[rx].ctun_frequency = r.receiver[rx].frequency;
+                                }
                             }
                         }
-                        app_widgets.filter_grid.update_filter_buttons(r.receiver[rx].band_info[index].mode.to_usize());
-                        app_widgets.filter_grid.set_active_index(r.receiver[rx].band_info[index].filter.to_usize());
+                        r.receiver[rx].set_ctun(r.receiver[rx].ctun);
+                        r.receiver[rx].select_band_squelch();
+                        r.receiver[rx].set_noise();
 
-                        if b != index { // band has changed
-                            r.receiver[rx].mode = r.receiver[rx].band_info[index].mode.to_usize();
-                            app_widgets.mode_grid.set_active_index(r.receiver[rx].mode);
-                            let (low, high) = app_widgets.filter_grid.get_filter_values(r.receiver[rx].band_info[index].mode.to_usize(), r.receiver[rx].band_info[index].filter.to_usize());
-                            app_widgets.filter_grid.set_active_values(low, high);
-                            r.receiver[rx].filter_low = low;
-                            r.receiver[rx].filter_high = high;
-                            if r.receiver[rx].mode == Modes::CWL.to_usize() {
-                                r.receiver[rx].filter_low = -r.receiver[rx].cw_pitch - low;
-                                r.receiver[rx].filter_high = -r.receiver[rx].cw_pitch + high;
-                            } else if r.receiver[rx].mode == Modes::CWU.to_usize() {
-                                r.receiver[rx].filter_low = r.receiver[rx].cw_pitch - low;
-                                r.receiver[rx].filter_high = r.receiver[rx].cw_pitch + high;
-                            }
-                            r.receiver[rx].set_mode();
+                        if !r.receiver[rx].filters_manual {
+                            r.receiver[rx].filters = r.receiver[rx].band_info[index].filters;
+                        }
 
-                            r.transmitter.filter_low = low;
-                            r.transmitter.filter_high = high;
-                            r.transmitter.mode = r.receiver[rx].band_info[index].mode.to_usize();
-                            r.transmitter.set_mode();
-                            r.transmitter.set_filter();
+                        app_widgets.filter_grid.update_filter_buttons(r.receiver[rx].band_info[index].mode.to_usize());
+                        app_widgets.filter_grid.set_active_index(r.receiver[rx].band_info[index].filter.to_usize());
 
-                            let formatted_value = format_u32_with_separators(r.receiver[rx].frequency as u32);
-                            if rx == 0 {
-                                app_widgets.vfo_a_frequency.set_label(&formatted_value);
-                            } else {
-                                app_widgets.vfo_b_frequency.set_label(&formatted_value);
-                            }
-                        }
-                        unsafe {
-                            RXANBPSetTuneFrequency(rx as i32, r.receiver[rx].frequency as f64);
+                        r.receiver[rx].mode = r.receiver[rx].band_info[index].mode.to_usize();
+                        r.receiver[rx].filter = r.receiver[rx].band_info[index].filter.to_usize();
+                        app_widgets.mode_grid.set_active_index(r.receiver[rx].mode);
+                        let (low, high) = app_widgets.filter_grid.get_filter_values(r.receiver[rx].band_info[index].mode.to_usize(), r.receiver[rx].band_info[index].filter.to_usize());
+                        app_widgets.filter_grid.set_active_values(low, high);
+                        r.receiver[rx].filter_low = low;
+                        r.receiver[rx].filter_high = high;
+                        if r.receiver[rx].mode == Modes::CWL.to_usize() {
+                            r.receiver[rx].filter_low = -r.receiver[rx].cw_pitch - low;
+                            r.receiver[rx].filter_high = -r.receiver[rx].cw_pitch + high;
+                        } else if r.receiver[rx].mode == Modes::CWU.to_usize() {
+                            r.receiver[rx].filter_low = r.receiver[rx].cw_pitch - low;
+                            r.receiver[rx].filter_high = r.receiver[rx].cw_pitch + high;
                         }
+                        r.receiver[rx].set_mode();
+                        r.update_tx_vfo();
 
+                        let mut frequency = r.receiver[rx].frequency;
+                        if r.receiver[rx].ctun {
+                            frequency = r.receiver[rx].ctun_frequency;
+                        }
+                        let formatted_value = format_u32_with_separators(frequency as u32);
+                        if rx == 0 {
+                            app_widgets.vfo_a_frequency.set_label(&formatted_value);
+                        } else {
+                            app_widgets.vfo_b_frequency.set_label(&formatted_value);
+                        }
+                        let ctun = r.receiver[rx].ctun;
+                        let squelch = r.receiver[rx].squelch;
+                        let squelch_level = r.receiver[rx].squelch_level;
+                        drop(r);
 
+                        app_widgets.ctun_button.set_active(ctun);
+                        app_widgets.squelch_button.set_active(squelch);
+                        app_widgets.squelch_adjustment.set_value(squelch_level.into());
+                        if let Some(entry) = entry {
+                            app_widgets.zoom_adjustment.set_value(entry.zoom.into());
+                            app_widgets.pan_adjustment.set_value(entry.pan.into());
+                        }
                     }, band);
 
 
@@ -1258,12 +1886,7 @@ fn build_ui(app: &Application) {
                             r.receiver[rx].filter_high = r.receiver[rx].cw_pitch + high;
                         }
                         r.receiver[rx].set_mode();
-
-                        r.transmitter.mode = index;
-                        r.transmitter.set_mode();
-                        r.transmitter.filter_low = low;
-                        r.transmitter.filter_high = high;
-                        r.transmitter.set_filter();
+                        r.update_tx_vfo();
                     }, mode);
 
                     let radio_mutex_clone = radio_mutex.clone();
@@ -1289,10 +1912,7 @@ fn build_ui(app: &Application) {
                             r.receiver[rx].filter_high = r.receiver[rx].cw_pitch + high;
                         }
                         r.receiver[rx].set_filter();
-
-                        r.transmitter.filter_low = low;
-                        r.transmitter.filter_high = high;
-                        r.transmitter.set_filter();
+                        r.update_tx_vfo();
                     }, filter);
 
                     let radio_mutex_clone = radio_mutex.clone();
@@ -1333,28 +1953,34 @@ fn build_ui(app: &Application) {
                         if r.receiver[1].active {
                             rx = 1;
                         }
-                        let active = button.is_active();
+                        r.receiver[rx].nb = button.is_active();
+                        r.receiver[rx].set_nb();
+                    });
 
-                        if button.is_active() {
-                            // NR is now enabled
-                            r.receiver[rx].nb = true;
-                            r.receiver[rx].set_nb(); // turn on
-                        } else {
-                            if r.receiver[rx].nb {
-                                // NR was active
-                                r.receiver[rx].nb = false;
-                                r.receiver[rx].set_nb(); // turn off
-                                // enable NR2
-                                r.receiver[rx].nb2 = true;
-                                r.receiver[rx].set_nb2(); // turn on
-                                button.set_label("NB2");
-                                button.set_active(true);
-                            } else {
-                                r.receiver[rx].nb2 = false;
-                                r.receiver[rx].set_nb2(); // turn off
-                                button.set_label("NB");
-                            }
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.nb2_button.clone().connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
                         }
+                        r.receiver[rx].nb2 = button.is_active();
+                        r.receiver[rx].set_nb2();
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.noise_button.connect_clicked(move |_| {
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        let noise_dialog = create_noise_dialog(&app_widgets.main_window, &radio_mutex_clone);
+                        app_widgets.noise_button.set_sensitive(false);
+                        noise_dialog.present();
+                        let rc_app_widgets = rc_app_widgets_clone_clone.clone();
+                        noise_dialog.connect_close_request(move |_| {
+                            let app_widgets = rc_app_widgets.borrow();
+                            app_widgets.noise_button.set_sensitive(true);
+                            Propagation::Proceed
+                        });
                     });
 
                     let radio_mutex_clone = radio_mutex.clone();
@@ -1379,17 +2005,128 @@ fn build_ui(app: &Application) {
                         r.receiver[rx].set_snb();
                     });
 
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.auto_notch_button.clone().connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].auto_notch = button.is_active();
+                        r.receiver[rx].set_auto_notch();
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.squelch_button.clone().connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        let b = r.receiver[rx].band.to_usize();
+                        r.receiver[rx].squelch = button.is_active();
+                        r.receiver[rx].band_info[b].squelch = button.is_active();
+                        r.receiver[rx].set_squelch();
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.squelch_adjustment.connect_value_changed(move |adjustment| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        let b = r.receiver[rx].band.to_usize();
+                        r.receiver[rx].squelch_level = adjustment.value() as f32;
+                        r.receiver[rx].band_info[b].squelch_level = adjustment.value() as f32;
+                        r.receiver[rx].set_squelch();
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.vox_button.clone().connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        r.vox_enabled = button.is_active();
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.vox_adjustment.connect_value_changed(move |adjustment| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        r.vox_threshold = adjustment.value() as f32;
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.swr_alarm_button.connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        r.clear_swr_alarm();
+                        button.set_visible(false);
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.tx_inhibit_button.connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        r.tx_inhibit = button.is_active();
+                        r.updated = true;
+                        r.set_state();
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.tx_profile_dropdown.connect_selected_notify(move |dropdown| {
+                        let mut name = String::new();
+                        if dropdown.selected() > 0 {
+                            if let Some(item) = dropdown.selected_item().and_downcast::<gtk::StringObject>() {
+                                name = item.string().to_string();
+                            }
+                        }
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        if r.tx_profiles.current != name {
+                            r.select_tx_profile(&name);
+                        }
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
+                    app_widgets.cw_decoder_button.clone().connect_clicked(move |button| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].cw_decoder = button.is_active();
+                        r.receiver[rx].cw_decoder_audio_buffer_offset = 0;
+                        r.receiver[rx].morse_decoder.reset();
+                        drop(r);
+                        let app_widgets = rc_app_widgets_clone_clone.borrow();
+                        if rx == 0 {
+                            app_widgets.cw_decoder_window.set_visible(button.is_active());
+                        } else {
+                            app_widgets.cw_decoder_2_window.set_visible(button.is_active());
+                        }
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    app_widgets.cw_decoder_threshold_adjustment.connect_value_changed(move |adjustment| {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let mut rx = 0;
+                        if r.receiver[1].active {
+                            rx = 1;
+                        }
+                        r.receiver[rx].cw_decoder_threshold = adjustment.value() as f32;
+                    });
+
                     let radio_mutex_clone = radio_mutex.clone();
                     let rc_app_widgets_clone_clone = rc_app_widgets_clone.clone();
                     app_widgets.mox_button.clone().connect_clicked(move |button| {
                         let mut r = radio_mutex_clone.radio.lock().unwrap();
                         let app_widgets = rc_app_widgets_clone_clone.borrow();
                         r.mox = button.is_active();
+                        if r.mox && r.tx_lockout().is_some() {
+                            r.mox = false;
+                            button.set_active(false);
+                        }
                         if r.mox {
                             if app_widgets.tun_button.is_active() {
                                app_widgets.tun_button.set_active(false);
-                               r.tune = false;
-                               r.transmitter.set_tuning(r.tune, r.cw_keyer_sidetone_frequency);
+                               r.set_tuning(false);
                             }
                         }
                         r.updated = true;
@@ -1418,14 +2155,21 @@ fn build_ui(app: &Application) {
                     app_widgets.tun_button.clone().connect_clicked(move |button| {
                         let mut r = radio_mutex_clone.radio.lock().unwrap();
                         let app_widgets = rc_app_widgets_clone_clone.borrow();
-                        r.tune = button.is_active();
-                        if r.tune {
+                        let mut tune = button.is_active();
+                        if tune && r.tx_lockout().is_some() {
+                            tune = false;
+                            button.set_active(false);
+                        }
+                        if tune {
                             if app_widgets.mox_button.is_active() {
                                app_widgets.mox_button.set_active(false);
                                r.mox = false;
                             }
+                            if r.tx_test.running {
+                                r.stop_tx_test();
+                            }
                         }
-                        r.transmitter.set_tuning(r.tune, r.cw_keyer_sidetone_frequency);
+                        r.set_tuning(tune);
                         r.updated = true;
                         r.set_state();
                         if r.tune {
@@ -1468,6 +2212,7 @@ fn build_ui(app: &Application) {
                         let index = dropdown.selected();
                         r.receiver[rx].agc = AGC::from_i32(index as i32).expect("Invalid AGC");
                         AGC::set_agc(&r.receiver[rx], r.receiver[rx].channel);
+                        AGC::set_agc(&r.receiver[rx], r.receiver[rx].subrx_channel());
                     });
 
                     let radio_mutex_clone = radio_mutex.clone();
@@ -1558,6 +2303,14 @@ fn build_ui(app: &Application) {
                         app_widgets.filter_grid.update_filter_buttons(r.receiver[rx].mode);
                         r.audio[0].init();
                         r.audio[1].init();
+                        r.midi.init();
+                        r.dxcluster.init();
+                        r.band_plan.init();
+                        r.memories.init();
+                        r.scanner.init();
+                        r.keyer.init();
+                        r.tx_profiles.init();
+                        r.tx_test.init();
                         r.receiver[rx].set_mode();
                         r.transmitter.init();
 
@@ -1590,31 +2343,14 @@ fn build_ui(app: &Application) {
                             app_widgets.nr_button.set_label("NR");
                         }
 
-                        app_widgets.nb_button.set_active(r.receiver[rx].nb | r.receiver[rx].nb2);
-                        if r.receiver[rx].nb2 {
-                            app_widgets.nb_button.set_label("NB2");
-                        } else {
-                            app_widgets.nb_button.set_label("NB");
-                        }
+                        app_widgets.nb_button.set_active(r.receiver[rx].nb);
+                        app_widgets.nb2_button.set_active(r.receiver[rx].nb2);
 
                         app_widgets.anf_button.set_active(r.receiver[rx].anf);
 
                         app_widgets.snb_button.set_active(r.receiver[rx].snb);
 
-                        //initialize the notch vector
-                        r.notch = 0;
-                        for i in 0..r.notches.len() {
-                            let notch = r.notches[i as usize];
-                            r.add_notch(notch);
-                        }
-
-                        // enable the notches
-                        unsafe {
-                            RXANBPSetTuneFrequency(0, r.receiver[0].frequency as f64);
-                            RXANBPSetTuneFrequency(1, r.receiver[1].frequency as f64);
-                            RXANBPSetNotchesRun(0, 1);
-                            RXANBPSetNotchesRun(1, 1);
-                        }
+                        app_widgets.auto_notch_button.set_active(r.receiver[rx].auto_notch);
 
                     }   
 
@@ -1753,6 +2489,71 @@ fn build_ui(app: &Application) {
                         Continue
                     });
 
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone2 = rc_app_widgets_clone.clone();
+                    timeout_add_local(Duration::from_millis(20), move || {
+                        midi_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        Continue
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone2 = rc_app_widgets_clone.clone();
+                    timeout_add_local(Duration::from_millis(250), move || {
+                        dxcluster_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        Continue
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone2 = rc_app_widgets_clone.clone();
+                    // start from the segments the VFOs are in so following does not change the mode until they move
+                    let r = radio_mutex.radio.lock().unwrap();
+                    let segments = vec![band_plan_segment(&r, 0), band_plan_segment(&r, 1)];
+                    drop(r);
+                    let last_segment: Rc<RefCell<Vec<Option<usize>>>> = Rc::new(RefCell::new(segments));
+                    timeout_add_local(Duration::from_millis(250), move || {
+                        band_plan_update(&radio_mutex_clone, &rc_app_widgets_clone2, &last_segment);
+                        Continue
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone2 = rc_app_widgets_clone.clone();
+                    timeout_add_local(Duration::from_millis(100), move || {
+                        let mut r = radio_mutex_clone.radio.lock().unwrap();
+                        let recall = r.memories.recall.take();
+                        drop(r);
+                        if let Some((rx, memory)) = recall {
+                            memory_recall(&radio_mutex_clone, &rc_app_widgets_clone2, rx, &memory);
+                        }
+                        Continue
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone2 = rc_app_widgets_clone.clone();
+                    timeout_add_local(Duration::from_millis(50), move || {
+                        scanner_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        Continue
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone2 = rc_app_widgets_clone.clone();
+                    timeout_add_local(Duration::from_millis(100), move || {
+                        squelch_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        vox_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        tx_profile_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        tune_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        swr_alarm_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        tx_timer_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        drive_range_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        Continue
+                    });
+
+                    let radio_mutex_clone = radio_mutex.clone();
+                    let rc_app_widgets_clone2 = rc_app_widgets_clone.clone();
+                    timeout_add_local(Duration::from_millis(100), move || {
+                        cw_decoder_update(&radio_mutex_clone, &rc_app_widgets_clone2);
+                        Continue
+                    });
+
 
                     let mut r = radio_mutex.radio.lock().unwrap();
                     r.spectrum_timeout_id = Some(spectrum_timeout_id);
@@ -1799,6 +2600,10 @@ fn spectrum_update(radio_mutex: &RadioMutex,  rc_app_widgets: &Rc<RefCell<AppWid
     let app_widgets = rc_app_widgets.borrow();
     let (flag, pixels) = radio_mutex.update_spectrum(app_widgets.spectrum_display.width());
     if flag != 0 {
+        if !is_transmitting {
+            scanner_peaks(radio_mutex, 0, &pixels);
+            carrier_notches(radio_mutex, 0, &pixels);
+        }
         let mut spectrum = rc_spectrum.borrow_mut();
         spectrum.update(app_widgets.spectrum_display.width(), app_widgets.spectrum_display.height(), &radio_mutex, &pixels);
         app_widgets.spectrum_display.queue_draw();
@@ -1814,6 +2619,8 @@ fn spectrum_2_update(radio_mutex: &RadioMutex,  rc_app_widgets: &Rc<RefCell<AppW
         let app_widgets = rc_app_widgets.borrow();
         let (flag, pixels) = radio_mutex.update_spectrum_2(app_widgets.spectrum_2_display.width());
         if flag != 0 {
+            scanner_peaks(radio_mutex, 1, &pixels);
+            carrier_notches(radio_mutex, 1, &pixels);
             let mut spectrum = rc_spectrum.borrow_mut();
             spectrum.update(app_widgets.spectrum_2_display.width(), app_widgets.spectrum_2_display.height(), &radio_mutex, &pixels);
             app_widgets.spectrum_2_display.queue_draw();
@@ -1885,35 +2692,26 @@ fn meter_tx_update(radio_mutex: &RadioMutex,  rc_app_widgets: &Rc<RefCell<AppWid
     let meter = rc_meter.borrow_mut();
     let r = radio_mutex.radio.lock().unwrap();
     let is_transmitting = r.is_transmitting();
-    let forward = r.transmitter.alex_forward_power;
-    let reverse = r.transmitter.alex_reverse_power;
-    let c1 = r.transmitter.c1;
-    let c2 = r.transmitter.c2;
-    drop(r);
-
-    // calculate the SWR
-    let fwd_power = forward as f32;
-    let rev_power = reverse as f32;
-
-    let v_fwd = (fwd_power / 4095.0) * c1;
-    let fwd = (v_fwd * v_fwd) / c2;
-
-    let v_rev = (rev_power / 4095.0) * c1;
-    let rev = (v_rev * v_rev) / c2;
-
-    let mut swr = (1.0 + (rev / fwd).sqrt())  / (1.0 - (rev / fwd).sqrt());
-    if swr < 0.0 {
-        swr = 1.0;
-    }
-    if swr.is_nan() {
-        swr = 1.0;
+    let fwd = r.forward_watts;
+    let rev = r.reflected_watts;
+    let swr = r.swr;
+    let mut alc = 0.0;
+    let mut compressor = 0.0;
+    if is_transmitting {
+        alc = r.transmitter.alc_meter();
+        compressor = r.transmitter.compressor_meter();
     }
+    drop(r);
 
     if is_transmitting {
         let formatted_power = format!("Power: {:.1} W", fwd);
         app_widgets.tx_power.set_label(&formatted_power);
         let formatted_swr = format!("SWR: {:.1}:1", swr);
         app_widgets.tx_swr.set_label(&formatted_swr);
+        let formatted_reflected = format!("Reflected: {:.1} W", rev);
+        app_widgets.tx_reflected.set_label(&formatted_reflected);
+        let formatted_alc = format!("ALC: {:.1} dB  Comp: {:.1} dB", alc, compressor);
+        app_widgets.tx_alc.set_label(&formatted_alc);
     }
 
     //if is_transmitting {
@@ -1922,7 +2720,559 @@ fn meter_tx_update(radio_mutex: &RadioMutex,  rc_app_widgets: &Rc<RefCell<AppWid
    // }
 }
 
-fn spectrum_waterfall_clicked(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>, rx: usize, x: f64, width: i32, button: u32) -> bool {
+fn keyboard_paddle(radio_mutex: &RadioMutex, keyval: gdk::Key, pressed: bool) -> Propagation {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    if !r.keyer.keyboard || r.cw_keyer_internal {
+        return Propagation::Proceed;
+    }
+    match keyval {
+        gdk::Key::Control_L => r.keyer.keyboard_dot = pressed,
+        gdk::Key::Control_R => r.keyer.keyboard_dash = pressed,
+        _ => return Propagation::Proceed,
+    }
+    Propagation::Stop
+}
+
+fn midi_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    let actions = r.midi.poll();
+    let mut rx = 0;
+    if r.receiver[1].active {
+        rx = 1;
+    }
+    let band = r.receiver[rx].band.to_usize();
+    let mode = r.receiver[rx].mode;
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    for (action, message) in actions {
+        match action {
+            MidiAction::VfoKnob => {
+                spectrum_waterfall_scroll(radio_mutex, rc_app_widgets, rx, -message.relative() as f64);
+            },
+            MidiAction::AfGain => {
+                midi_set_adjustment(&app_widgets.afgain_adjustment, message.position());
+            },
+            MidiAction::AgcGain => {
+                midi_set_adjustment(&app_widgets.agcgain_adjustment, message.position());
+            },
+            MidiAction::Drive => {
+                midi_set_adjustment(&app_widgets.drive_adjustment, message.position());
+            },
+            MidiAction::MicGain => {
+                midi_set_adjustment(&app_widgets.micgain_adjustment, message.position());
+            },
+            MidiAction::Mox => {
+                if message.pressed() {
+                    app_widgets.mox_button.emit_clicked();
+                }
+            },
+            MidiAction::Tune => {
+                if message.pressed() {
+                    app_widgets.tun_button.emit_clicked();
+                }
+            },
+            MidiAction::Nr => {
+                if message.pressed() {
+                    app_widgets.nr_button.emit_clicked();
+                }
+            },
+            MidiAction::Nb => {
+                if message.pressed() {
+                    app_widgets.nb_button.emit_clicked();
+                }
+            },
+            MidiAction::BandUp | MidiAction::BandDown => {
+                if message.pressed() {
+                    let bands = Bands::BandWWV.to_usize() + 1;
+                    let mut b = band;
+                    if action == MidiAction::BandUp {
+                        b = (b + 1) % bands;
+                    } else {
+                        b = (b + bands - 1) % bands;
+                    }
+                    let band_button = app_widgets.band_grid.get_button(b);
+                    band_button.emit_by_name::<()>("clicked", &[]);
+                }
+            },
+            MidiAction::ModeUp | MidiAction::ModeDown => {
+                if message.pressed() {
+                    let modes = Modes::DRM.to_usize() + 1;
+                    let mut m = mode;
+                    if action == MidiAction::ModeUp {
+                        m = (m + 1) % modes;
+                    } else {
+                        m = (m + modes - 1) % modes;
+                    }
+                    let mode_button = app_widgets.mode_grid.get_button(m);
+                    mode_button.emit_by_name::<()>("clicked", &[]);
+                }
+            },
+            MidiAction::StepUp | MidiAction::StepDown => {
+                if message.pressed() {
+                    let steps = app_widgets.step_dropdown.model().map(|m| m.n_items()).unwrap_or(1);
+                    let mut step = app_widgets.step_dropdown.selected();
+                    if action == MidiAction::StepUp {
+                        if step + 1 < steps {
+                            step = step + 1;
+                        }
+                    } else if step > 0 {
+                        step = step - 1;
+                    }
+                    app_widgets.step_dropdown.set_selected(step);
+                }
+            },
+            MidiAction::None => {},
+        }
+    }
+
+    // LED feedback
+    let mut r = radio_mutex.radio.lock().unwrap();
+    r.midi.set_led(MidiAction::Mox, app_widgets.mox_button.is_active());
+    r.midi.set_led(MidiAction::Tune, app_widgets.tun_button.is_active());
+    r.midi.set_led(MidiAction::Nr, app_widgets.nr_button.is_active());
+    r.midi.set_led(MidiAction::Nb, app_widgets.nb_button.is_active());
+}
+
+fn midi_set_adjustment(adjustment: &Adjustment, position: f64) {
+    let value = adjustment.lower() + ((adjustment.upper() - adjustment.lower()) * position);
+    adjustment.set_value(value);
+}
+
+// restore a memory into a receiver, making it the active one
+fn memory_recall(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>, rx: usize, memory: &Memory) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    if rx == 1 && !r.rx2_enabled {
+        return;
+    }
+    r.receiver[0].active = rx == 0;
+    r.receiver[1].active = rx == 1;
+
+    let band = band_for_frequency(&r.receiver[rx].band_info, memory.frequency);
+    let b = r.receiver[rx].band.to_usize();
+    if band.to_usize() != b {
+        r.receiver[rx].band_info[b].current = r.receiver[rx].frequency;
+        r.receiver[rx].band = band;
+        if !r.receiver[rx].filters_manual {
+            r.receiver[rx].filters = r.receiver[rx].band_info[band.to_usize()].filters;
+        }
+        r.receiver[rx].select_band_squelch();
+        r.receiver[rx].set_noise();
+    }
+
+    r.receiver[rx].mode = memory.mode.to_usize();
+    r.receiver[rx].filter = memory.filter;
+
+    r.receiver[rx].ctun = memory.ctun;
+    if memory.ctun {
+        r.receiver[rx].frequency = memory.frequency - memory.ctun_offset;
+        r.receiver[rx].ctun_frequency = memory.frequency;
+    } else {
+        r.receiver[rx].frequency = memory.frequency;
+        r.receiver[rx].ctun_frequency = 0.0;
+    }
+    r.receiver[rx].set_ctun(memory.ctun);
+
+    r.receiver[rx].agc = memory.agc;
+    AGC::set_agc(&r.receiver[rx], r.receiver[rx].channel);
+    AGC::set_agc(&r.receiver[rx], r.receiver[rx].subrx_channel());
+
+    r.receiver[rx].nr = memory.nr;
+    r.receiver[rx].set_nr();
+    r.receiver[rx].nr2 = memory.nr2;
+    r.receiver[rx].set_nr2();
+    r.receiver[rx].nb = memory.nb;
+    r.receiver[rx].set_nb();
+    r.receiver[rx].nb2 = memory.nb2;
+    r.receiver[rx].set_nb2();
+
+    let adc = r.receiver[rx].adc;
+    r.adc[adc].rx_antenna = memory.antenna;
+    r.updated = true;
+
+    // repeater tones and offset
+    r.receiver[rx].ctcss = memory.tone_squelch && memory.ctcss.is_some();
+    r.transmitter.ctcss = memory.ctcss.is_some();
+    if let Some(ctcss) = memory.ctcss {
+        r.receiver[rx].ctcss_frequency = ctcss;
+        r.transmitter.ctcss_frequency = ctcss;
+    }
+    r.receiver[rx].ctcss_decoder.reset();
+    r.transmitter.set_ctcss();
+    if memory.offset > 0.0 {
+        r.transmitter.duplex = Duplex::Plus;
+        r.transmitter.repeater_offset = memory.offset;
+    } else if memory.offset < 0.0 {
+        r.transmitter.duplex = Duplex::Minus;
+        r.transmitter.repeater_offset = -memory.offset;
+    } else {
+        r.transmitter.duplex = Duplex::Simplex;
+    }
+    r.transmitter.reverse = false;
+
+    drop(r);
+
+    // bring the band, mode, filter and DSP buttons in line with the receiver
+    update_ui(radio_mutex, rc_app_widgets);
+
+    let app_widgets = rc_app_widgets.borrow();
+    app_widgets.reverse_button.set_active(false);
+    let formatted_value = format_u32_with_separators(memory.frequency as u32);
+    if rx == 0 {
+        app_widgets.vfo_a_frequency.set_label(&formatted_value);
+    } else {
+        app_widgets.vfo_b_frequency.set_label(&formatted_value);
+    }
+}
+
+// the band plan segment the receiver is tuned to
+fn band_plan_segment(r: &Radio, rx: usize) -> Option<usize> {
+    let mut frequency = r.receiver[rx].frequency;
+    if r.receiver[rx].ctun {
+        frequency = r.receiver[rx].ctun_frequency;
+    }
+    r.band_plan.find(frequency).map(|(index, _)| index)
+}
+
+fn band_plan_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>, last_segment: &Rc<RefCell<Vec<Option<usize>>>>) {
+    let r = radio_mutex.radio.lock().unwrap();
+    let mut rx = 0;
+    if r.receiver[1].active {
+        rx = 1;
+    }
+    let mut names = Vec::new();
+    let mut follow_mode = None;
+    let mut last = last_segment.borrow_mut();
+    for i in 0..2 {
+        let mut frequency = r.receiver[i].frequency;
+        if r.receiver[i].ctun {
+            frequency = r.receiver[i].ctun_frequency;
+        }
+        let mut name = String::from("");
+        let mut index = None;
+        if let Some((segment_index, segment)) = r.band_plan.find(frequency) {
+            index = Some(segment_index);
+            name = segment.name.clone();
+            if !r.band_plan.is_licensed(segment) {
+                name = format!("{} (not licensed)", name);
+            } else if segment.segment_type == SegmentType::Beacon && !segment.blocks_tx() {
+                name = format!("{} (avoid transmitting)", name);
+            }
+            // only change the mode when moving into a new segment so it can still be overridden
+            if i == rx && r.band_plan.follow && last[i] != index && !r.is_transmitting() {
+                if let Some(mode) = segment.mode() {
+                    if mode.to_usize() != r.receiver[i].mode {
+                        follow_mode = Some(mode);
+                    }
+                }
+            }
+        }
+        last[i] = index;
+        names.push(name);
+    }
+    drop(last);
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    app_widgets.vfo_a_segment.set_label(&names[0]);
+    app_widgets.vfo_b_segment.set_label(&names[1]);
+    if let Some(mode) = follow_mode {
+        let mode_button = app_widgets.mode_grid.get_button(mode.to_usize());
+        mode_button.emit_by_name::<()>("clicked", &[]);
+    }
+}
+
+fn dxcluster_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    r.dxcluster.poll();
+    let request = r.dxcluster.qsy.take();
+    let mut rx = 0;
+    if r.receiver[1].active {
+        rx = 1;
+    }
+    drop(r);
+
+    if let Some((frequency, mode)) = request {
+        qsy(radio_mutex, rc_app_widgets, rx, frequency, mode);
+    }
+}
+
+fn squelch_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let r = radio_mutex.radio.lock().unwrap();
+    let mut rx = 0;
+    if r.receiver[1].active {
+        rx = 1;
+    }
+    let open = r.receiver[rx].squelch_open;
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    if open {
+        app_widgets.squelch_indicator.set_label("Open");
+        app_widgets.squelch_indicator.remove_css_class("squelch-closed");
+        app_widgets.squelch_indicator.add_css_class("squelch-open");
+    } else {
+        app_widgets.squelch_indicator.set_label("Closed");
+        app_widgets.squelch_indicator.remove_css_class("squelch-open");
+        app_widgets.squelch_indicator.add_css_class("squelch-closed");
+    }
+}
+
+fn vox_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let r = radio_mutex.radio.lock().unwrap();
+    let level = r.vox_level;
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    let mut db = -60.0;
+    if level > 0.0 {
+        db = (20.0 * level.log10()).clamp(-60.0, 0.0);
+    }
+    app_widgets.vox_level_bar.set_value(db.into());
+}
+
+// stop tuning after the tune timeout
+fn tune_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let r = radio_mutex.radio.lock().unwrap();
+    let expired = r.tune && r.tune_expired();
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    if expired && app_widgets.tun_button.is_active() {
+        app_widgets.tun_button.emit_clicked();
+    }
+}
+
+// run the transmit timeout and show the countdown or why transmit is refused
+fn tx_timer_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    r.update_tx_timer();
+    let mox = r.mox;
+    let status = if let Some(lockout) = r.tx_lockout() {
+        String::from(lockout.label())
+    } else if let Some(remaining) = r.tune_remaining() {
+        format!("Tune: {}s", remaining)
+    } else if let Some(remaining) = r.tx_remaining() {
+        format!("TX: {}:{:02}", remaining / 60, remaining % 60)
+    } else {
+        String::new()
+    };
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    if app_widgets.tx_status.label() != status {
+        app_widgets.tx_status.set_label(&status);
+    }
+    // the transmit timeout drops MOX
+    if !mox && app_widgets.mox_button.is_active() {
+        app_widgets.mox_button.emit_clicked();
+    }
+}
+
+// show a latched SWR alarm and release the transmit buttons it dropped
+fn swr_alarm_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let r = radio_mutex.radio.lock().unwrap();
+    let alarm = r.swr_alarm;
+    let dropped = alarm && r.transmitter.swr_protection == SwrProtection::DropTx;
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    if app_widgets.swr_alarm_button.is_visible() != alarm {
+        app_widgets.swr_alarm_button.set_visible(alarm);
+    }
+    if dropped {
+        if app_widgets.mox_button.is_active() {
+            app_widgets.mox_button.emit_clicked();
+        }
+        if app_widgets.tun_button.is_active() {
+            app_widgets.tun_button.emit_clicked();
+        }
+    }
+}
+
+// the drive slider is in watts, up to the maximum power of the radio
+fn drive_range_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let r = radio_mutex.radio.lock().unwrap();
+    let max_power = r.transmitter.max_power as f64;
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    if app_widgets.drive_adjustment.upper() != max_power {
+        app_widgets.drive_adjustment.set_upper(max_power);
+        if app_widgets.drive_adjustment.value() > max_power {
+            app_widgets.drive_adjustment.set_value(max_power);
+        }
+    }
+}
+
+// keep the TX profile selector in step with the profiles and the current mode
+fn tx_profile_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    if !r.tx_profiles.changed {
+        return;
+    }
+    r.tx_profiles.changed = false;
+    let mut names = r.tx_profiles.names();
+    let current = r.tx_profiles.current.clone();
+    let micgain = r.transmitter.micgain;
+    drop(r);
+
+    names.insert(0, String::from("None"));
+    let app_widgets = rc_app_widgets.borrow();
+    // the profile may have set the mic gain
+    if app_widgets.micgain_adjustment.value() != micgain as f64 {
+        app_widgets.micgain_adjustment.set_value(micgain.into());
+    }
+    let dropdown = &app_widgets.tx_profile_dropdown;
+    let mut items: Vec<String> = Vec::new();
+    if let Some(model) = dropdown.model().and_downcast::<StringList>() {
+        for i in 0..model.n_items() {
+            items.push(model.string(i).map(|s| s.to_string()).unwrap_or_default());
+        }
+    }
+    if items != names {
+        let list: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        dropdown.set_model(Some(&StringList::new(&list)));
+    }
+    let selected = names.iter().skip(1).position(|n| *n == current).map(|i| i + 1).unwrap_or(0);
+    if dropdown.selected() != selected as u32 {
+        dropdown.set_selected(selected as u32);
+    }
+}
+
+// append the decoded CW to each receiver's text pane
+fn cw_decoder_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    let mut rx = 0;
+    if r.receiver[1].active {
+        rx = 1;
+    }
+    let cw_decoder = r.receiver[rx].cw_decoder;
+    let wpm = r.receiver[rx].morse_decoder.wpm();
+    let text = [r.receiver[0].morse_decoder.take_text(), r.receiver[1].morse_decoder.take_text()];
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    if cw_decoder {
+        app_widgets.cw_decoder_wpm_label.set_label(&format!("{:.0} WPM", wpm));
+    } else {
+        app_widgets.cw_decoder_wpm_label.set_label("-- WPM");
+    }
+    let text_views = [&app_widgets.cw_decoder_text_view, &app_widgets.cw_decoder_2_text_view];
+    for i in 0..2 {
+        if text[i].is_empty() {
+            continue;
+        }
+        let buffer = text_views[i].buffer();
+        let mut end = buffer.end_iter();
+        buffer.insert(&mut end, &text[i]);
+        // keep the last few lines only
+        if buffer.char_count() > MORSE_TEXT_MAX {
+            let mut start = buffer.start_iter();
+            let mut trim = buffer.iter_at_offset(buffer.char_count() - MORSE_TEXT_MAX);
+            buffer.delete(&mut start, &mut trim);
+        }
+        let mut end = buffer.end_iter();
+        text_views[i].scroll_to_iter(&mut end, 0.0, false, 0.0, 0.0);
+    }
+}
+
+// hand a receiver spectrum to the panoramic scanner when it is waiting for one
+fn scanner_peaks(radio_mutex: &RadioMutex, rx: usize, pixels: &Vec<f32>) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    if r.scanner.wants_peaks(rx) && !pixels.is_empty() {
+        let sample_rate = r.receiver[rx].sample_rate as f32;
+        let frequency_low = r.receiver[rx].nco_frequency() - (sample_rate / 2.0);
+        let hz_per_pixel = sample_rate / pixels.len() as f32;
+        r.scanner.update_peaks(pixels, frequency_low, hz_per_pixel);
+    }
+}
+
+// look for carriers to notch in a receiver spectrum
+fn carrier_notches(radio_mutex: &RadioMutex, rx: usize, pixels: &Vec<f32>) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    if r.receiver[rx].auto_notch && !pixels.is_empty() {
+        let sample_rate = r.receiver[rx].sample_rate as f64;
+        // the spectrum is centred on the frequency the DDC is tuned to
+        let frequency_low = r.receiver[rx].nco_frequency() as f64 - (sample_rate / 2.0);
+        let hz_per_pixel = sample_rate / pixels.len() as f64;
+        r.receiver[rx].update_auto_notches(pixels, frequency_low, hz_per_pixel);
+    }
+}
+
+fn scanner_update(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    if !r.scanner.running || r.is_transmitting() {
+        return;
+    }
+    let rx = r.scanner.rx;
+    if rx == 1 && !r.rx2_enabled {
+        r.scanner.stop();
+        return;
+    }
+    let level: f32;
+    unsafe {
+        level = GetRXAMeter(r.receiver[rx].channel, rxaMeterType_RXA_S_AV as i32) as f32;
+    }
+    let mut squelch = None;
+    if r.receiver[rx].squelch {
+        squelch = Some(r.receiver[rx].squelch_open);
+    }
+    let step = r.receiver[rx].step;
+    let mode = Modes::from_usize(r.receiver[rx].mode).unwrap_or(Modes::USB);
+    let radio = &mut *r;
+    let action = radio.scanner.tick(level, squelch, &radio.memories.channels, step);
+    drop(r);
+
+    match action {
+        Some(ScanAction::Tune(frequency)) => qsy(radio_mutex, rc_app_widgets, rx, frequency, mode),
+        Some(ScanAction::Recall(memory)) => memory_recall(radio_mutex, rc_app_widgets, rx, &memory),
+        None => {},
+    }
+}
+
+// tune the active receiver to a frequency, changing band and mode as needed
+fn qsy(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>, rx: usize, frequency: f32, mode: Modes) {
+    let r = radio_mutex.radio.lock().unwrap();
+    let band = band_for_frequency(&r.receiver[rx].band_info, frequency).to_usize();
+    let current_band = r.receiver[rx].band.to_usize();
+    drop(r);
+
+    let app_widgets = rc_app_widgets.borrow();
+    if band != current_band {
+        let band_button = app_widgets.band_grid.get_button(band);
+        band_button.emit_by_name::<()>("clicked", &[]);
+    }
+
+    let r = radio_mutex.radio.lock().unwrap();
+    let current_mode = r.receiver[rx].mode;
+    drop(r);
+    if mode.to_usize() != current_mode {
+        let mode_button = app_widgets.mode_grid.get_button(mode.to_usize());
+        mode_button.emit_by_name::<()>("clicked", &[]);
+    }
+
+    let mut r = radio_mutex.radio.lock().unwrap();
+    if r.receiver[rx].ctun {
+        let frequency_low = r.receiver[rx].frequency - (r.receiver[rx].sample_rate/2) as f32;
+        let frequency_high = r.receiver[rx].frequency + (r.receiver[rx].sample_rate/2) as f32;
+        if frequency < frequency_low || frequency > frequency_high {
+            r.receiver[rx].frequency = frequency;
+        }
+        r.receiver[rx].ctun_frequency = frequency;
+        r.receiver[rx].set_ctun_frequency();
+    } else {
+        r.receiver[rx].frequency = frequency;
+    }
+    let formatted_value = format_u32_with_separators(frequency as u32);
+    if rx == 0 {
+        app_widgets.vfo_a_frequency.set_label(&formatted_value);
+    } else {
+        app_widgets.vfo_b_frequency.set_label(&formatted_value);
+    }
+}
+
+fn spectrum_waterfall_clicked(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>, rx: usize, x: f64, width: i32, button: u32, state: gdk::ModifierType) -> bool {
     let mut r = radio_mutex.radio.lock().unwrap();
     if rx == 0 {
         if !r.receiver[0].active {
@@ -1940,8 +3290,10 @@ fn spectrum_waterfall_clicked(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefC
 
     let app_widgets = rc_app_widgets.borrow();
         
-    let frequency_low = r.receiver[rx].frequency - (r.receiver[rx].sample_rate/2) as f32;
-    let frequency_high = r.receiver[rx].frequency + (r.receiver[rx].sample_rate/2) as f32;
+    // the same frequencies the spectrum is drawn with
+    let nco_frequency = r.receiver[rx].nco_frequency();
+    let frequency_low = nco_frequency - (r.receiver[rx].sample_rate/2) as f32;
+    let frequency_high = nco_frequency + (r.receiver[rx].sample_rate/2) as f32;
     let frequency_range = frequency_high - frequency_low;
                 
     let display_frequency_range = frequency_range / r.receiver[rx].zoom as f32;
@@ -1953,6 +3305,12 @@ fn spectrum_waterfall_clicked(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefC
         
     let f1 = display_frequency_low + (x as f32 * display_hz_per_pixel);
     let f1 = (f1 as u32 / r.receiver[rx].step as u32 * r.receiver[rx].step as u32) as f32;
+
+    // Ctrl+click tunes the sub-RX
+    if r.receiver[rx].subrx && state.contains(gdk::ModifierType::CONTROL_MASK) {
+        r.receiver[rx].set_subrx_frequency(f1);
+        return true;
+    }
         
     if r.receiver[rx].ctun {
         r.receiver[rx].ctun_frequency = f1;
@@ -1973,11 +3331,75 @@ fn spectrum_waterfall_clicked(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefC
         }
     }
 
-    unsafe {
-        RXANBPSetTuneFrequency(rx as i32, r.receiver[rx].frequency as f64);
+    true
+}
+
+// right click deletes a notch, Ctrl+right click enables or disables it, otherwise adds one
+fn spectrum_notch_clicked(radio_mutex: &RadioMutex, spectrum: &Spectrum, rx: usize, x: f64, state: gdk::ModifierType) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    match spectrum.notch_at(x) {
+        Some((index, _edge)) => {
+            if state.contains(gdk::ModifierType::CONTROL_MASK) {
+                let active = r.receiver[rx].notches()[index].is_active();
+                r.receiver[rx].set_notch_active(index, !active);
+            } else {
+                r.receiver[rx].delete_notch(index);
+            }
+        }
+        None => {
+            r.receiver[rx].add_notch(spectrum.frequency_at(x), NOTCH_DEFAULT_WIDTH);
+        }
     }
+}
 
-    true
+fn spectrum_notch_drag(display: &DrawingArea, radio_mutex: &RadioMutex, rc_spectrum: &Rc<RefCell<Spectrum>>, rx: usize) {
+    // the notch being dragged with its frequency and width at the start
+    let dragging: Rc<Cell<Option<(usize, NotchEdge, f64, f64)>>> = Rc::new(Cell::new(None));
+    let drag_gesture = GestureDrag::new();
+    drag_gesture.set_button(1); // left button
+
+    let radio_mutex_clone = radio_mutex.clone();
+    let rc_spectrum_clone = rc_spectrum.clone();
+    let dragging_clone = dragging.clone();
+    drag_gesture.connect_drag_begin(move |_gesture, x, _y| {
+        dragging_clone.set(None);
+        if let Some((index, edge)) = rc_spectrum_clone.borrow().notch_at(x) {
+            let r = radio_mutex_clone.radio.lock().unwrap();
+            if let Some(notch) = r.receiver[rx].notches().get(index) {
+                dragging_clone.set(Some((index, edge, notch.frequency, notch.width)));
+            }
+        }
+    });
+
+    let radio_mutex_clone = radio_mutex.clone();
+    let rc_spectrum_clone = rc_spectrum.clone();
+    let dragging_clone = dragging.clone();
+    drag_gesture.connect_drag_update(move |_gesture, offset_x, _offset_y| {
+        let Some((index, edge, frequency, width)) = dragging_clone.get() else {
+            return;
+        };
+        let offset = offset_x * rc_spectrum_clone.borrow().hz_per_pixel();
+        let (mut low, mut high) = (frequency - (width / 2.0), frequency + (width / 2.0));
+        match edge {
+            NotchEdge::Left => low = (low + offset).min(high - NOTCH_MIN_WIDTH),
+            NotchEdge::Right => high = (high + offset).max(low + NOTCH_MIN_WIDTH),
+            NotchEdge::Body => {
+                low = low + offset;
+                high = high + offset;
+            }
+        }
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if index < r.receiver[rx].notches().len() {
+            r.receiver[rx].edit_notch(index, (low + high) / 2.0, high - low);
+        }
+    });
+
+    let dragging_clone = dragging.clone();
+    drag_gesture.connect_drag_end(move |_gesture, _offset_x, _offset_y| {
+        dragging_clone.set(None);
+    });
+
+    display.add_controller(drag_gesture);
 }
 
 fn spectrum_waterfall_scroll(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>, rx: usize, dy: f64) {
@@ -2009,12 +3431,24 @@ fn spectrum_waterfall_scroll(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCe
             app_widgets.vfo_b_frequency.set_label(&formatted_value);
         }
     }
+}
 
-    unsafe {
-        RXANBPSetTuneFrequency(rx as i32, r.receiver[rx].frequency as f64);
+// Ctrl+scroll steps the sub-RX, only while it is on
+fn subrx_scroll(radio_mutex: &RadioMutex, rx: usize, dy: f64) {
+    let mut r = radio_mutex.radio.lock().unwrap();
+    if r.receiver[rx].subrx {
+        let frequency = r.receiver[rx].subrx_frequency - (r.receiver[rx].step * dy as f32);
+        r.receiver[rx].set_subrx_frequency(frequency);
     }
 }
 
+// the sub-RX filter choices follow its mode
+fn subrx_filter_list(app_widgets: &AppWidgets, mode: usize, filter: usize) {
+    let labels: Vec<&str> = (0..12).map(|i| app_widgets.filter_grid.get_filter_label(mode, i)).collect();
+    app_widgets.subrx_filter_dropdown.set_model(Some(&StringList::new(&labels)));
+    app_widgets.subrx_filter_dropdown.set_selected(filter as u32);
+}
+
 fn update_ui(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>) {
     let r = radio_mutex.radio.lock().unwrap();
     let mut rx = 0;
@@ -2031,13 +3465,26 @@ fn update_ui(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>)
     let nb2 = r.receiver[rx].nb2;
     let anf = r.receiver[rx].anf;
     let snb = r.receiver[rx].snb;
+    let auto_notch = r.receiver[rx].auto_notch;
     let afgain = r.receiver[rx].afgain;
     let agc = r.receiver[rx].agc;
     let agcgain = r.receiver[rx].agcgain;
     let ctun = r.receiver[rx].ctun;
+    let rit = r.receiver[rx].rit;
+    let rit_offset = r.receiver[rx].rit_offset;
+    let xit = r.receiver[rx].xit;
+    let xit_offset = r.receiver[rx].xit_offset;
+    let subrx = r.receiver[rx].subrx;
+    let subrx_balance = r.receiver[rx].subrx_balance;
+    let subrx_mode = r.receiver[rx].subrx_mode;
+    let subrx_filter = r.receiver[rx].subrx_filter;
     let zoom = r.receiver[rx].zoom;
     let pan = r.receiver[rx].pan;
     let cw_pitch = r.receiver[rx].cw_pitch;
+    let squelch = r.receiver[rx].squelch;
+    let squelch_level = r.receiver[rx].squelch_level;
+    let cw_decoder = r.receiver[rx].cw_decoder;
+    let cw_decoder_threshold = r.receiver[rx].cw_decoder_threshold;
     drop(r);
 
     let app_widgets = rc_app_widgets.borrow();
@@ -2055,8 +3502,9 @@ fn update_ui(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>)
         app_widgets.mode_frame.set_label(Some("RX2 Mode"));
         app_widgets.filter_frame.set_label(Some("RX2 Filter"));
     }
-    let band_button = app_widgets.band_grid.get_button(band.to_usize());
-    band_button.emit_by_name::<()>("clicked", &[]);
+    // select the band without clicking it, a click would move on to the next stacking register
+    let mut band_grid = app_widgets.band_grid.clone();
+    band_grid.set_active_index(band.to_usize());
 
     // update mode
     let mode_button = app_widgets.mode_grid.get_button(mode);
@@ -2075,12 +3523,8 @@ fn update_ui(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>)
     }
 
     // update NB/NB2
-    app_widgets.nb_button.set_active(nb | nb2);
-    if nb2 {
-        app_widgets.nb_button.set_label("NB2");
-    } else {
-        app_widgets.nb_button.set_label("NB");
-    }
+    app_widgets.nb_button.set_active(nb);
+    app_widgets.nb2_button.set_active(nb2);
 
     // update ANF
     app_widgets.anf_button.set_active(anf);
@@ -2088,6 +3532,9 @@ fn update_ui(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>)
     // update SNB
     app_widgets.snb_button.set_active(snb);
 
+    // update carrier notching
+    app_widgets.auto_notch_button.set_active(auto_notch);
+
     // update AFGain
     app_widgets.afgain_adjustment.set_value((afgain * 100.0).into());
 
@@ -2100,9 +3547,29 @@ fn update_ui(radio_mutex: &RadioMutex, rc_app_widgets: &Rc<RefCell<AppWidgets>>)
     // cw pitch
     app_widgets.cwpitch_adjustment.set_value(cw_pitch.into());
 
+    // update squelch
+    app_widgets.squelch_button.set_active(squelch);
+    app_widgets.squelch_adjustment.set_value(squelch_level.into());
+
+    // update CW decoder
+    app_widgets.cw_decoder_button.set_active(cw_decoder);
+    app_widgets.cw_decoder_threshold_adjustment.set_value(cw_decoder_threshold.into());
+
     // update CTUN
     app_widgets.ctun_button.set_active(ctun);
 
+    // update RIT/XIT
+    app_widgets.rit_button.set_active(rit);
+    app_widgets.rit_adjustment.set_value(rit_offset.into());
+    app_widgets.xit_button.set_active(xit);
+    app_widgets.xit_adjustment.set_value(xit_offset.into());
+
+    // update sub-RX
+    app_widgets.subrx_button.set_active(subrx);
+    app_widgets.subrx_balance_adjustment.set_value((subrx_balance * 100.0).into());
+    app_widgets.subrx_mode_dropdown.set_selected(subrx_mode as u32);
+    subrx_filter_list(&app_widgets, subrx_mode, subrx_filter);
+
     app_widgets.zoom_adjustment.set_value(zoom.into());
     app_widgets.pan_adjustment.set_value(pan.into());
 }
@@ -0,0 +1,573 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use gtk::prelude::*;
+use gtk::{gio, ApplicationWindow, Builder, Button, CheckButton, ComboBoxText, Entry, FileDialog, Label, ListBox, ListBoxRow, Orientation, Window};
+use serde::{Deserialize, Serialize};
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::agc::AGC;
+use crate::bands::*;
+use crate::modes::Modes;
+use crate::radio::{Radio, RadioMutex};
+use crate::util::*;
+
+const CSV_HEADER: &str = "Location,Name,Frequency,Duplex,Offset,Tone,rToneFreq,cToneFreq,DtcsCode,DtcsPolarity,Mode,TStep,Skip,Comment,URCALL,RPT1CALL,RPT2CALL,RxMode,Filter,AGC,NR,NB,Antenna,CTUNOffset,Tags";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Memory {
+    pub name: String,
+    pub frequency: f32,
+    pub mode: Modes,
+    pub filter: usize,
+    pub ctun: bool,
+    pub ctun_offset: f32,
+    pub agc: AGC,
+    pub nr: bool,
+    pub nr2: bool,
+    pub nb: bool,
+    pub nb2: bool,
+    pub antenna: u32,
+    pub ctcss: Option<f32>,
+    pub tone_squelch: bool,
+    pub offset: f32,
+    pub tags: Vec<String>,
+    pub skip: bool,
+}
+
+impl Memory {
+
+    // capture the current state of a receiver
+    pub fn from_receiver(r: &Radio, rx: usize, name: &str, tags: Vec<String>) -> Memory {
+        let receiver = &r.receiver[rx];
+        let mut ctcss = None;
+        if r.transmitter.ctcss {
+            ctcss = Some(r.transmitter.ctcss_frequency);
+        } else if receiver.ctcss {
+            ctcss = Some(receiver.ctcss_frequency);
+        }
+        let mut frequency = receiver.frequency;
+        let mut ctun_offset = 0.0;
+        if receiver.ctun {
+            frequency = receiver.ctun_frequency;
+            ctun_offset = receiver.ctun_frequency - receiver.frequency;
+        }
+        Memory {
+            name: String::from(name),
+            frequency,
+            mode: Modes::from_usize(receiver.mode).unwrap_or(Modes::USB),
+            filter: receiver.filter,
+            ctun: receiver.ctun,
+            ctun_offset,
+            agc: receiver.agc,
+            nr: receiver.nr,
+            nr2: receiver.nr2,
+            nb: receiver.nb,
+            nb2: receiver.nb2,
+            antenna: r.adc[receiver.adc].rx_antenna,
+            ctcss,
+            tone_squelch: receiver.ctcss,
+            offset: r.transmitter.repeater_shift(),
+            tags,
+            skip: false,
+        }
+    }
+
+    fn to_csv(&self, location: usize) -> String {
+        let mut duplex = "";
+        if self.offset > 0.0 {
+            duplex = "+";
+        } else if self.offset < 0.0 {
+            duplex = "-";
+        }
+        let mut tone = "";
+        let mut tone_frequency = 88.5;
+        if let Some(ctcss) = self.ctcss {
+            tone = "Tone";
+            if self.tone_squelch {
+                tone = "TSQL";
+            }
+            tone_frequency = ctcss;
+        }
+        let mode = match self.mode {
+            Modes::LSB => "LSB",
+            Modes::USB => "USB",
+            Modes::CWL => "CWR",
+            Modes::CWU => "CW",
+            Modes::FMN => "FM",
+            Modes::DIGU | Modes::DIGL => "DIG",
+            _ => "AM",
+        };
+        let mut nr = "";
+        if self.nr {
+            nr = "NR";
+        } else if self.nr2 {
+            nr = "NR2";
+        }
+        let mut nb = "";
+        if self.nb {
+            nb = "NB";
+        } else if self.nb2 {
+            nb = "NB2";
+        }
+        let mut skip = "";
+        if self.skip {
+            skip = "S";
+        }
+        let mut ctun_offset = String::from("");
+        if self.ctun {
+            ctun_offset = format!("{}", self.ctun_offset);
+        }
+        format!("{},{},{:.6},{},{:.6},{},{:.1},{:.1},023,NN,{},5.00,{},,,,,{:?},{},{:?},{},{},{},{},{}",
+            location,
+            csv_field(&self.name),
+            self.frequency as f64 / 1000000.0,
+            duplex,
+            self.offset.abs() as f64 / 1000000.0,
+            tone,
+            tone_frequency,
+            tone_frequency,
+            mode,
+            skip,
+            self.mode,
+            self.filter,
+            self.agc,
+            nr,
+            nb,
+            self.antenna,
+            ctun_offset,
+            csv_field(&self.tags.join(";")))
+    }
+
+    fn from_csv(header: &Vec<String>, fields: &Vec<String>, band_info: &Vec<BandInfo>) -> Option<Memory> {
+        let get = |name: &str| -> &str {
+            match header.iter().position(|h| h.eq_ignore_ascii_case(name)) {
+                Some(i) if i < fields.len() => fields[i].trim(),
+                _ => "",
+            }
+        };
+        let frequency = (get("Frequency").parse::<f64>().ok()? * 1000000.0) as f32;
+        let band = band_for_frequency(band_info, frequency).to_usize();
+
+        let mut mode = match get("Mode").to_uppercase().as_str() {
+            "LSB" => Modes::LSB,
+            "USB" => Modes::USB,
+            "CW" => Modes::CWU,
+            "CWR" => Modes::CWL,
+            "FM" | "NFM" | "WFM" => Modes::FMN,
+            "AM" | "NAM" => Modes::AM,
+            "DIG" | "PKT" => Modes::DIGU,
+            "RTTY" => Modes::DIGL,
+            _ => band_info[band].mode,
+        };
+        let rx_mode = get("RxMode");
+        for i in 0..=Modes::DRM.to_usize() {
+            let m = Modes::from_usize(i).unwrap();
+            if format!("{:?}", m) == rx_mode {
+                mode = m;
+            }
+        }
+
+        let mut filter = band_info[band].filter.to_usize();
+        if let Ok(f) = get("Filter").parse::<usize>() {
+            filter = f;
+        }
+
+        let mut agc = AGC::FAST;
+        for i in 0..=AGC::FAST.to_i32() {
+            let a = AGC::from_i32(i).unwrap();
+            if format!("{:?}", a) == get("AGC") {
+                agc = a;
+            }
+        }
+
+        let mut offset = (get("Offset").parse::<f64>().unwrap_or(0.0) * 1000000.0) as f32;
+        match get("Duplex") {
+            "+" => {},
+            "-" => offset = -offset,
+            _ => offset = 0.0,
+        }
+
+        let ctcss = match get("Tone") {
+            "Tone" => get("rToneFreq").parse::<f32>().ok(),
+            "TSQL" => get("cToneFreq").parse::<f32>().ok(),
+            _ => None,
+        };
+
+        let ctun_offset = get("CTUNOffset").parse::<f32>().ok();
+
+        let mut tags: Vec<String> = get("Tags").split(';').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+        if tags.is_empty() && !get("Comment").is_empty() {
+            tags.push(get("Comment").to_string());
+        }
+
+        let mut name = get("Name").to_string();
+        if name.is_empty() {
+            name = format_u32_with_separators(frequency as u32);
+        }
+
+        Some(Memory {
+            name,
+            frequency,
+            mode,
+            filter,
+            ctun: ctun_offset.is_some(),
+            ctun_offset: ctun_offset.unwrap_or(0.0),
+            agc,
+            nr: get("NR") == "NR",
+            nr2: get("NR") == "NR2",
+            nb: get("NB") == "NB",
+            nb2: get("NB") == "NB2",
+            antenna: get("Antenna").parse::<u32>().unwrap_or(0),
+            ctcss,
+            tone_squelch: get("Tone") == "TSQL",
+            offset,
+            tags,
+            skip: get("Skip") == "S",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Memories {
+    pub channels: Vec<Memory>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub recall: Option<(usize, Memory)>,
+}
+
+impl Memories {
+
+    pub fn new() -> Memories {
+        let channels: Vec<Memory> = Vec::new();
+        let recall = None;
+        Memories {
+            channels,
+            recall,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.recall = None;
+    }
+
+    // all the tags in use, sorted
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for memory in &self.channels {
+            for tag in &memory.tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+        tags.sort();
+        tags
+    }
+
+    pub fn export_csv(&self, path: &Path) -> Result<(), std::io::Error> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", CSV_HEADER)?;
+        for (i, memory) in self.channels.iter().enumerate() {
+            writeln!(file, "{}", memory.to_csv(i))?;
+        }
+        Ok(())
+    }
+
+    // returns the number of memories added
+    pub fn import_csv(&mut self, path: &Path, band_info: &Vec<BandInfo>) -> Result<usize, std::io::Error> {
+        let mut file = File::open(path)?;
+        let mut s = String::new();
+        file.read_to_string(&mut s)?;
+        let mut lines = s.lines().filter(|line| !line.trim().is_empty());
+        let header = match lines.next() {
+            Some(line) => csv_split(line),
+            None => return Ok(0),
+        };
+        let mut count = 0;
+        for line in lines {
+            if let Some(memory) = Memory::from_csv(&header, &csv_split(line), band_info) {
+                self.channels.push(memory);
+                count = count + 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_split(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    quoted = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == '"' {
+            quoted = true;
+        } else if c == ',' {
+            fields.push(field.clone());
+            field.clear();
+        } else {
+            field.push(c);
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+pub fn create_memories_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMutex) -> Window {
+
+    let ui_xml = include_str!("../ui/memories.xml");
+    let builder = Builder::from_string(ui_xml);
+
+    let window: Window = builder
+            .object("memories_window")
+            .expect("Could not get object `memories_window` from builder.");
+
+    window.set_transient_for(Some(parent)); // keeps it on top
+
+    let memory_list: ListBox = builder
+            .object("memory_list")
+            .expect("Could not get object `memory_list` from builder.");
+
+    let tag_combo_box: ComboBoxText = builder
+            .object("memory_tag_combo_box")
+            .expect("Could not get object `memory_tag_combo_box` from builder.");
+    populate_tags(&tag_combo_box, radio_mutex);
+    let memory_list_clone = memory_list.clone();
+    let radio_mutex_clone = radio_mutex.clone();
+    tag_combo_box.connect_changed(move |combo_box| {
+        populate_memory_list(&memory_list_clone, &radio_mutex_clone, &tag(combo_box));
+    });
+
+    let name_entry: Entry = builder
+            .object("memory_name_entry")
+            .expect("Could not get object `memory_name_entry` from builder.");
+
+    let tags_entry: Entry = builder
+            .object("memory_tags_entry")
+            .expect("Could not get object `memory_tags_entry` from builder.");
+
+    for rx in 0..2 {
+        let id = format!("memory_store_rx{}_button", rx + 1);
+        let store_button: Button = builder
+                .object(id.as_str())
+                .expect("Could not get object `memory_store_button` from builder.");
+        let r = radio_mutex.radio.lock().unwrap();
+        store_button.set_sensitive(rx == 0 || r.rx2_enabled);
+        drop(r);
+        let radio_mutex_clone = radio_mutex.clone();
+        let memory_list_clone = memory_list.clone();
+        let tag_combo_box_clone = tag_combo_box.clone();
+        let name_entry_clone = name_entry.clone();
+        let tags_entry_clone = tags_entry.clone();
+        store_button.connect_clicked(move |_| {
+            let tags: Vec<String> = tags_entry_clone.text().split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            let mut name = name_entry_clone.text().to_string();
+            if name.is_empty() {
+                let mut frequency = r.receiver[rx].frequency;
+                if r.receiver[rx].ctun {
+                    frequency = r.receiver[rx].ctun_frequency;
+                }
+                name = format_u32_with_separators(frequency as u32);
+            }
+            let memory = Memory::from_receiver(&r, rx, &name, tags);
+            r.memories.channels.push(memory);
+            drop(r);
+            name_entry_clone.set_text("");
+            populate_tags(&tag_combo_box_clone, &radio_mutex_clone);
+            populate_memory_list(&memory_list_clone, &radio_mutex_clone, &tag(&tag_combo_box_clone));
+        });
+    }
+
+    let import_button: Button = builder
+            .object("memory_import_button")
+            .expect("Could not get object `memory_import_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    let memory_list_clone = memory_list.clone();
+    let tag_combo_box_clone = tag_combo_box.clone();
+    import_button.connect_clicked(move |_| {
+        let dialog = FileDialog::builder().title("Import Memories").build();
+        let radio_mutex_clone = radio_mutex_clone.clone();
+        let memory_list_clone = memory_list_clone.clone();
+        let tag_combo_box_clone = tag_combo_box_clone.clone();
+        dialog.open(Some(&window_clone), None::<&gio::Cancellable>, move |result| {
+            if let Ok(file) = result {
+                if let Some(path) = file.path() {
+                    let mut r = radio_mutex_clone.radio.lock().unwrap();
+                    let band_info = r.receiver[0].band_info.clone();
+                    match r.memories.import_csv(&path, &band_info) {
+                        Ok(count) => println!("Imported {} memories from {:?}", count, path),
+                        Err(e) => eprintln!("Error importing memories from {:?}: {}", path, e),
+                    }
+                    drop(r);
+                    populate_tags(&tag_combo_box_clone, &radio_mutex_clone);
+                    populate_memory_list(&memory_list_clone, &radio_mutex_clone, &tag(&tag_combo_box_clone));
+                }
+            }
+        });
+    });
+
+    let export_button: Button = builder
+            .object("memory_export_button")
+            .expect("Could not get object `memory_export_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    export_button.connect_clicked(move |_| {
+        let dialog = FileDialog::builder().title("Export Memories").initial_name("memories.csv").build();
+        let radio_mutex_clone = radio_mutex_clone.clone();
+        dialog.save(Some(&window_clone), None::<&gio::Cancellable>, move |result| {
+            if let Ok(file) = result {
+                if let Some(path) = file.path() {
+                    let r = radio_mutex_clone.radio.lock().unwrap();
+                    if let Err(e) = r.memories.export_csv(&path) {
+                        eprintln!("Error exporting memories to {:?}: {}", path, e);
+                    }
+                }
+            }
+        });
+    });
+
+    populate_memory_list(&memory_list, radio_mutex, "");
+
+    window
+}
+
+// the selected tag, empty for all
+fn tag(combo_box: &ComboBoxText) -> String {
+    match combo_box.active() {
+        Some(0) | None => String::from(""),
+        Some(_) => combo_box.active_text().map(|t| t.to_string()).unwrap_or_default(),
+    }
+}
+
+fn populate_tags(combo_box: &ComboBoxText, radio_mutex: &RadioMutex) {
+    let current = tag(combo_box);
+    let r = radio_mutex.radio.lock().unwrap();
+    let tags = r.memories.tags();
+    drop(r);
+
+    combo_box.remove_all();
+    combo_box.append_text("All");
+    combo_box.set_active(Some(0));
+    for (i, t) in tags.iter().enumerate() {
+        combo_box.append_text(t);
+        if *t == current {
+            combo_box.set_active(Some(i as u32 + 1));
+        }
+    }
+}
+
+fn populate_memory_list(list: &ListBox, radio_mutex: &RadioMutex, tag: &str) {
+
+    // Remove any existing rows
+    while let Some(row) = list.row_at_index(0) {
+        list.remove(&row);
+    }
+
+    let r = radio_mutex.radio.lock().unwrap();
+    let rx2_enabled = r.rx2_enabled;
+    let mut memories: Vec<(usize, Memory)> = r.memories.channels.iter().cloned().enumerate()
+        .filter(|(_, m)| tag.is_empty() || m.tags.iter().any(|t| t == tag))
+        .collect();
+    drop(r);
+
+    // group by the first tag
+    memories.sort_by(|(_, a), (_, b)| a.tags.first().cmp(&b.tags.first()).then(a.frequency.total_cmp(&b.frequency)));
+
+    for (i, memory) in memories {
+        let row = ListBoxRow::new();
+        let hbox = gtk::Box::new(Orientation::Horizontal, 10);
+        let label_name = Label::new(Some(&memory.name));
+        label_name.set_xalign(0.0); // Align text to the left
+        label_name.set_size_request(150,-1);
+        hbox.append(&label_name);
+        let frequency = format_u32_with_separators(memory.frequency as u32);
+        let label_frequency = Label::new(Some(&frequency));
+        label_frequency.set_xalign(1.0); // Align text to the right
+        label_frequency.set_size_request(100,-1);
+        hbox.append(&label_frequency);
+        let mode = format!("{:?}", memory.mode);
+        let label_mode = Label::new(Some(&mode));
+        label_mode.set_xalign(0.0); // Align text to the left
+        label_mode.set_size_request(50,-1);
+        hbox.append(&label_mode);
+        let label_tags = Label::new(Some(&memory.tags.join(", ")));
+        label_tags.set_xalign(0.0); // Align text to the left
+        label_tags.set_size_request(150,-1);
+        hbox.append(&label_tags);
+        let scan_check_button = CheckButton::with_label("Scan");
+        scan_check_button.set_active(!memory.skip);
+        let radio_mutex_clone = radio_mutex.clone();
+        scan_check_button.connect_toggled(move |button| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            if i < r.memories.channels.len() {
+                r.memories.channels[i].skip = !button.is_active();
+            }
+        });
+        hbox.append(&scan_check_button);
+        for rx in 0..2 {
+            let recall_button = Button::with_label(&format!("RX{}", rx + 1));
+            recall_button.set_sensitive(rx == 0 || rx2_enabled);
+            let radio_mutex_clone = radio_mutex.clone();
+            let memory_clone = memory.clone();
+            recall_button.connect_clicked(move |_| {
+                let mut r = radio_mutex_clone.radio.lock().unwrap();
+                r.memories.recall = Some((rx, memory_clone.clone()));
+            });
+            hbox.append(&recall_button);
+        }
+        let delete_button = Button::with_label("Delete");
+        let list_clone = list.clone();
+        let radio_mutex_clone = radio_mutex.clone();
+        let tag_clone = String::from(tag);
+        delete_button.connect_clicked(move |_| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            if i < r.memories.channels.len() {
+                r.memories.channels.remove(i);
+            }
+            drop(r);
+            populate_memory_list(&list_clone, &radio_mutex_clone, &tag_clone);
+        });
+        hbox.append(&delete_button);
+        row.set_child(Some(&hbox));
+
+        list.append(&row);
+    }
+}
@@ -0,0 +1,395 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use alsa::seq::{Addr, ClientIter, EvCtrl, EvNote, Event, EventType, PortCap, PortIter, PortSubscribe, PortType, Seq};
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Copy, Clone)]
+pub enum MidiAction {
+    None,
+    VfoKnob,
+    AfGain,
+    AgcGain,
+    Drive,
+    MicGain,
+    Mox,
+    Tune,
+    Nr,
+    Nb,
+    BandUp,
+    BandDown,
+    ModeUp,
+    ModeDown,
+    StepUp,
+    StepDown,
+}
+
+impl MidiAction {
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(MidiAction::None),
+            1 => Some(MidiAction::VfoKnob),
+            2 => Some(MidiAction::AfGain),
+            3 => Some(MidiAction::AgcGain),
+            4 => Some(MidiAction::Drive),
+            5 => Some(MidiAction::MicGain),
+            6 => Some(MidiAction::Mox),
+            7 => Some(MidiAction::Tune),
+            8 => Some(MidiAction::Nr),
+            9 => Some(MidiAction::Nb),
+            10 => Some(MidiAction::BandUp),
+            11 => Some(MidiAction::BandDown),
+            12 => Some(MidiAction::ModeUp),
+            13 => Some(MidiAction::ModeDown),
+            14 => Some(MidiAction::StepUp),
+            15 => Some(MidiAction::StepDown),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MidiAction::None => "None",
+            MidiAction::VfoKnob => "VFO Knob",
+            MidiAction::AfGain => "AF Gain",
+            MidiAction::AgcGain => "AGC Gain",
+            MidiAction::Drive => "Drive",
+            MidiAction::MicGain => "Mic Gain",
+            MidiAction::Mox => "MOX",
+            MidiAction::Tune => "TUN",
+            MidiAction::Nr => "NR",
+            MidiAction::Nb => "NB",
+            MidiAction::BandUp => "Band Up",
+            MidiAction::BandDown => "Band Down",
+            MidiAction::ModeUp => "Mode Up",
+            MidiAction::ModeDown => "Mode Down",
+            MidiAction::StepUp => "Step Up",
+            MidiAction::StepDown => "Step Down",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Copy, Clone)]
+pub enum MidiEventType {
+    Note,
+    Controller,
+    PitchBend,
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+pub struct MidiMapping {
+    pub event: MidiEventType,
+    pub channel: u8,
+    pub param: u32,
+    pub action: MidiAction,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct MidiMessage {
+    pub event: MidiEventType,
+    pub channel: u8,
+    pub param: u32,
+    pub value: i32,
+}
+
+impl MidiMessage {
+    // jog wheels send relative values, 1..63 clockwise and 65..127 anti-clockwise
+    pub fn relative(&self) -> i32 {
+        match self.event {
+            MidiEventType::PitchBend => self.value.signum(),
+            _ => {
+                if self.value < 64 {
+                    self.value
+                } else {
+                    self.value - 128
+                }
+            }
+        }
+    }
+
+    // absolute position of a fader in the range 0.0 to 1.0
+    pub fn position(&self) -> f64 {
+        match self.event {
+            MidiEventType::PitchBend => (self.value + 8192) as f64 / 16383.0,
+            _ => self.value as f64 / 127.0,
+        }
+    }
+
+    pub fn pressed(&self) -> bool {
+        self.value >= 64 || (self.event == MidiEventType::Note && self.value > 0)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Midi {
+    pub enabled: bool,
+    pub device: String,
+    pub mappings: Vec<MidiMapping>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub learn: Option<MidiAction>,
+    #[serde(skip_serializing, skip_deserializing)]
+    running: Option<Arc<AtomicBool>>,
+    #[serde(skip_serializing, skip_deserializing)]
+    input: Option<Receiver<MidiMessage>>,
+    #[serde(skip_serializing, skip_deserializing)]
+    output: Option<Sender<MidiMessage>>,
+    #[serde(skip_serializing, skip_deserializing)]
+    leds: Vec<(MidiAction, bool)>,
+}
+
+impl Midi {
+
+    pub fn new() -> Midi {
+        let enabled = false;
+        let device = String::from("");
+        let mappings: Vec<MidiMapping> = Vec::new();
+        let learn = None;
+        let running = None;
+        let input = None;
+        let output = None;
+        let leds: Vec<(MidiAction, bool)> = Vec::new();
+        Midi {
+            enabled,
+            device,
+            mappings,
+            learn,
+            running,
+            input,
+            output,
+            leds,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.learn = None;
+        self.running = None;
+        self.input = None;
+        self.output = None;
+        self.leds = Vec::new();
+        if self.enabled {
+            if let Err(e) = self.open() {
+                eprintln!("midi::open failed: {}", e);
+            }
+        }
+    }
+
+    pub fn list_devices() -> Vec<String> {
+        let mut devices = Vec::<String>::new();
+        let seq = match Seq::open(None, None, true) {
+            Ok(seq) => seq,
+            Err(e) => {
+                eprintln!("Failed to open ALSA sequencer: {}", e);
+                return devices;
+            }
+        };
+        let my_id = seq.client_id().unwrap_or(-1);
+        for client in ClientIter::new(&seq) {
+            if client.get_client() == my_id {
+                continue;
+            }
+            for port in PortIter::new(&seq, client.get_client()) {
+                let caps = port.get_capability();
+                if caps.contains(PortCap::READ) && caps.contains(PortCap::SUBS_READ) {
+                    if let (Ok(client_name), Ok(port_name)) = (client.get_name(), port.get_name()) {
+                        devices.push(format!("{}:{}", client_name, port_name));
+                    }
+                }
+            }
+        }
+        devices
+    }
+
+    fn find_device(seq: &Seq, name: &str) -> Option<Addr> {
+        for client in ClientIter::new(seq) {
+            for port in PortIter::new(seq, client.get_client()) {
+                if let (Ok(client_name), Ok(port_name)) = (client.get_name(), port.get_name()) {
+                    if format!("{}:{}", client_name, port_name) == name {
+                        return Some(port.addr());
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub fn open(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        println!("midi::open {}", self.device);
+        self.close();
+
+        let seq = Seq::open(None, None, true)?;
+        seq.set_client_name(&CString::new("rustyHPSDR")?)?;
+        let port = seq.create_simple_port(
+            &CString::new("rustyHPSDR")?,
+            PortCap::READ | PortCap::SUBS_READ | PortCap::WRITE | PortCap::SUBS_WRITE,
+            PortType::MIDI_GENERIC | PortType::APPLICATION,
+        )?;
+        let me = Addr { client: seq.client_id()?, port };
+
+        let device = Self::find_device(&seq, &self.device).ok_or("MIDI device not found")?;
+
+        // controller to us
+        let subscribe = PortSubscribe::empty()?;
+        subscribe.set_sender(device);
+        subscribe.set_dest(me);
+        seq.subscribe_port(&subscribe)?;
+
+        // us to controller for the LEDs, not all devices accept input
+        let subscribe = PortSubscribe::empty()?;
+        subscribe.set_sender(me);
+        subscribe.set_dest(device);
+        if let Err(e) = seq.subscribe_port(&subscribe) {
+            eprintln!("midi::open no LED output: {}", e);
+        }
+
+        let running = Arc::new(AtomicBool::new(true));
+        let (input_tx, input_rx) = channel::<MidiMessage>();
+        let (output_tx, output_rx) = channel::<MidiMessage>();
+        self.running = Some(running.clone());
+        self.input = Some(input_rx);
+        self.output = Some(output_tx);
+        self.leds = Vec::new();
+
+        thread::spawn(move || {
+            let mut input = seq.input();
+            while running.load(Ordering::Relaxed) {
+                while input.event_input_pending(true).unwrap_or(0) > 0 {
+                    let event = match input.event_input() {
+                        Ok(event) => event,
+                        Err(_) => break,
+                    };
+                    let message = match event.get_type() {
+                        EventType::Noteon | EventType::Noteoff => {
+                            event.get_data::<EvNote>().map(|note| {
+                                let mut velocity = note.velocity as i32;
+                                if event.get_type() == EventType::Noteoff {
+                                    velocity = 0;
+                                }
+                                MidiMessage { event: MidiEventType::Note, channel: note.channel, param: note.note as u32, value: velocity }
+                            })
+                        }
+                        EventType::Controller => {
+                            event.get_data::<EvCtrl>().map(|ctrl| {
+                                MidiMessage { event: MidiEventType::Controller, channel: ctrl.channel, param: ctrl.param, value: ctrl.value }
+                            })
+                        }
+                        EventType::Pitchbend => {
+                            event.get_data::<EvCtrl>().map(|ctrl| {
+                                MidiMessage { event: MidiEventType::PitchBend, channel: ctrl.channel, param: 0, value: ctrl.value }
+                            })
+                        }
+                        _ => None,
+                    };
+                    if let Some(message) = message {
+                        if input_tx.send(message).is_err() {
+                            return;
+                        }
+                    }
+                }
+
+                while let Ok(led) = output_rx.try_recv() {
+                    let note = EvNote { channel: led.channel, note: led.param as u8, velocity: led.value as u8, off_velocity: 0, duration: 0 };
+                    let mut event = Event::new(EventType::Noteon, &note);
+                    event.set_source(port);
+                    event.set_subs();
+                    event.set_direct();
+                    let _ = seq.event_output_direct(&mut event);
+                }
+
+                thread::sleep(Duration::from_millis(5));
+            }
+            println!("midi thread exit");
+        });
+
+        println!("midi::open Ok");
+        Ok(())
+    }
+
+    pub fn close(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.store(false, Ordering::Relaxed);
+        }
+        self.input = None;
+        self.output = None;
+    }
+
+    // returns the mapped actions for any events received since the last call
+    pub fn poll(&mut self) -> Vec<(MidiAction, MidiMessage)> {
+        let mut actions = Vec::new();
+        let mut messages = Vec::new();
+        if let Some(input) = &self.input {
+            while let Ok(message) = input.try_recv() {
+                messages.push(message);
+            }
+        }
+
+        for message in messages {
+            if let Some(action) = self.learn {
+                // ignore button releases so the press is what gets learnt
+                if message.event == MidiEventType::Note && message.value == 0 {
+                    continue;
+                }
+                self.mappings.retain(|m| m.action != action && !(m.event == message.event && m.channel == message.channel && m.param == message.param));
+                self.mappings.push(MidiMapping { event: message.event, channel: message.channel, param: message.param, action });
+                self.learn = None;
+                self.leds = Vec::new();
+                continue;
+            }
+            for mapping in &self.mappings {
+                if mapping.event == message.event && mapping.channel == message.channel && mapping.param == message.param {
+                    actions.push((mapping.action, message));
+                }
+            }
+        }
+        actions
+    }
+
+    pub fn remove_mapping(&mut self, index: usize) {
+        if index < self.mappings.len() {
+            self.mappings.remove(index);
+        }
+    }
+
+    // turn the LED on a mapped button on or off, only sent when the state changes
+    pub fn set_led(&mut self, action: MidiAction, on: bool) {
+        if let Some(led) = self.leds.iter_mut().find(|(a, _)| *a == action) {
+            if led.1 == on {
+                return;
+            }
+            led.1 = on;
+        } else {
+            self.leds.push((action, on));
+        }
+        if let Some(output) = &self.output {
+            for mapping in &self.mappings {
+                if mapping.action == action && mapping.event == MidiEventType::Note {
+                    let value = if on { 127 } else { 0 };
+                    let _ = output.send(MidiMessage { event: MidiEventType::Note, channel: mapping.channel, param: mapping.param, value });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+pub const MORSE_MIN_WPM: f32 = 5.0;
+pub const MORSE_MAX_WPM: f32 = 40.0;
+// characters kept in a decoder text pane
+pub const MORSE_TEXT_MAX: i32 = 2000;
+
+// Goertzel block length, about 5ms at 12000 samples per second
+const BLOCK_SIZE: usize = 64;
+// marks shorter than this many blocks are treated as noise
+const GLITCH_BLOCKS: usize = 2;
+// the signal must be this far above the noise floor (power ratio) before the key is seen
+const MIN_SNR: f32 = 4.0;
+// recent mark lengths used to separate dots from dashes
+const MARK_HISTORY: usize = 16;
+
+const MORSE_TABLE: [(&str, char); 54] = [
+    (".-", 'A'), ("-...", 'B'), ("-.-.", 'C'), ("-..", 'D'), (".", 'E'), ("..-.", 'F'),
+    ("--.", 'G'), ("....", 'H'), ("..", 'I'), (".---", 'J'), ("-.-", 'K'), (".-..", 'L'),
+    ("--", 'M'), ("-.", 'N'), ("---", 'O'), (".--.", 'P'), ("--.-", 'Q'), (".-.", 'R'),
+    ("...", 'S'), ("-", 'T'), ("..-", 'U'), ("...-", 'V'), (".--", 'W'), ("-..-", 'X'),
+    ("-.--", 'Y'), ("--..", 'Z'),
+    ("-----", '0'), (".----", '1'), ("..---", '2'), ("...--", '3'), ("....-", '4'),
+    (".....", '5'), ("-....", '6'), ("--...", '7'), ("---..", '8'), ("----.", '9'),
+    (".-.-.-", '.'), ("--..--", ','), ("..--..", '?'), ("-..-.", '/'), ("-...-", '='),
+    (".-.-.", '+'), ("-....-", '-'), (".----.", '\''), ("-.-.--", '!'), ("-.--.", '('),
+    ("-.--.-", ')'), (".-...", '&'), ("---...", ':'), ("-.-.-.", ';'), (".-..-.", '"'),
+    ("...-..-", '$'), (".--.-.", '@'), ("..--.-", '_'),
+];
+
+pub fn morse_to_char(code: &str) -> Option<char> {
+    MORSE_TABLE.iter().find(|(c, _)| *c == code).map(|(_, ch)| *ch)
+}
+
+pub fn char_to_morse(ch: char) -> Option<&'static str> {
+    let ch = ch.to_ascii_uppercase();
+    MORSE_TABLE.iter().find(|(_, c)| *c == ch).map(|(code, _)| *code)
+}
+
+#[derive(Debug, Clone)]
+pub struct MorseDecoder {
+    sample_rate: f32,
+    block: Vec<f32>,
+    // adaptive signal peak and noise floor for QSB
+    peak: f32,
+    noise: f32,
+    key_down: bool,
+    mark_blocks: usize,
+    space_blocks: usize,
+    dot_ms: f32,
+    marks: VecDeque<f32>,
+    code: String,
+    word_sent: bool,
+    text: String,
+}
+
+impl MorseDecoder {
+
+    pub fn new(sample_rate: f32, wpm: f32) -> MorseDecoder {
+        let block = Vec::with_capacity(BLOCK_SIZE);
+        let peak = 0.0;
+        let noise = 0.0;
+        let key_down = false;
+        let mark_blocks = 0;
+        let space_blocks = 0;
+        let dot_ms = 1200.0 / wpm.clamp(MORSE_MIN_WPM, MORSE_MAX_WPM);
+        let marks = VecDeque::with_capacity(MARK_HISTORY);
+        let code = String::new();
+        let word_sent = true;
+        let text = String::new();
+        MorseDecoder {
+            sample_rate,
+            block,
+            peak,
+            noise,
+            key_down,
+            mark_blocks,
+            space_blocks,
+            dot_ms,
+            marks,
+            code,
+            word_sent,
+            text,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        let sample_rate = self.sample_rate;
+        let wpm = self.wpm();
+        *self = MorseDecoder::new(sample_rate, wpm);
+    }
+
+    pub fn wpm(&self) -> f32 {
+        1200.0 / self.dot_ms
+    }
+
+    // the decoded text since the last call
+    pub fn take_text(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+
+    // threshold is where the key is detected between the noise floor and the signal peak, 0.0 to 1.0
+    pub fn process(&mut self, samples: &[f32], pitch: f32, threshold: f32) {
+        for &sample in samples {
+            self.block.push(sample);
+            if self.block.len() >= BLOCK_SIZE {
+                let power = self.goertzel(pitch);
+                self.block.clear();
+                self.detect(power, threshold);
+            }
+        }
+    }
+
+    fn goertzel(&self, frequency: f32) -> f32 {
+        let coefficient = 2.0 * (2.0 * PI * frequency / self.sample_rate).cos();
+        let mut s1 = 0.0;
+        let mut s2 = 0.0;
+        for &x in &self.block {
+            let s0 = x + coefficient * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        (s1 * s1 + s2 * s2 - coefficient * s1 * s2) / (BLOCK_SIZE * BLOCK_SIZE) as f32
+    }
+
+    fn block_ms(&self) -> f32 {
+        BLOCK_SIZE as f32 * 1000.0 / self.sample_rate
+    }
+
+    fn detect(&mut self, power: f32, threshold: f32) {
+        // fast attack and slow release on the peak, the reverse on the noise floor,
+        // so both follow fading without losing the key between elements
+        if power > self.peak {
+            self.peak = power;
+        } else {
+            self.peak = self.peak * 0.995 + power * 0.005;
+        }
+        if power < self.noise || self.noise == 0.0 {
+            self.noise = power;
+        } else {
+            self.noise = self.noise * 0.998 + power * 0.002;
+        }
+
+        let level = self.noise + (self.peak - self.noise) * threshold.clamp(0.05, 0.95);
+        let hysteresis = (self.peak - self.noise) * 0.1;
+        let signal = self.peak > self.noise * MIN_SNR;
+        let key_down = if self.key_down {
+            signal && power > level - hysteresis
+        } else {
+            signal && power > level + hysteresis
+        };
+
+        if key_down {
+            self.mark_blocks += 1;
+        } else if self.key_down {
+            if self.mark_blocks >= GLITCH_BLOCKS {
+                self.mark_ended();
+            } else {
+                // too short to be a mark, carry on timing the space
+                self.space_blocks += self.mark_blocks;
+            }
+            self.mark_blocks = 0;
+        } else {
+            self.space_blocks += 1;
+            self.check_space();
+        }
+        self.key_down = key_down;
+    }
+
+    fn mark_ended(&mut self) {
+        let mark_ms = self.mark_blocks as f32 * self.block_ms();
+        if self.marks.len() >= MARK_HISTORY {
+            self.marks.pop_front();
+        }
+        self.marks.push_back(mark_ms);
+
+        // once both dots and dashes have been seen split them half way,
+        // otherwise go by the current speed estimate
+        let shortest = self.marks.iter().cloned().fold(f32::MAX, f32::min);
+        let longest = self.marks.iter().cloned().fold(0.0, f32::max);
+        let mut boundary = self.dot_ms * 2.0;
+        if longest >= shortest * 2.0 {
+            boundary = (shortest + longest) / 2.0;
+        }
+
+        let dot_ms = if mark_ms < boundary {
+            self.code.push('.');
+            mark_ms
+        } else {
+            self.code.push('-');
+            mark_ms / 3.0
+        };
+        // track the sending speed
+        self.dot_ms = (self.dot_ms * 0.8 + dot_ms * 0.2).clamp(1200.0 / MORSE_MAX_WPM, 1200.0 / MORSE_MIN_WPM);
+        if self.code.len() > 7 {
+            // not a character we know, drop it
+            self.code.clear();
+        }
+        self.word_sent = false;
+        self.space_blocks = 0;
+    }
+
+    fn check_space(&mut self) {
+        let space_ms = self.space_blocks as f32 * self.block_ms();
+        if !self.code.is_empty() && space_ms > self.dot_ms * 2.0 {
+            match morse_to_char(&self.code) {
+                Some(ch) => self.text.push(ch),
+                None => self.text.push('*'),
+            }
+            self.code.clear();
+        }
+        if !self.word_sent && self.code.is_empty() && space_ms > self.dot_ms * 5.0 {
+            self.text.push(' ');
+            self.word_sent = true;
+        }
+    }
+}
+
+impl Default for MorseDecoder {
+    fn default() -> Self {
+        MorseDecoder::new(12000.0, 20.0)
+    }
+}
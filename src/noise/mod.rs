@@ -0,0 +1,311 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use gtk::prelude::*;
+use gtk::{Adjustment, ApplicationWindow, Builder, CheckButton, DropDown, Label, Window};
+use serde::{Deserialize, Serialize};
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::radio::RadioMutex;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoisePreset {
+    Custom,
+    Default,
+    PowerLine,
+    Ignition,
+}
+
+impl NoisePreset {
+    pub fn from_u32(i: u32) -> Option<Self> {
+        match i {
+            0 => Some(NoisePreset::Custom),
+            1 => Some(NoisePreset::Default),
+            2 => Some(NoisePreset::PowerLine),
+            3 => Some(NoisePreset::Ignition),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+
+    // the preset the settings came from, if they have not been changed since
+    pub fn matching(settings: &NoiseSettings) -> Self {
+        for preset in [NoisePreset::Default, NoisePreset::PowerLine, NoisePreset::Ignition] {
+            if preset.settings() == Some(*settings) {
+                return preset;
+            }
+        }
+        NoisePreset::Custom
+    }
+
+    pub fn settings(&self) -> Option<NoiseSettings> {
+        match self {
+            NoisePreset::Custom => None,
+            NoisePreset::Default => Some(NoiseSettings::default()),
+            // long bursts of buzz, blank early and hold off for longer
+            NoisePreset::PowerLine => Some(NoiseSettings {
+                nr2_gain_method: 2,
+                nr2_npe_method: 1,
+                nr2_ae: true,
+                nb_tau: 0.1,
+                nb_hang: 0.5,
+                nb_advance: 0.2,
+                nb_backtau: 50.0,
+                nb_threshold: 8.0,
+                nb2_mode: 4,
+                nb2_tau: 0.1,
+                nb2_hang: 0.5,
+                nb2_advance: 0.2,
+                nb2_backtau: 50.0,
+                nb2_threshold: 8.0,
+            }),
+            // short sharp clicks well above the noise
+            NoisePreset::Ignition => Some(NoiseSettings {
+                nr2_gain_method: 2,
+                nr2_npe_method: 0,
+                nr2_ae: true,
+                nb_tau: 0.05,
+                nb_hang: 0.05,
+                nb_advance: 0.05,
+                nb_backtau: 20.0,
+                nb_threshold: 15.0,
+                nb2_mode: 1,
+                nb2_tau: 0.05,
+                nb2_hang: 0.05,
+                nb2_advance: 0.05,
+                nb2_backtau: 20.0,
+                nb2_threshold: 15.0,
+            }),
+        }
+    }
+}
+
+// NR2 and noise blanker parameters, times are in ms
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct NoiseSettings {
+    pub nr2_gain_method: i32,
+    pub nr2_npe_method: i32,
+    pub nr2_ae: bool,
+    pub nb_tau: f32,
+    pub nb_hang: f32,
+    pub nb_advance: f32,
+    pub nb_backtau: f32,
+    pub nb_threshold: f32,
+    pub nb2_mode: i32,
+    pub nb2_tau: f32,
+    pub nb2_hang: f32,
+    pub nb2_advance: f32,
+    pub nb2_backtau: f32,
+    pub nb2_threshold: f32,
+}
+
+impl Default for NoiseSettings {
+    fn default() -> Self {
+        NoiseSettings {
+            nr2_gain_method: 2, // gamma
+            nr2_npe_method: 0, // OSMS
+            nr2_ae: true,
+            nb_tau: 0.1,
+            nb_hang: 0.1,
+            nb_advance: 0.1,
+            nb_backtau: 50.0,
+            nb_threshold: 20.0,
+            nb2_mode: 0, // zero
+            nb2_tau: 0.1,
+            nb2_hang: 0.1,
+            nb2_advance: 0.1,
+            nb2_backtau: 50.0,
+            nb2_threshold: 20.0,
+        }
+    }
+}
+
+// keep the preset shown in step with the settings, unless a preset is being shown
+fn show_preset(dropdown: &DropDown, updating: &Cell<bool>, settings: &NoiseSettings) {
+    if !updating.get() {
+        dropdown.set_selected(NoisePreset::matching(settings).to_u32());
+    }
+}
+
+// edits the settings for the band the active receiver is on
+pub fn create_noise_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMutex) -> Window {
+
+    let ui_xml = include_str!("../ui/noise.xml");
+    let builder = Builder::from_string(ui_xml);
+
+    let window: Window = builder
+            .object("noise_window")
+            .expect("Could not get object `noise_window` from builder.");
+
+    window.set_transient_for(Some(parent)); // keeps it on top
+
+    let r = radio_mutex.radio.lock().unwrap();
+        let mut rx = 0;
+        if r.receiver[1].active {
+            rx = 1;
+        }
+        let b = r.receiver[rx].band.to_usize();
+        let label = r.receiver[rx].band_info[b].label.clone();
+        let settings = r.receiver[rx].band_info[b].noise;
+    drop(r);
+
+    let band_label: Label = builder
+            .object("noise_band_label")
+            .expect("Could not get object `noise_band_label` from builder.");
+    band_label.set_label(&format!("RX{} {}", rx + 1, label));
+
+    let preset_dropdown: DropDown = builder
+            .object("noise_preset_dropdown")
+            .expect("Could not get object `noise_preset_dropdown` from builder.");
+    preset_dropdown.set_selected(NoisePreset::matching(&settings).to_u32());
+    let updating = Rc::new(Cell::new(false));
+
+    let nr2_gain_dropdown: DropDown = builder
+            .object("noise_nr2_gain_dropdown")
+            .expect("Could not get object `noise_nr2_gain_dropdown` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let preset_dropdown_clone = preset_dropdown.clone();
+    let updating_clone = updating.clone();
+    nr2_gain_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.receiver[rx].band_info[b].noise.nr2_gain_method = dropdown.selected() as i32;
+        r.receiver[rx].set_noise();
+        let noise = r.receiver[rx].band_info[b].noise;
+        drop(r);
+        show_preset(&preset_dropdown_clone, &updating_clone, &noise);
+    });
+
+    let nr2_npe_dropdown: DropDown = builder
+            .object("noise_nr2_npe_dropdown")
+            .expect("Could not get object `noise_nr2_npe_dropdown` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let preset_dropdown_clone = preset_dropdown.clone();
+    let updating_clone = updating.clone();
+    nr2_npe_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.receiver[rx].band_info[b].noise.nr2_npe_method = dropdown.selected() as i32;
+        r.receiver[rx].set_noise();
+        let noise = r.receiver[rx].band_info[b].noise;
+        drop(r);
+        show_preset(&preset_dropdown_clone, &updating_clone, &noise);
+    });
+
+    let nr2_ae_check_button: CheckButton = builder
+            .object("noise_nr2_ae_check_button")
+            .expect("Could not get object `noise_nr2_ae_check_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let preset_dropdown_clone = preset_dropdown.clone();
+    let updating_clone = updating.clone();
+    nr2_ae_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.receiver[rx].band_info[b].noise.nr2_ae = button.is_active();
+        r.receiver[rx].set_noise();
+        let noise = r.receiver[rx].band_info[b].noise;
+        drop(r);
+        show_preset(&preset_dropdown_clone, &updating_clone, &noise);
+    });
+
+    let nb2_mode_dropdown: DropDown = builder
+            .object("noise_nb2_mode_dropdown")
+            .expect("Could not get object `noise_nb2_mode_dropdown` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let preset_dropdown_clone = preset_dropdown.clone();
+    let updating_clone = updating.clone();
+    nb2_mode_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.receiver[rx].band_info[b].noise.nb2_mode = dropdown.selected() as i32;
+        r.receiver[rx].set_noise();
+        let noise = r.receiver[rx].band_info[b].noise;
+        drop(r);
+        show_preset(&preset_dropdown_clone, &updating_clone, &noise);
+    });
+
+    // the blanker times and thresholds, NB in the first column and NB2 in the second
+    let mut adjustments: Vec<Adjustment> = Vec::new();
+    for name in ["tau", "hang", "advance", "backtau", "threshold"] {
+        for blanker in ["nb", "nb2"] {
+            let id = format!("noise_{}_{}_adjustment", blanker, name);
+            let adjustment: Adjustment = builder
+                    .object(id.as_str())
+                    .expect("Could not get object `noise_adjustment` from builder.");
+            let radio_mutex_clone = radio_mutex.clone();
+            let preset_dropdown_clone = preset_dropdown.clone();
+            let updating_clone = updating.clone();
+            adjustment.connect_value_changed(move |adjustment| {
+                let mut r = radio_mutex_clone.radio.lock().unwrap();
+                let value = adjustment.value() as f32;
+                let noise = &mut r.receiver[rx].band_info[b].noise;
+                match (blanker, name) {
+                    ("nb", "tau") => noise.nb_tau = value,
+                    ("nb", "hang") => noise.nb_hang = value,
+                    ("nb", "advance") => noise.nb_advance = value,
+                    ("nb", "backtau") => noise.nb_backtau = value,
+                    ("nb", "threshold") => noise.nb_threshold = value,
+                    ("nb2", "tau") => noise.nb2_tau = value,
+                    ("nb2", "hang") => noise.nb2_hang = value,
+                    ("nb2", "advance") => noise.nb2_advance = value,
+                    ("nb2", "backtau") => noise.nb2_backtau = value,
+                    _ => noise.nb2_threshold = value,
+                }
+                r.receiver[rx].set_noise();
+                let noise = r.receiver[rx].band_info[b].noise;
+                drop(r);
+                show_preset(&preset_dropdown_clone, &updating_clone, &noise);
+            });
+            adjustments.push(adjustment);
+        }
+    }
+
+    let show_settings = move |settings: &NoiseSettings| {
+        nr2_gain_dropdown.set_selected(settings.nr2_gain_method as u32);
+        nr2_npe_dropdown.set_selected(settings.nr2_npe_method as u32);
+        nr2_ae_check_button.set_active(settings.nr2_ae);
+        nb2_mode_dropdown.set_selected(settings.nb2_mode as u32);
+        let values = [
+            settings.nb_tau, settings.nb2_tau,
+            settings.nb_hang, settings.nb2_hang,
+            settings.nb_advance, settings.nb2_advance,
+            settings.nb_backtau, settings.nb2_backtau,
+            settings.nb_threshold, settings.nb2_threshold,
+        ];
+        for (adjustment, value) in adjustments.iter().zip(values.iter()) {
+            adjustment.set_value((*value).into());
+        }
+    };
+    show_settings(&settings);
+
+    let radio_mutex_clone = radio_mutex.clone();
+    preset_dropdown.connect_selected_notify(move |dropdown| {
+        let Some(settings) = NoisePreset::from_u32(dropdown.selected()).and_then(|preset| preset.settings()) else {
+            return;
+        };
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.receiver[rx].band_info[b].noise = settings;
+        r.receiver[rx].set_noise();
+        drop(r);
+        updating.set(true);
+        show_settings(&settings);
+        updating.set(false);
+    });
+
+    window
+}
@@ -17,6 +17,24 @@
 
 use serde::{Deserialize, Serialize};
 
+use std::time::{Duration, Instant};
+
+pub const NOTCH_DEFAULT_WIDTH: f64 = 500.0;
+pub const NOTCH_MIN_WIDTH: f64 = 10.0;
+
+// the most carriers notched automatically, and the narrowest of those notches
+pub const AUTO_NOTCH_MAX: usize = 8;
+pub const AUTO_NOTCH_MIN_WIDTH: f64 = 50.0;
+
+// dB a carrier must be above the noise floor, and above the pixels two either side of it
+const CARRIER_THRESHOLD: f32 = 15.0;
+const CARRIER_NARROW: f32 = 10.0;
+// pixels either side of a carrier used to find the noise floor
+const CARRIER_FLOOR_PIXELS: usize = 32;
+// how long a carrier must be present before it is notched, and missing before the notch is removed
+const CARRIER_PERSIST: Duration = Duration::from_secs(2);
+const CARRIER_RELEASE: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct Notch {
     pub rx: i32,
@@ -29,5 +47,103 @@ impl Notch {
     pub fn new(rx: i32, frequency: f64, width: f64, active: i32) -> Notch {
         Notch { rx, frequency, width, active }
     }
+
+    pub fn is_active(&self) -> bool {
+        self.active != 0
+    }
+
+    pub fn low(&self) -> f64 {
+        self.frequency - (self.width / 2.0)
+    }
+
+    pub fn high(&self) -> f64 {
+        self.frequency + (self.width / 2.0)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+struct Carrier {
+    id: u32,
+    frequency: f64,
+    first_seen: Instant,
+    last_seen: Instant,
 }
 
+// follows the narrow carriers in a receiver spectrum so they can be notched
+#[derive(Debug, Clone, Default)]
+pub struct CarrierTracker {
+    carriers: Vec<Carrier>,
+    next_id: u32,
+}
+
+impl CarrierTracker {
+    pub fn new() -> CarrierTracker {
+        CarrierTracker::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.carriers.clear();
+    }
+
+    // narrow peaks well above the noise floor around them, frequency_low is the frequency of the first pixel
+    pub fn find_carriers(pixels: &Vec<f32>, frequency_low: f64, hz_per_pixel: f64) -> Vec<f64> {
+        let mut carriers: Vec<f64> = Vec::new();
+        for i in 2..pixels.len().saturating_sub(2) {
+            let level = pixels[i];
+            if level < pixels[i - 1] || level <= pixels[i + 1] {
+                continue;
+            }
+            if level - pixels[i - 2] < CARRIER_NARROW || level - pixels[i + 2] < CARRIER_NARROW {
+                continue;
+            }
+            let low = i.saturating_sub(CARRIER_FLOOR_PIXELS);
+            let high = (i + CARRIER_FLOOR_PIXELS + 1).min(pixels.len());
+            let mut window = pixels[low..high].to_vec();
+            window.sort_by(|a, b| a.total_cmp(b));
+            let floor = window[window.len() / 2];
+            if level - floor < CARRIER_THRESHOLD {
+                continue;
+            }
+            // fit a parabola through the peak to get closer than a pixel
+            let (a, b, c) = (pixels[i - 1], level, pixels[i + 1]);
+            let mut offset = 0.0;
+            let denominator = a - (2.0 * b) + c;
+            if denominator != 0.0 {
+                offset = (0.5 * (a - c) / denominator).clamp(-0.5, 0.5);
+            }
+            carriers.push(frequency_low + ((i as f64 + offset as f64) * hz_per_pixel));
+        }
+        carriers
+    }
+
+    // match the carriers found to those being followed, letting them drift by up to tolerance Hz
+    pub fn update(&mut self, found: &Vec<f64>, tolerance: f64, now: Instant) {
+        let mut unmatched = found.clone();
+        for carrier in self.carriers.iter_mut() {
+            let nearest = unmatched.iter().enumerate()
+                .map(|(i, frequency)| (i, (frequency - carrier.frequency).abs()))
+                .filter(|(_, distance)| *distance <= tolerance)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((i, _)) = nearest {
+                let frequency = unmatched.remove(i);
+                carrier.frequency = carrier.frequency + ((frequency - carrier.frequency) / 2.0);
+                carrier.last_seen = now;
+            }
+        }
+        self.carriers.retain(|carrier| now.duration_since(carrier.last_seen) <= CARRIER_RELEASE);
+        for frequency in unmatched {
+            self.carriers.push(Carrier { id: self.next_id, frequency, first_seen: now, last_seen: now });
+            self.next_id = self.next_id.wrapping_add(1);
+        }
+    }
+
+    // the carriers that have been present long enough to notch, oldest first
+    pub fn persistent(&self) -> Vec<(u32, f64)> {
+        let mut carriers: Vec<&Carrier> = self.carriers.iter()
+            .filter(|carrier| carrier.last_seen.duration_since(carrier.first_seen) >= CARRIER_PERSIST)
+            .collect();
+        carriers.sort_by_key(|carrier| carrier.first_seen);
+        carriers.iter().take(AUTO_NOTCH_MAX).map(|carrier| (carrier.id, carrier.frequency)).collect()
+    }
+}
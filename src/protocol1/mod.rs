@@ -233,6 +233,7 @@ impl Protocol1 {
                 }
             2 => {
                 r.transmitter.alex_reverse_power=u16::from_be_bytes([c1,c2]);
+                r.update_swr();
                 }
             3 => {
                 }
@@ -281,6 +282,8 @@ impl Protocol1 {
             mic_samples = mic_samples + 1;
             if mic_samples >= mic_sample_divisor {
                 mic_samples = 0;
+                r.process_vox_sample(mic_sample as f64 / 32768.0);
+                r.process_cw_sample();
                 let x = r.transmitter.microphone_samples * 2;
                 if r.tune {
                     r.transmitter.microphone_buffer[x] = 0.0;
@@ -444,24 +447,6 @@ impl Protocol1 {
         let mut c4: u8 = 0x00;
 
         let r = radio_mutex.radio.lock().unwrap();
-        let mut frequency = r.receiver[0].frequency;
-        if r.receiver[0].ctun {
-            frequency = r.receiver[0].ctun_frequency;
-        }
-        if r.receiver[0].mode == Modes::CWL.to_usize() {
-             frequency = frequency + r.receiver[0].cw_pitch;
-        } else if r.receiver[0].mode == Modes::CWU.to_usize() {
-             frequency = frequency - r.receiver[0].cw_pitch;
-        }
-        let mut frequency_b = r.receiver[1].frequency;
-        if r.receiver[1].ctun {
-            frequency_b = r.receiver[1].ctun_frequency;
-        }
-        if r.receiver[1].mode == Modes::CWL.to_usize() {
-             frequency_b = frequency_b + r.receiver[1].cw_pitch;
-        } else if r.receiver[1].mode == Modes::CWU.to_usize() {
-             frequency_b = frequency_b - r.receiver[1].cw_pitch;
-        }
 
         let attenuation = r.adc[rx as usize].attenuation;
 
@@ -501,10 +486,7 @@ impl Protocol1 {
                 1 => {
                     c0 = 0x02; // C0
                     // TX frequency
-                    let mut f: i32 = frequency as i32;
-                    if r.split {
-                        f = frequency_b as i32;
-                    }
+                    let f = r.tx_nco_frequency() as i32;
                     c1 = (f >> 24) as u8; // C1
                     c2 = (f>>16) as u8; // C2
                     c3 = (f>>8) as u8; // C3
@@ -513,7 +495,7 @@ impl Protocol1 {
                 2 => {
                     c0 = 0x04 + (self.current_receiver * 2); // C0
                     // RX frequency
-                    let f = r.receiver[self.current_receiver as usize].frequency as i32;
+                    let f = r.rx_nco_frequency(self.current_receiver as usize) as i32;
                     c1 = (f >> 24) as u8; // C1
                     c2 = (f>>16) as u8; // C2
                     c3 = (f>>8) as u8; // C3
@@ -527,30 +509,7 @@ impl Protocol1 {
                     c0 = 0x12; // C0
                     c1 = 0x00; // C1 // drive level 0..255
                     if r.is_transmitting() {
-                        let mut b = r.receiver[0].band.to_usize();
-                        if r.split {
-                            b = r.receiver[1].band.to_usize();
-                        }
-
-                        let power = r.transmitter.drive;
-
-                        let mut target_dbm = 10.0 * ((power * 1000.0).log10());
-                        let gbb = r.transmitter.pa_calibration[b];
-                        target_dbm = target_dbm - gbb;
-                        let target_volts = (10.0_f32.powf(target_dbm * 0.1) * 0.05).sqrt();
-                        let volts=(target_volts / 0.8).min(1.0);
-                        let mut actual_volts=volts*(1.0/0.98);
-
-                        if actual_volts<0.0 {
-                          actual_volts=0.0;
-                        } else if actual_volts>1.0 {
-                          actual_volts=1.0;
-                        }
-
-                        let level=(actual_volts*255.0) as u8;
-
-                        //c1 = ((r.transmitter.drive/100.0) * 255.0) as u8;
-                        c1 = level;
+                        c1 = r.drive_level();
                     }
                     c2 = 0x00; // C2
                     if r.mic_boost {
@@ -559,8 +518,8 @@ impl Protocol1 {
                     if self.device.device == 6 { // HERMES_LITE
                         if self.device.version > 42 { // HERMES_LITE_2
                             c2 |= 0x2C; // PA
-                            if r.tune {
-                                c2 |= 0x10;
+                            if r.tune && r.transmitter.tune_atu {
+                                c2 |= 0x10; // request the external ATU to tune
                             }
                         }
                     }
@@ -601,8 +560,15 @@ impl Protocol1 {
                     c0 = 0x16; // C0
                     c1 = 0x00; // C1
                     c2 = 0x00; // C2
-                    c3 = 0x0C; // C3
-                    c4 = 0x1E; // C4
+                    if r.cw_keys_reversed {
+                        c2 |= 0x40;
+                    }
+                    c3 = (r.cw_keyer_speed & 0x3F) as u8; // C3 keyer speed and mode
+                    c3 |= (r.cw_keyer_mode.to_u32() << 6) as u8;
+                    c4 = (r.cw_keyer_weight & 0x7F) as u8; // C4 keyer weight and spacing
+                    if r.cw_keyer_spacing != 0 {
+                        c4 |= 0x80;
+                    }
                      },
                 6 => {
                     c0 = 0x1C; // C0
@@ -613,17 +579,20 @@ impl Protocol1 {
                      },
                 7 => {
                     c0 = 0x1E; // C0
-                    c1 = 0x00; // C1
-                    c2 = 0x14; // C2
-                    c3 = 0x14; // C3
+                    c1 = 0x00; // C1 internal keyer
+                    if r.cw_keyer_internal && !r.tune && !r.keyer.is_sending() && (r.transmitter.mode == Modes::CWL.to_usize() || r.transmitter.mode == Modes::CWU.to_usize()) {
+                        c1 |= 0x01;
+                    }
+                    c2 = (r.cw_keyer_sidetone_volume & 0x7F) as u8; // C2
+                    c3 = (r.cw_keyer_ptt_delay & 0xFF) as u8; // C3
                     c4 = 0x00; // C4
                      },
                 8 => {
                     c0 = 0x20; // C0
-                    c1 = 0x00; // C1
-                    c2 = 0x00; // C2
-                    c3 = 0x28; // C3
-                    c4 = 0x0A; // C4
+                    c1 = ((r.cw_keyer_hang_time >> 2) & 0xFF) as u8; // C1 hang time bits 9-2
+                    c2 = (r.cw_keyer_hang_time & 0x03) as u8; // C2 hang time bits 1-0
+                    c3 = ((r.cw_keyer_sidetone_frequency >> 4) & 0xFF) as u8; // C3 sidetone bits 11-4
+                    c4 = (r.cw_keyer_sidetone_frequency & 0x0F) as u8; // C4 sidetone bits 3-0
                      },
                 9 => {
                     c0 = 0x22; // C0
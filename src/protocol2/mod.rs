@@ -117,8 +117,6 @@ impl Protocol2 {
         let mut buffer = vec![0; 65536];
         let microphone_buffer: Vec<f64> = vec![0.0; (r.transmitter.microphone_buffer_size * 2) as usize];
         let microphone_samples: usize = 0;
-        let microphone_iq_buffer: Vec<f64> = vec![0.0; (r.transmitter.output_samples * 2) as usize];
-        let microphone_iq_buffer_offset: usize = 0;
         let mut tx_iq_buffer: Vec<f64> = vec![0.0; IQ_BUFFER_SIZE*2];
         let mut tx_iq_buffer_offset: usize = 0;
         drop(r);
@@ -143,9 +141,10 @@ impl Protocol2 {
                                     r.dot = ((buffer[4] >> 1) & 0x01) == 0x01;
                                     r.dash = ((buffer[4] >> 2) & 0x01) == 0x01;
 
-                                    r.pll_locked = ((buffer[5] >> 2) & 0x01) == 0x01;
+                                    r.pll_locked = Some(((buffer[4] >> 3) & 0x01) == 0x01);
                                     r.transmitter.alex_forward_power = u16::from_be_bytes([buffer[14], buffer[15]]);
                                     r.transmitter.alex_reverse_power = u16::from_be_bytes([buffer[22], buffer[23]]);
+                                    r.update_swr();
                                     r.supply_volts = u16::from_be_bytes([buffer[49], buffer[50]]) as i32;
 
                                     if r.ptt != previous_ptt || r.dot != previous_dot || r.dash != previous_dash {
@@ -175,11 +174,12 @@ impl Protocol2 {
                                     let mut b = MIC_HEADER_SIZE;
                                     if size >= MIC_HEADER_SIZE + data_size {
                                         drop(r);
+                                        // 16 bit signed big endian samples scaled to -1.0..1.0
                                         for _i in 0..MIC_SAMPLES {
                                             if buffer[b] & 0x80 != 0 {
-                                                sample = u32::from_be_bytes([0xFF, 0xFF, buffer[b], buffer[b+1]]) as f64;
+                                                sample = u32::from_be_bytes([0xFF, 0xFF, buffer[b], buffer[b+1]]) as i32 as f64 / 32768.0;
                                             } else {
-                                                sample = u32::from_be_bytes([0, 0, buffer[b], buffer[b+1]]) as f64;
+                                                sample = u32::from_be_bytes([0, 0, buffer[b], buffer[b+1]]) as i32 as f64 / 32768.0;
                                             }
                                             b = b + 2;
                                             iq_buffer = self.microphone_sample(sample, radio_mutex);
@@ -187,12 +187,13 @@ impl Protocol2 {
                                         r = radio_mutex.radio.lock().unwrap();
                                     }
                                 }
+                                // send the IQ the transmitter channel has just produced
                                 if r.is_transmitting()  && iq_buffer {
                                     for j in 0..r.transmitter.output_samples {
                                         let ix = j * 2;
                                         let ox = tx_iq_buffer_offset * 2;
-                                        tx_iq_buffer[ox] = microphone_iq_buffer[ix as usize] as f64;
-                                        tx_iq_buffer[ox+1] = microphone_iq_buffer[(ix+1) as usize] as f64;
+                                        tx_iq_buffer[ox] = r.transmitter.iq_buffer[ix as usize];
+                                        tx_iq_buffer[ox+1] = r.transmitter.iq_buffer[(ix+1) as usize];
                                         tx_iq_buffer_offset = tx_iq_buffer_offset + 1;
                                         if tx_iq_buffer_offset >= IQ_BUFFER_SIZE {
                                             self.send_iq_buffer(tx_iq_buffer.clone());
@@ -353,6 +354,8 @@ impl Protocol2 {
     fn microphone_sample(&self, sample: f64, radio_mutex: &RadioMutex) -> bool{
         let mut processed = false;
         let mut r = radio_mutex.radio.lock().unwrap();
+        r.process_vox_sample(sample);
+        r.process_cw_sample();
         let x = r.transmitter.microphone_samples * 2;
         r.transmitter.microphone_buffer[x] = sample;
         r.transmitter.microphone_buffer[x+1] = 0.0;
@@ -414,12 +417,7 @@ impl Protocol2 {
         let mut f = 0.0;
         for i in 0..r.receivers {
             // convert frequency to phase
-            f = r.receiver[i as usize].frequency;
-            if r.receiver[i as usize].mode == Modes::CWL.to_usize() {
-                 f = f + r.receiver[i as usize].cw_pitch;
-            } else if r.receiver[i as usize].mode == Modes::CWU.to_usize() {
-                 f = f - r.receiver[i as usize].cw_pitch;
-            }
+            f = r.rx_nco_frequency(i as usize);
 
             phase = ((4294967296.0*f)/122880000.0) as u32;
             buf[(9+(i*4)) as usize] = ((phase>>24) & 0xFF) as u8;
@@ -430,27 +428,7 @@ impl Protocol2 {
         }
 
         // transmit frequency
-        if r.split {
-            f = r.receiver[1].frequency;
-            if r.receiver[1].ctun {
-                f = r.receiver[1].ctun_frequency;
-            }
-            if r.receiver[1].mode == Modes::CWL.to_usize() {
-                 f = f + r.receiver[1].cw_pitch;
-            } else if r.receiver[1].mode == Modes::CWU.to_usize() {
-                 f = f - r.receiver[1].cw_pitch;
-            }
-        } else {
-            f = r.receiver[0].frequency;
-            if r.receiver[0].ctun {
-                f = r.receiver[0].ctun_frequency;
-            }
-            if r.receiver[0].mode == Modes::CWL.to_usize() {
-                 f = f + r.receiver[0].cw_pitch;
-            } else if r.receiver[0].mode == Modes::CWU.to_usize() {
-                 f = f - r.receiver[0].cw_pitch;
-            }
-        }
+        f = r.tx_nco_frequency();
         phase = ((4294967296.0*f)/122880000.0) as u32;
         buf[329] = ((phase>>24) & 0xFF) as u8;
         buf[330] = ((phase>>16) & 0xFF) as u8;
@@ -458,14 +436,11 @@ impl Protocol2 {
         buf[332] = (phase & 0xFF) as u8;
 
         // transmit power
-        let mut power = 0.0;
+        let mut power = 0;
         if r.is_transmitting() {
-            power = r.transmitter.drive * 255.0 / 100.0;
-            if power > 255.0 {
-                power = 255.0;
-            }
+            power = r.drive_level();
         }
-        buf[345] = power as u8;
+        buf[345] = power;
 
         let mut filter: u32 = 0x00000000;
         //filter |= r.adc[r.receiver[0].adc].rx_antenna;
@@ -662,7 +637,7 @@ impl Protocol2 {
         buf[4] = 1; // DACs
 
         buf[5] = 0;
-        if tx.mode == Modes::CWL.to_usize()  || tx.mode == Modes::CWU.to_usize() {
+        if r.cw_keyer_internal && !r.keyer.is_sending() && (tx.mode == Modes::CWL.to_usize()  || tx.mode == Modes::CWU.to_usize()) {
             buf[5] |= 0x02;
         }
         if r.cw_keys_reversed {
@@ -692,6 +667,7 @@ impl Protocol2 {
         buf[10] = r.cw_keyer_weight as u8;
         buf[11] = ((r.cw_keyer_hang_time >> 8) & 0xFF) as u8;
         buf[12] = (r.cw_keyer_hang_time &0xFF) as u8;
+        buf[13] = (r.cw_keyer_ptt_delay & 0xFF) as u8;
 
         buf[50] = 0x00;
         if r.line_in {
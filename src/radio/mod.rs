@@ -31,6 +31,7 @@ use std::os::raw::c_int;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 
@@ -38,12 +39,48 @@ use serde::{Deserialize, Serialize};
 use crate::discovery::{Boards,Device};
 use crate::modes::Modes;
 use crate::receiver::Receiver;
-use crate::transmitter::Transmitter;
+use crate::transmitter::{SwrProtection, Transmitter};
 use crate::wdsp::*;
 use crate::audio::*;
 use crate::alex::*;
 use crate::adc::*;
 use crate::notches::*;
+use crate::midi::*;
+use crate::dxcluster::*;
+use crate::bandplan::*;
+use crate::memories::*;
+use crate::scanner::*;
+use crate::keyer::*;
+use crate::txprofiles::*;
+use crate::txtest::*;
+
+// the VOX level falls by about 20dB in 100ms
+const VOX_LEVEL_DECAY: f32 = 0.99952;
+
+// the SWR is only measured above this fraction of the maximum power
+const SWR_MIN_POWER: f32 = 0.01;
+// smoothing for each new SWR reading
+const SWR_ALPHA: f32 = 0.5;
+
+// why the radio is refusing to transmit
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TxLockout {
+    Inhibited,
+    PllUnlocked,
+    NotLicensed,
+    TimedOut,
+}
+
+impl TxLockout {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TxLockout::Inhibited => "TX Inhibited",
+            TxLockout::PllUnlocked => "PLL Unlocked",
+            TxLockout::NotLicensed => "Not Licensed",
+            TxLockout::TimedOut => "TX Timed Out",
+        }
+    }
+}
 
 #[derive(PartialEq, Serialize, Deserialize, Copy, Clone, Debug)]
 pub enum RadioModels {
@@ -157,8 +194,31 @@ pub struct Radio {
     pub mox: bool,
 #[serde(skip_serializing, skip_deserializing)]
     pub vox: bool,
+    pub vox_enabled: bool,
+    pub vox_threshold: f32,
+    pub vox_hang: i32,
+    pub anti_vox: bool,
+    pub anti_vox_gain: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub vox_level: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    vox_hang_count: i32,
 #[serde(skip_serializing, skip_deserializing)]
     pub tune: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    tune_started: Option<Instant>,
+#[serde(skip_serializing, skip_deserializing)]
+    pub pa_calibration_level: Option<u8>,
+    // seconds, 0 to transmit until stopped
+    pub tx_timeout: u32,
+#[serde(skip_serializing, skip_deserializing)]
+    tx_started: Option<Instant>,
+#[serde(skip_serializing, skip_deserializing)]
+    tx_timed_out: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    pub tx_inhibit: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    out_of_band: bool,
 #[serde(skip_serializing, skip_deserializing)]
     pub dot: bool,
 #[serde(skip_serializing, skip_deserializing)]
@@ -193,8 +253,9 @@ pub struct Radio {
     pub keepalive: bool,
 #[serde(skip_serializing, skip_deserializing)]
     pub received: bool,
+// None until the radio has reported the PLL state
 #[serde(skip_serializing, skip_deserializing)]
-    pub pll_locked: bool,
+    pub pll_locked: Option<bool>,
 #[serde(skip_serializing, skip_deserializing)]
     pub adc_overload: bool,
 #[serde(skip_serializing, skip_deserializing)]
@@ -202,6 +263,13 @@ pub struct Radio {
 
 #[serde(skip_serializing, skip_deserializing)]
     pub swr: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub forward_watts: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub reflected_watts: f32,
+    // latched by the SWR protection until the operator clears it
+#[serde(skip_serializing, skip_deserializing)]
+    pub swr_alarm: bool,
 
     pub line_in: bool,
     pub mic_boost: bool,
@@ -222,8 +290,18 @@ pub struct Radio {
 #[serde(skip_serializing, skip_deserializing)]
     pub meter_2_timeout_id: Option<SourceId>,
 
-    pub notch: i32,
-    pub notches: Vec<Notch>,
+
+    pub midi: Midi,
+
+    pub dxcluster: DxCluster,
+
+    pub band_plan: BandPlan,
+
+    pub memories: Memories,
+    pub scanner: Scanner,
+    pub keyer: CwKeyer,
+    pub tx_profiles: TxProfiles,
+    pub tx_test: TxTest,
 }
 
 #[derive(Clone)]
@@ -297,7 +375,21 @@ impl Radio {
         let ptt = false;
         let mox = false;
         let vox = false;
+        let vox_enabled = false;
+        let vox_threshold = -40.0;
+        let vox_hang = 500;
+        let anti_vox = false;
+        let anti_vox_gain = 0.0;
+        let vox_level = 0.0;
+        let vox_hang_count = 0;
         let tune = false;
+        let tune_started = None;
+        let pa_calibration_level = None;
+        let tx_timeout = 180;
+        let tx_started = None;
+        let tx_timed_out = false;
+        let tx_inhibit = false;
+        let out_of_band = false;
         let dot = false;
         let dash = false;
         let mut audio: Vec<Audio> = Vec::new();
@@ -342,10 +434,13 @@ impl Radio {
         let keepalive = false;
         let received = false;
 
-        let pll_locked = false;
+        let pll_locked = None;
         let adc_overload = false;
         let supply_volts = 0;
         let swr = 1.0;
+        let forward_watts = 0.0;
+        let reflected_watts = 0.0;
+        let swr_alarm = false;
 
         let line_in = false;
         let mic_boost = true;
@@ -362,8 +457,16 @@ impl Radio {
         let meter_1_timeout_id = None;
         let meter_2_timeout_id = None;
 
-        let notch = 0;
-        let notches = vec![];
+
+        let midi = Midi::new();
+        let dxcluster = DxCluster::new();
+        let band_plan = BandPlan::new();
+        let memories = Memories::new();
+        let scanner = Scanner::new();
+        let keyer = CwKeyer::new();
+        let tx_profiles = TxProfiles::new();
+        let mut tx_test = TxTest::new();
+        tx_test.power_cap = transmitter.max_power * 0.1;
 
         Radio {
             name,
@@ -382,7 +485,21 @@ impl Radio {
             ptt,
             mox,
             vox,
+            vox_enabled,
+            vox_threshold,
+            vox_hang,
+            anti_vox,
+            anti_vox_gain,
+            vox_level,
+            vox_hang_count,
             tune,
+            tune_started,
+            pa_calibration_level,
+            tx_timeout,
+            tx_started,
+            tx_timed_out,
+            tx_inhibit,
+            out_of_band,
             dot,
             dash,
             audio,
@@ -416,6 +533,9 @@ impl Radio {
             supply_volts,
 
             swr,
+            forward_watts,
+            reflected_watts,
+            swr_alarm,
             line_in,
             mic_boost,
             mic_ptt,
@@ -429,8 +549,14 @@ impl Radio {
             waterfall_timeout_id,
             meter_1_timeout_id,
             meter_2_timeout_id,
-            notch,
-            notches,
+            midi,
+            dxcluster,
+            band_plan,
+            memories,
+            scanner,
+            keyer,
+            tx_profiles,
+            tx_test,
         }
     }
 
@@ -439,27 +565,490 @@ impl Radio {
         self.ptt = false;
         self.mox = false;
         self.vox = false;
+        self.vox_level = 0.0;
+        self.vox_hang_count = 0;
         self.tune = false;
+        self.tune_started = None;
+        self.pa_calibration_level = None;
+        self.tx_started = None;
+        self.tx_timed_out = false;
+        self.tx_inhibit = false;
+        self.out_of_band = false;
         self.dot = false;
         self.dash = false;
         self.updated = false;
 
-        self.pll_locked = false;
+        self.pll_locked = None;
         self.adc_overload = false;
         self.supply_volts = 0;
 
         self.swr = 1.0;
+        self.forward_watts = 0.0;
+        self.reflected_watts = 0.0;
+        self.swr_alarm = false;
     }
 
     pub fn is_transmitting(&self) -> bool {
-        let cw = (self.dot | self.dash) && (self.receiver[0].mode == Modes::CWL.to_usize() || self.receiver[0].mode == Modes::CWU.to_usize());
+        if self.tx_lockout().is_some() {
+            return false;
+        }
+        if self.swr_alarm && self.transmitter.swr_protection == SwrProtection::DropTx {
+            return false;
+        }
+        self.tx_requested()
+    }
+
+    // true when something is asking to transmit, whether or not it is allowed to
+    pub fn tx_requested(&self) -> bool {
+        let cw_mode = self.receiver[0].mode == Modes::CWL.to_usize() || self.receiver[0].mode == Modes::CWU.to_usize();
+        let cw = if self.keyer.is_sending() {
+            cw_mode
+        } else if self.cw_keyer_internal {
+            (self.dot | self.dash) && cw_mode
+        } else {
+            self.cw_breakin && cw_mode && self.keyer.is_keyed()
+        };
+
+        self.mox | self.ptt | cw | self.vox | self.tune | self.tx_test.running | self.pa_calibration_level.is_some()
+    }
+
+    pub fn tx_lockout(&self) -> Option<TxLockout> {
+        if self.tx_inhibit {
+            Some(TxLockout::Inhibited)
+        } else if self.protocol == 2 && self.pll_locked == Some(false) {
+            // only protocol 2 reports the PLL state
+            Some(TxLockout::PllUnlocked)
+        } else if self.out_of_band {
+            Some(TxLockout::NotLicensed)
+        } else if self.tx_timed_out {
+            Some(TxLockout::TimedOut)
+        } else {
+            None
+        }
+    }
+
+    // the receiver whose VFO the transmitter uses, VFO B when split
+    pub fn tx_vfo(&self) -> usize {
+        if self.split {
+            1
+        } else {
+            0
+        }
+    }
+
+    // the frequency the transmitter is on
+    pub fn tx_frequency(&self) -> f32 {
+        let tx = self.tx_vfo();
+        let mut frequency = self.receiver[tx].frequency;
+        if self.receiver[tx].ctun {
+            frequency = self.receiver[tx].ctun_frequency;
+        }
+        frequency + self.receiver[tx].xit_shift() + self.transmitter.duplex_offset()
+    }
+
+    // the lowest and highest frequencies of the transmitted signal
+    pub fn tx_edges(&self) -> (f32, f32) {
+        let frequency = self.tx_frequency();
+        let mode = self.receiver[self.tx_vfo()].mode;
+        if mode == Modes::CWL.to_usize() || mode == Modes::CWU.to_usize() {
+            return (frequency, frequency);
+        }
+        let low = self.transmitter.filter_low.min(self.transmitter.filter_high);
+        let high = self.transmitter.filter_low.max(self.transmitter.filter_high);
+        (frequency + low, frequency + high)
+    }
+
+    // the receive frequency sent to the radio, RIT is applied in the receiver
+    pub fn rx_nco_frequency(&self, rx: usize) -> f32 {
+        self.receiver[rx].nco_frequency()
+    }
+
+    // the transmit frequency sent to the radio
+    pub fn tx_nco_frequency(&self) -> f32 {
+        let tx = self.tx_vfo();
+        let mut frequency = self.tx_frequency();
+        if self.receiver[tx].mode == Modes::CWL.to_usize() {
+            frequency = frequency + self.receiver[tx].cw_pitch;
+        } else if self.receiver[tx].mode == Modes::CWU.to_usize() {
+            frequency = frequency - self.receiver[tx].cw_pitch;
+        }
+        frequency
+    }
+
+    // put VFO B above VFO A in the same mode and transmit on it
+    pub fn split_up(&mut self, offset: f32) {
+        let mut frequency = self.receiver[0].frequency;
+        if self.receiver[0].ctun {
+            frequency = self.receiver[0].ctun_frequency;
+        }
+        self.receiver[1].frequency = frequency + offset;
+        self.receiver[1].band = self.receiver[0].band;
+        self.receiver[1].mode = self.receiver[0].mode;
+        self.receiver[1].filter = self.receiver[0].filter;
+        self.receiver[1].filter_low = self.receiver[0].filter_low;
+        self.receiver[1].filter_high = self.receiver[0].filter_high;
+        self.receiver[1].set_mode();
+        self.split = true;
+        self.update_tx_vfo();
+        self.updated = true;
+    }
+
+    // the transmitter follows the mode and filter of the VFO it is on
+    pub fn update_tx_vfo(&mut self) {
+        let tx = self.tx_vfo();
+        let mode = self.receiver[tx].mode;
+        let (low, high) = self.tx_vfo_filter();
+        let mode_changed = mode != self.transmitter.mode;
+        if mode_changed {
+            self.transmitter.mode = mode;
+            self.transmitter.set_mode();
+        }
+        // a TX profile sets its own transmit filter
+        if mode_changed || self.tx_profiles.current.is_empty() {
+            self.transmitter.filter_low = low;
+            self.transmitter.filter_high = high;
+            self.transmitter.set_filter();
+        }
+        if mode_changed {
+            self.tx_profile_mode_changed();
+        }
+    }
+
+    // the transmit filter when it follows the receive filter of the VFO
+    fn tx_vfo_filter(&self) -> (f32, f32) {
+        let tx = self.tx_vfo();
+        let mode = self.receiver[tx].mode;
+        let pitch = self.receiver[tx].cw_pitch;
+        let mut low = self.receiver[tx].filter_low;
+        let mut high = self.receiver[tx].filter_high;
+        if mode == Modes::CWL.to_usize() {
+            low = -(self.receiver[tx].filter_low + pitch);
+            high = self.receiver[tx].filter_high + pitch;
+        } else if mode == Modes::CWU.to_usize() {
+            low = pitch - self.receiver[tx].filter_low;
+            high = self.receiver[tx].filter_high - pitch;
+        }
+        (low, high)
+    }
+
+    // called every 100ms to run the transmit timeout and check the transmit frequency
+    pub fn update_tx_timer(&mut self) {
+        let was_transmitting = self.is_transmitting();
+        let (low, high) = self.tx_edges();
+        self.out_of_band = !self.band_plan.may_transmit(low, high);
+
+        // tuning has its own timeout
+        if self.tx_requested() && !self.tune {
+            let started = *self.tx_started.get_or_insert_with(Instant::now);
+            if self.tx_timeout > 0 && started.elapsed() >= Duration::from_secs(self.tx_timeout as u64) {
+                // stays timed out until everything asking to transmit lets go
+                self.tx_timed_out = true;
+                self.mox = false;
+                self.vox = false;
+                if self.tx_test.running {
+                    self.stop_tx_test();
+                }
+            }
+        } else {
+            self.tx_started = None;
+            self.tx_timed_out = false;
+        }
+
+        if self.is_transmitting() != was_transmitting {
+            self.updated = true;
+            self.set_state();
+        }
+    }
+
+    // seconds left before the transmit timeout
+    pub fn tx_remaining(&self) -> Option<u64> {
+        match self.tx_started {
+            Some(started) if self.tx_timeout > 0 => Some((self.tx_timeout as u64).saturating_sub(started.elapsed().as_secs())),
+            _ => None,
+        }
+    }
+
+    // seconds left before the tune timeout
+    pub fn tune_remaining(&self) -> Option<u64> {
+        match self.tune_started {
+            Some(started) if self.transmitter.tune_timeout > 0 => Some((self.transmitter.tune_timeout as u64).saturating_sub(started.elapsed().as_secs())),
+            _ => None,
+        }
+    }
+
+    // called when new forward and reflected power readings arrive from the radio
+    pub fn update_swr(&mut self) {
+        if !self.is_transmitting() {
+            return;
+        }
+        let fwd = self.transmitter.forward_power();
+        let rev = self.transmitter.reverse_power();
+        self.forward_watts = fwd;
+        self.reflected_watts = rev;
+        if fwd < self.transmitter.max_power * SWR_MIN_POWER {
+            return;
+        }
+        let gamma = (rev / fwd).sqrt();
+        let swr = if gamma < 1.0 {
+            (1.0 + gamma) / (1.0 - gamma)
+        } else {
+            99.9
+        };
+        self.swr = (SWR_ALPHA * swr) + ((1.0 - SWR_ALPHA) * self.swr);
+
+        let band = self.tx_band();
+        if self.transmitter.swr_protection != SwrProtection::Off && !self.swr_alarm
+                && (self.swr > self.transmitter.swr_limit[band] || rev > self.transmitter.reflected_limit[band]) {
+            self.trip_swr_alarm();
+        }
+    }
+
+    fn trip_swr_alarm(&mut self) {
+        self.swr_alarm = true;
+        if self.transmitter.swr_protection == SwrProtection::DropTx {
+            self.mox = false;
+            self.vox = false;
+            if self.tune {
+                self.set_tuning(false);
+            }
+            if self.tx_test.running {
+                self.stop_tx_test();
+            }
+            if self.pa_calibration_level.is_some() {
+                self.stop_pa_calibration();
+            }
+            self.set_state();
+        }
+        self.updated = true;
+    }
 
-        self.mox | self.ptt | cw | self.vox | self.tune
+    pub fn clear_swr_alarm(&mut self) {
+        self.swr_alarm = false;
+        self.swr = 1.0;
+        self.updated = true;
+        self.set_state();
     }
 
     pub fn run(&self) {
     }
 
+    // voice operated transmit, called for each microphone sample at 48000 per second
+    pub fn process_vox_sample(&mut self, sample: f64) {
+        let level = sample.abs() as f32;
+        if level > self.vox_level {
+            self.vox_level = level;
+        } else {
+            self.vox_level *= VOX_LEVEL_DECAY;
+        }
+
+        let cw_mode = self.transmitter.mode == Modes::CWL.to_usize() || self.transmitter.mode == Modes::CWU.to_usize();
+        let mut vox = false;
+        if self.vox_enabled && !cw_mode && !self.tune && !self.tx_test.running {
+            let mut triggered = self.vox_level > 10.0_f32.powf(self.vox_threshold / 20.0);
+            if triggered && self.anti_vox && !self.vox {
+                // ignore the microphone picking up the receiver audio from the speaker
+                let mut rx_level = self.receiver[0].audio_level;
+                if self.rx2_enabled {
+                    rx_level = rx_level.max(self.receiver[1].audio_level);
+                }
+                triggered = self.vox_level > rx_level * 10.0_f32.powf(self.anti_vox_gain / 20.0);
+            }
+            if triggered {
+                self.vox_hang_count = self.vox_hang * 48;
+            } else if self.vox_hang_count > 0 {
+                self.vox_hang_count -= 1;
+            }
+            vox = self.vox_hang_count > 0;
+        } else {
+            self.vox_hang_count = 0;
+        }
+
+        if vox != self.vox {
+            self.vox = vox;
+            self.set_state();
+            self.updated = true;
+        }
+    }
+
+    // run the software keyer for one microphone sample when the radio's internal keyer is not used
+    // or when sending CW text
+    pub fn process_cw_sample(&mut self) {
+        let cw_mode = self.transmitter.mode == Modes::CWL.to_usize() || self.transmitter.mode == Modes::CWU.to_usize();
+        if !cw_mode && self.keyer.is_sending() {
+            self.keyer.abort_text();
+            self.set_state();
+            self.updated = true;
+        }
+        self.transmitter.cw_keyer = (!self.cw_keyer_internal || self.keyer.is_sending()) && cw_mode && !self.tune;
+        if !self.transmitter.cw_keyer {
+            return;
+        }
+
+        let mut tx = 0;
+        if self.split {
+            tx = 1;
+        }
+        self.transmitter.cw_pitch = self.receiver[tx].cw_pitch;
+        if self.receiver[tx].mode == Modes::CWL.to_usize() {
+            self.transmitter.cw_pitch = -self.receiver[tx].cw_pitch;
+        }
+
+        let (mut dot, mut dash) = self.keyer.paddles(self.dot, self.dash);
+        if self.cw_keys_reversed {
+            std::mem::swap(&mut dot, &mut dash);
+        }
+        let was_transmitting = self.is_transmitting();
+        let was_sending = self.keyer.is_sending();
+        self.keyer.configure(self.cw_keyer_mode, self.cw_keyer_speed, self.cw_keyer_weight, self.cw_keyer_spacing != 0, self.cw_keyer_ptt_delay, self.cw_keyer_hang_time);
+        let envelope = self.keyer.process(dot, dash);
+        let transmitting = self.is_transmitting();
+        if transmitting != was_transmitting {
+            self.set_state();
+        }
+        if self.keyer.is_sending() != was_sending {
+            // the radio's internal keyer is turned off while sending text
+            self.updated = true;
+        }
+
+        let x = self.transmitter.microphone_samples;
+        if transmitting {
+            self.transmitter.cw_envelope[x] = envelope;
+        } else {
+            self.transmitter.cw_envelope[x] = 0.0;
+        }
+
+        // local sidetone, the receiver audio is muted while transmitting
+        if transmitting && self.audio[0].local_output {
+            if let Some(buffer) = self.keyer.sidetone(self.cw_keyer_sidetone_frequency, self.cw_keyer_sidetone_volume) {
+                let _ = self.audio[0].write_output(&buffer);
+            }
+        }
+    }
+
+    // the band the transmitter is on
+    pub fn tx_band(&self) -> usize {
+        self.receiver[self.tx_vfo()].band.to_usize()
+    }
+
+    pub fn set_tuning(&mut self, state: bool) {
+        self.tune = state;
+        if state {
+            self.tune_started = Some(Instant::now());
+        } else {
+            self.tune_started = None;
+        }
+        self.transmitter.set_tuning(state, self.transmitter.tune_pulsed, self.cw_keyer_sidetone_frequency);
+    }
+
+    // true when tuning has run for longer than the tune timeout
+    pub fn tune_expired(&self) -> bool {
+        match self.tune_started {
+            Some(started) => self.transmitter.tune_timeout > 0 && started.elapsed() >= Duration::from_secs(self.transmitter.tune_timeout as u64),
+            None => false,
+        }
+    }
+
+    // the drive level sent to the radio, reduced while tuning, transmitting a test signal or after an SWR alarm
+    pub fn tx_drive(&self) -> f32 {
+        let drive = if self.tune {
+            self.transmitter.tune_drive[self.tx_band()]
+        } else if self.tx_test.running {
+            self.transmitter.drive.min(self.tx_test.power_cap)
+        } else {
+            self.transmitter.drive
+        };
+        // tune drive and the test power cap are in watts, never ask for more than the radio can give
+        let drive = drive.min(self.transmitter.max_power);
+        if self.swr_alarm && self.transmitter.swr_protection == SwrProtection::ReduceDrive {
+            drive.min(self.transmitter.swr_reduced_drive)
+        } else {
+            drive
+        }
+    }
+
+    // returns false if already transmitting
+    pub fn start_tx_test(&mut self) -> bool {
+        if self.is_transmitting() || self.tx_lockout().is_some() {
+            return false;
+        }
+        self.tx_test.start();
+        self.transmitter.test_signal = true;
+        self.transmitter.set_test_signal(&self.tx_test, true);
+        self.updated = true;
+        self.set_state();
+        true
+    }
+
+    pub fn stop_tx_test(&mut self) {
+        self.tx_test.stop();
+        self.transmitter.test_signal = false;
+        self.transmitter.set_test_signal(&self.tx_test, false);
+        self.updated = true;
+        self.set_state();
+    }
+
+    // the drive byte sent to the radio
+    pub fn drive_level(&self) -> u8 {
+        if let Some(level) = self.pa_calibration_level {
+            return level;
+        }
+        Transmitter::drive_level(self.tx_drive(), self.transmitter.pa_calibration[self.tx_band()])
+    }
+
+    // key a steady carrier at a fixed drive byte to measure the PA gain, returns false if already transmitting
+    pub fn start_pa_calibration(&mut self, level: u8) -> bool {
+        if self.is_transmitting() || self.tx_lockout().is_some() {
+            return false;
+        }
+        self.pa_calibration_level = Some(level);
+        self.transmitter.set_tuning(true, false, self.cw_keyer_sidetone_frequency);
+        self.updated = true;
+        self.set_state();
+        true
+    }
+
+    pub fn stop_pa_calibration(&mut self) {
+        self.pa_calibration_level = None;
+        self.transmitter.set_tuning(false, false, self.cw_keyer_sidetone_frequency);
+        self.updated = true;
+        self.set_state();
+    }
+
+    // use the named TX profile, an empty name lets the transmit filter follow the receiver
+    pub fn select_tx_profile(&mut self, name: &str) {
+        let previous = self.tx_profiles.current.clone();
+        if previous != name {
+            self.tx_profiles.current = String::from(name);
+            self.tx_profiles.changed = true;
+        }
+        if let Some(profile) = self.tx_profiles.find(name).cloned() {
+            if previous.is_empty() {
+                self.tx_profiles.baseline = Some(TxProfile::from_transmitter(&self.transmitter, ""));
+            }
+            profile.apply(&mut self.transmitter);
+            self.tx_profiles.changed = true;
+        } else if !previous.is_empty() {
+            // no profile, go back to the settings from before and let the filter follow the receiver
+            if let Some(baseline) = self.tx_profiles.baseline.take() {
+                baseline.apply_processing(&mut self.transmitter);
+            }
+            let (low, high) = self.tx_vfo_filter();
+            self.transmitter.filter_low = low;
+            self.transmitter.filter_high = high;
+            self.transmitter.set_filter();
+            self.tx_profiles.changed = true;
+        }
+    }
+
+    // switch to the default TX profile for the new transmit mode, or to none
+    pub fn tx_profile_mode_changed(&mut self) {
+        let mut name = String::new();
+        if let Some(mode_profile) = self.tx_profiles.mode_profiles.get(self.transmitter.mode) {
+            name = mode_profile.clone();
+        }
+        self.select_tx_profile(&name);
+    }
+
     pub fn update_spectrum(&mut self, width: i32) -> (c_int, Vec<f32>) {
         let mut zoom = self.receiver[0].zoom;
         let mut channel = self.receiver[0].channel;
@@ -846,18 +1435,6 @@ pub fn draw_spectrum(radio_mutex: &RadioMutex, cr: &Context, width: i32, height:
         }
     }
 
-    pub fn add_notch_to_vector(&mut self, notch: Notch) {
-        self.notches.push(notch);
-    }
-
-    pub fn add_notch(&mut self, notch: Notch) {
-        unsafe {
-            let res = RXANBPAddNotch (notch.rx, self.notch, notch.frequency, notch.width, notch.active);
-        }
-        eprintln!("add notch {} at {} for {} active={}", self.notch, notch.frequency, notch.width, notch.active);
-        self.notch = self.notch + 1;
-    }
-
     // only called when radio is running protocol 1
     pub fn sample_rate_changed(&mut self, rate: i32) {
         self.sample_rate = rate;
@@ -20,16 +20,21 @@ use serde::{Deserialize, Serialize};
 use std::cmp::{max, min};
 use std::ffi::CString;
 use std::os::raw::{c_char, c_int};
+use std::time::Instant;
 
 use crate::agc::AGC;
-use crate::bands::{Bands, BandInfo};
+use crate::bands::{Bands, BandInfo, BandStack};
 use crate::filters::Filters;
 use crate::modes::Modes;
+use crate::morse::MorseDecoder;
+use crate::notches::{CarrierTracker, Notch, AUTO_NOTCH_MIN_WIDTH};
+use crate::repeater::CtcssDecoder;
 use crate::wdsp::*;
 
 const DEFAULT_SAMPLE_RATE: i32 = 384000; // 1536000;// 768000; // 384000;
 const DEFAULT_SPECTRUM_AVERAGE_TIME: f32 = 250.0;
 const DEFAULT_WATERFALL_AVERAGE_TIME: f32 = 10.0;
+const SQUELCH_SILENCE: f64 = 1.0e-6;
 const SUBRX_BASE_CHANNEL: i32 = 16;
 
 #[derive(PartialEq, Serialize, Deserialize, Copy, Clone, Debug)]
@@ -77,6 +82,11 @@ pub struct Receiver {
     pub step: f32,
     pub ctun:  bool,
     pub ctun_frequency: f32,
+    // receive and transmit offsets from the dial frequency
+    pub rit: bool,
+    pub rit_offset: f32,
+    pub xit: bool,
+    pub xit_offset: f32,
     pub nr: bool,
     pub nr_taps: i32,
     pub nr_delay: i32,
@@ -91,6 +101,7 @@ pub struct Receiver {
     pub anf_gain: f32,
     pub anf_leak: f32,
     pub snb: bool,
+    pub auto_notch: bool,
 
     pub agc_position: i32,
 
@@ -111,6 +122,28 @@ pub struct Receiver {
     pub filter_high: f32,
     pub mode: usize,
     pub filter: usize,
+    // a second WDSP channel on the same IQ, heard in the right ear
+    pub subrx: bool,
+    pub subrx_frequency: f32,
+    pub subrx_mode: usize,
+    pub subrx_filter: usize,
+    pub subrx_filter_low: f32,
+    pub subrx_filter_high: f32,
+    pub subrx_balance: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub subrx_shift: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub subrx_audio_buffer: Vec<f64>,
+#[serde(skip_serializing, skip_deserializing)]
+    pub notch_band: Option<Bands>,
+#[serde(skip_serializing, skip_deserializing)]
+    pub auto_notches: Vec<(u32, Notch)>,
+#[serde(skip_serializing, skip_deserializing)]
+    pub carrier_tracker: CarrierTracker,
+#[serde(skip_serializing, skip_deserializing)]
+    pub notch_tune_frequency: f64,
+#[serde(skip_serializing, skip_deserializing)]
+    pub notch_shift: f64,
 #[serde(skip_serializing, skip_deserializing)]
     pub iq_input_buffer: Vec<f64>,
     pub samples: usize,
@@ -129,6 +162,19 @@ pub struct Receiver {
     pub rxgain: i32,
     pub cw_pitch: f32,
     pub cw_decoder: bool,
+    pub cw_decoder_threshold: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub morse_decoder: MorseDecoder,
+    pub squelch: bool,
+    pub squelch_level: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub squelch_open: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    pub audio_level: f32,
+    pub ctcss: bool,
+    pub ctcss_frequency: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub ctcss_decoder: CtcssDecoder,
 #[serde(skip_serializing, skip_deserializing)]
     pub cw_decoder_audio_buffer_offset: usize,
 #[serde(skip_serializing, skip_deserializing)]
@@ -168,6 +214,10 @@ impl Receiver {
         let step: f32 = 1000.0; // 1KHz
         let ctun: bool = false;
         let ctun_frequency: f32 = 0.0;
+        let rit = false;
+        let rit_offset: f32 = 0.0;
+        let xit = false;
+        let xit_offset: f32 = 0.0;
         let nr: bool = false;
         let nr_taps: i32 = 64;
         let nr_delay: i32 = 16;
@@ -182,6 +232,7 @@ impl Receiver {
         let anf_gain: f32 = 100.0;
         let anf_leak: f32 = 100.0;
         let snb: bool = false;
+        let auto_notch: bool = false;
         let agc_position: i32 = 0;
         let spectrum_fps = 80.0;
         let spectrum_width: i32 = pixels;
@@ -200,11 +251,25 @@ impl Receiver {
         let filter_high: f32 = 2700.0;
         let mode = Modes::USB.to_usize();
         let filter = Filters::F6.to_usize(); // 2.4k
+        let subrx: bool = false;
+        let subrx_frequency: f32 = frequency;
+        let subrx_mode = mode;
+        let subrx_filter = filter;
+        let subrx_filter_low: f32 = filter_low;
+        let subrx_filter_high: f32 = filter_high;
+        let subrx_balance: f32 = 0.5;
+        let subrx_shift: f32 = 0.0;
+        let notch_band: Option<Bands> = None;
+        let auto_notches: Vec<(u32, Notch)> = Vec::new();
+        let carrier_tracker = CarrierTracker::new();
+        let notch_tune_frequency: f64 = 0.0;
+        let notch_shift: f64 = 0.0;
         let iq_input_buffer = vec![0.0; (buffer_size * 2) as usize];
         let samples: usize = 0;
         let local_output: bool = false;
         let audio_output: AudioOutput = AudioOutput::Stereo;
         let audio_buffer = vec![0.0; (output_samples * 2) as usize];
+        let subrx_audio_buffer = vec![0.0; (output_samples * 2) as usize];
         let local_audio_buffer_size: usize = 2048;
         let local_audio_buffer = vec![0i16; local_audio_buffer_size*2];
         let local_audio_buffer_offset: usize = 0;
@@ -214,6 +279,15 @@ impl Receiver {
         let rxgain: i32 = 0;
         let cw_pitch: f32 = 400.0;
         let cw_decoder: bool =  false;
+        let cw_decoder_threshold: f32 = 50.0;
+        let morse_decoder = MorseDecoder::new((output_rate/4) as f32, 20.0);
+        let squelch: bool = false;
+        let squelch_level: f32 = 0.0;
+        let squelch_open: bool = true;
+        let audio_level: f32 = 0.0;
+        let ctcss: bool = false;
+        let ctcss_frequency: f32 = 88.5;
+        let ctcss_decoder = CtcssDecoder::default();
         let cw_decoder_audio_buffer_offset: usize =0;
         let cw_decoder_audio_buffer = vec![0.0f32; local_audio_buffer_size];
         let equalizer_enabled: bool = true;
@@ -246,6 +320,10 @@ impl Receiver {
                             step,
                             ctun,
                             ctun_frequency,
+                            rit,
+                            rit_offset,
+                            xit,
+                            xit_offset,
                             nr,
                             nr_taps,
                             nr_delay,
@@ -260,6 +338,7 @@ impl Receiver {
                             anf_gain,
                             anf_leak,
                             snb,
+                            auto_notch,
                             agc_position,
                             spectrum_fps,
                             spectrum_width,
@@ -278,6 +357,20 @@ impl Receiver {
                             filter_high,
                             mode,
                             filter,
+                            subrx,
+                            subrx_frequency,
+                            subrx_mode,
+                            subrx_filter,
+                            subrx_filter_low,
+                            subrx_filter_high,
+                            subrx_balance,
+                            subrx_shift,
+                            subrx_audio_buffer,
+                            notch_band,
+                            auto_notches,
+                            carrier_tracker,
+                            notch_tune_frequency,
+                            notch_shift,
                             iq_input_buffer,
                             samples,
                             local_output,
@@ -292,6 +385,15 @@ impl Receiver {
                             rxgain,
                             cw_pitch,
                             cw_decoder,
+                            cw_decoder_threshold,
+                            morse_decoder,
+                            squelch,
+                            squelch_level,
+                            squelch_open,
+                            audio_level,
+                            ctcss,
+                            ctcss_frequency,
+                            ctcss_decoder,
                             cw_decoder_audio_buffer_offset,
                             cw_decoder_audio_buffer,
                             equalizer_enabled,
@@ -311,17 +413,21 @@ impl Receiver {
         self.iq_input_buffer = vec![0.0; (self.buffer_size * 2) as usize];
         self.samples = 0;
         self.audio_buffer = vec![0.0; (self.output_samples * 2) as usize];
+        self.subrx_audio_buffer = vec![0.0; (self.output_samples * 2) as usize];
+        self.notch_band = None;
         self.local_audio_buffer = vec![0i16; self.local_audio_buffer_size*2];
         self.local_audio_buffer_offset = 0;
         self.remote_audio_buffer = vec![0u8; self.remote_audio_buffer_size];
         self.remote_audio_buffer_offset = 4;
         self.cw_decoder_audio_buffer_offset = 0;
         self.cw_decoder_audio_buffer = vec![0.0f32; self.local_audio_buffer_size];
-        //self.morsedecoder = MorseDecoder::new((self.output_rate/4) as f32, 20.0);
+        self.morse_decoder = MorseDecoder::new((self.output_rate/4) as f32, 20.0);
 
         self.init_wdsp(self.channel);
         self.create_display(self.channel);
         self.init_analyzer(self.channel);
+        self.init_subrx();
+        self.set_noise();
 
         self.enable_equalizer();
 
@@ -340,6 +446,9 @@ impl Receiver {
         if self.snb {
             self.set_snb();
         }
+        self.squelch_open = true;
+        self.set_squelch();
+        self.ctcss_decoder.reset();
 
     }
 
@@ -370,18 +479,15 @@ impl Receiver {
 
             SetEXTANBSamplerate (channel, self.sample_rate);
             SetEXTNOBSamplerate (channel, self.sample_rate);
-            SetEXTANBRun(channel, 0); //self.nb);
-            SetEXTNOBRun(channel, self.nb.into()); //self.nb2);
+            SetEXTANBRun(channel, self.nb.into());
+            SetEXTNOBRun(channel, self.nb2.into());
 
             SetRXAEMNRPosition(channel, self.agc_position.into());
-            SetRXAEMNRgainMethod(channel, 2); //self.nr2_gain_method);
-            SetRXAEMNRnpeMethod(channel, 0); //self.nr2_npe_method);
-            SetRXAEMNRRun(channel, self.nr.into()); //self.nr2);
-            SetRXAEMNRaeRun(channel, 1); //self.nr2_ae);
+            SetRXAEMNRRun(channel, self.nr2.into());
 
             SetRXAANRPosition(channel, self.agc_position.into());
             SetRXAANRVals(channel, self.nr_taps, self.nr_delay, 1e-6 * self.nr_gain as f64, 1e-3 * self.nr_leak as f64);
-            SetRXAANRRun(channel, 0); //self.nr);
+            SetRXAANRRun(channel, self.nr.into());
 
             SetRXAANFPosition(channel, self.agc_position.into());
             SetRXAANFVals(channel, self.anf_taps, self.anf_delay, 1e-6 * self.anf_gain as f64, 1e-3 * self.anf_leak as f64);
@@ -395,29 +501,68 @@ impl Receiver {
                 RXASetPassband(channel,self.filter_low.into(),self.filter_high.into());
             }
 
-            if self.ctun {
-                let mut offset = self.ctun_frequency - self.frequency;
-                if self.mode == Modes::CWL.to_usize() {
-                     offset = offset + self.cw_pitch;
-                } else if self.mode == Modes::CWU.to_usize() {
-                     offset = offset - self.cw_pitch;
-                }
+            if self.ctun || self.rit {
+                let offset = self.shift_offset();
                 SetRXAShiftRun(channel, 1);
                 SetRXAShiftFreq(channel, offset.into());
-                RXANBPSetShiftFrequency(channel, 0.0);
             }
         }
     }
 
+    // the sub-RX has no display of its own, it is drawn on this receivers panadapter
+    fn init_subrx(&mut self) {
+        let channel = self.subrx_channel();
+        self.subrx_shift = self.subrx_offset();
+        unsafe {
+            OpenChannel(channel, self.buffer_size as i32, self.fft_size, self.sample_rate, self.dsp_rate, self.output_rate, 0, 1, 0.010, 0.025, 0.0, 0.010, 0);
+            RXASetNC(channel, self.fft_size);
+            RXASetMP(channel, 0); // low_latency
+
+            SetRXAPanelGain1(channel, self.afgain.into());
+            AGC::set_agc(&self, channel);
+            SetRXAAGCTop(channel, self.agcgain.into());
+            SetRXAPanelSelect(channel, 3);
+            SetRXAPanelPan(channel, 1.0);
+            SetRXAPanelCopy(channel, 0);
+            SetRXAPanelBinaural(channel, 0);
+            SetRXAPanelRun(channel, 1);
+            SetRXAEQRun(channel, 0);
+
+            // the noise blankers work on the IQ shared with the main receiver
+            SetRXAEMNRPosition(channel, self.agc_position.into());
+            SetRXAEMNRRun(channel, self.nr2.into());
+
+            SetRXAANRPosition(channel, self.agc_position.into());
+            SetRXAANRVals(channel, self.nr_taps, self.nr_delay, 1e-6 * self.nr_gain as f64, 1e-3 * self.nr_leak as f64);
+            SetRXAANRRun(channel, self.nr.into());
+
+            SetRXAANFPosition(channel, self.agc_position.into());
+            SetRXAANFVals(channel, self.anf_taps, self.anf_delay, 1e-6 * self.anf_gain as f64, 1e-3 * self.anf_leak as f64);
+            SetRXAANFRun(channel, self.anf.into());
+            SetRXASNBARun(channel, self.snb.into());
+
+            SetRXAMode(channel, self.subrx_mode as i32);
+            RXASetPassband(channel, self.subrx_filter_low.into(), self.subrx_filter_high.into());
+
+            SetRXAShiftRun(channel, 1);
+            SetRXAShiftFreq(channel, self.subrx_shift.into());
+        }
+        if self.subrx {
+            self.set_subrx();
+        }
+    }
+
     pub fn update_Nrvals(&self) {
         unsafe {
             SetRXAANRVals(self.channel, self.nr_taps, self.nr_delay, 1e-6 * self.nr_gain as f64, 1e-3 * self.nr_leak as f64);
+            SetRXAANRVals(self.subrx_channel(), self.nr_taps, self.nr_delay, 1e-6 * self.nr_gain as f64, 1e-3 * self.nr_leak as f64);
         }
     }
 
     pub fn update_Anfvals(&self) {
         unsafe {
             SetRXAANFVals(self.channel, self.anf_taps, self.anf_delay, 1e-6 * self.anf_gain as f64, 1e-3 * self.anf_leak as f64);
+            SetRXAANFVals(self.subrx_channel(), self.anf_taps, self.anf_delay, 1e-6 * self.anf_gain as f64, 1e-3 * self.anf_leak as f64);
         }
     }
 
@@ -426,6 +571,9 @@ impl Receiver {
             SetRXAEMNRPosition(self.channel, self.agc_position.into());
             SetRXAANRPosition(self.channel, self.agc_position.into());
             SetRXAANFPosition(self.channel, self.agc_position.into());
+            SetRXAEMNRPosition(self.subrx_channel(), self.agc_position.into());
+            SetRXAANRPosition(self.subrx_channel(), self.agc_position.into());
+            SetRXAANFPosition(self.subrx_channel(), self.agc_position.into());
         }
     }
 
@@ -488,23 +636,80 @@ impl Receiver {
             SetRXAMode(self.channel, self.mode as i32);
         }
         self.set_filter();
+        self.set_squelch();
     }
 
-    pub fn set_ctun_frequency(&self) {
-        let mut offset = self.ctun_frequency - self.frequency;
+    // the current state as a band stacking register
+    pub fn band_stack(&self) -> BandStack {
+        let b = self.band.to_usize();
+        BandStack {
+            frequency: self.frequency,
+            mode: Modes::from_usize(self.mode).unwrap_or(self.band_info[b].mode),
+            filter: Filters::from_usize(self.filter).unwrap_or(self.band_info[b].filter),
+            ctun: self.ctun,
+            ctun_frequency: self.ctun_frequency,
+            zoom: self.zoom,
+            pan: self.pan,
+            spectrum_low: self.band_info[b].spectrum_low,
+            spectrum_high: self.band_info[b].spectrum_high,
+            waterfall_low: self.band_info[b].waterfall_low,
+            waterfall_high: self.band_info[b].waterfall_high,
+        }
+    }
+
+    // the receive offset when RIT is on
+    pub fn rit_shift(&self) -> f32 {
+        if self.rit {
+            self.rit_offset
+        } else {
+            0.0
+        }
+    }
+
+    // the transmit offset when XIT is on
+    pub fn xit_shift(&self) -> f32 {
+        if self.xit {
+            self.xit_offset
+        } else {
+            0.0
+        }
+    }
+
+    // the receiver shift for CTUN plus any RIT offset
+    fn shift_offset(&self) -> f32 {
+        let mut offset = 0.0;
+        if self.ctun {
+            offset = self.ctun_frequency - self.frequency;
+            if self.mode == Modes::CWL.to_usize() {
+                 offset = offset + self.cw_pitch;
+            } else if self.mode == Modes::CWU.to_usize() {
+                 offset = offset - self.cw_pitch;
+            }
+        }
+        offset + self.rit_shift()
+    }
+
+    // the frequency the DDC is tuned to, CW is offset by the pitch
+    pub fn nco_frequency(&self) -> f32 {
+        let mut frequency = self.frequency;
         if self.mode == Modes::CWL.to_usize() {
-             offset = offset + self.cw_pitch;
+            frequency = frequency + self.cw_pitch;
         } else if self.mode == Modes::CWU.to_usize() {
-             offset = offset - self.cw_pitch;
+            frequency = frequency - self.cw_pitch;
         }
+        frequency
+    }
+
+    pub fn set_ctun_frequency(&self) {
+        let offset = self.shift_offset();
         unsafe {
             SetRXAShiftFreq(self.channel, offset.into());
-            RXANBPSetShiftFrequency(self.channel, offset.into());
         }
     }
 
     pub fn set_ctun(&self, state: bool) {
-        if state {
+        // RIT uses the same shift
+        if state || self.rit {
             unsafe {
                 SetRXAShiftRun(self.channel, 1);
                 self.set_ctun_frequency();
@@ -516,13 +721,281 @@ impl Receiver {
         }
     }
 
+    pub fn set_rit(&self) {
+        self.set_ctun(self.ctun);
+    }
+
+    // the notches for the current band, at absolute frequencies
+    pub fn notches(&self) -> &Vec<Notch> {
+        &self.band_info[self.band.to_usize()].notches
+    }
+
+    // replace the notches in WDSP with those stored for the current band
+    fn load_notches(&mut self) {
+        let mut count: c_int = 0;
+        unsafe {
+            RXANBPGetNumNotches(self.channel, &mut count);
+            for _ in 0..count {
+                RXANBPDeleteNotch(self.channel, 0);
+            }
+        }
+        for (i, notch) in self.notches().iter().enumerate() {
+            unsafe {
+                RXANBPAddNotch(self.channel, i as i32, notch.frequency, notch.width, notch.active);
+            }
+        }
+        unsafe {
+            RXANBPSetNotchesRun(self.channel, 1);
+        }
+        self.auto_notches.clear();
+        self.carrier_tracker.clear();
+        self.notch_band = Some(self.band);
+    }
+
+    pub fn add_notch(&mut self, frequency: f64, width: f64) {
+        let b = self.band.to_usize();
+        let notch = Notch::new(self.channel, frequency, width, 1);
+        let index = self.band_info[b].notches.len();
+        self.band_info[b].notches.push(notch);
+        unsafe {
+            RXANBPAddNotch(self.channel, index as i32, notch.frequency, notch.width, notch.active);
+        }
+    }
+
+    pub fn edit_notch(&mut self, index: usize, frequency: f64, width: f64) {
+        let b = self.band.to_usize();
+        let notch = &mut self.band_info[b].notches[index];
+        notch.frequency = frequency;
+        notch.width = width;
+        unsafe {
+            RXANBPEditNotch(self.channel, index as i32, notch.frequency, notch.width, notch.active);
+        }
+    }
+
+    pub fn set_notch_active(&mut self, index: usize, active: bool) {
+        let b = self.band.to_usize();
+        let notch = &mut self.band_info[b].notches[index];
+        notch.active = active as i32;
+        unsafe {
+            RXANBPEditNotch(self.channel, index as i32, notch.frequency, notch.width, notch.active);
+        }
+    }
+
+    pub fn delete_notch(&mut self, index: usize) {
+        let b = self.band.to_usize();
+        self.band_info[b].notches.remove(index);
+        unsafe {
+            RXANBPDeleteNotch(self.channel, index as i32);
+        }
+    }
+
+    pub fn set_auto_notch(&mut self) {
+        if !self.auto_notch {
+            self.clear_auto_notches();
+        }
+    }
+
+    // the carrier notches follow the band's notches in WDSP
+    fn clear_auto_notches(&mut self) {
+        // a band change reloads all the notches anyway
+        if self.notch_band == Some(self.band) {
+            let base = self.notches().len();
+            for j in (0..self.auto_notches.len()).rev() {
+                unsafe {
+                    RXANBPDeleteNotch(self.channel, (base + j) as i32);
+                }
+            }
+        }
+        self.auto_notches.clear();
+        self.carrier_tracker.clear();
+    }
+
+    // notch the persistent carriers in a spectrum, frequency_low is the frequency of the first pixel
+    pub fn update_auto_notches(&mut self, pixels: &Vec<f32>, frequency_low: f64, hz_per_pixel: f64) {
+        if !self.auto_notch || self.notch_band != Some(self.band) {
+            return;
+        }
+
+        // only carriers in the passband can be heard, leave alone a CW signal being listened to and the carrier of an AM or FM station
+        let mut frequency = self.frequency;
+        if self.ctun {
+            frequency = self.ctun_frequency;
+        }
+        if self.mode == Modes::CWL.to_usize() {
+            frequency = frequency + self.cw_pitch;
+        } else if self.mode == Modes::CWU.to_usize() {
+            frequency = frequency - self.cw_pitch;
+        }
+        let frequency = (frequency + self.rit_shift()) as f64;
+        let tolerance = (hz_per_pixel * 2.0).max(20.0);
+        let mut excluded: Option<(f64, f64)> = None;
+        if self.mode == Modes::CWL.to_usize() || self.mode == Modes::CWU.to_usize() {
+            excluded = Some((frequency + self.filter_low as f64, frequency + self.filter_high as f64));
+        } else if self.mode == Modes::AM.to_usize() || self.mode == Modes::SAM.to_usize() || self.mode == Modes::FMN.to_usize() {
+            excluded = Some((frequency - tolerance, frequency + tolerance));
+        }
+        let passband_low = frequency + self.filter_low.min(self.filter_high) as f64;
+        let passband_high = frequency + self.filter_low.max(self.filter_high) as f64;
+        let mut found = CarrierTracker::find_carriers(pixels, frequency_low, hz_per_pixel);
+        found.retain(|f| *f >= passband_low && *f <= passband_high);
+        if let Some((low, high)) = excluded {
+            found.retain(|f| *f < low || *f > high);
+        }
+        self.carrier_tracker.update(&found, tolerance, Instant::now());
+        let carriers = self.carrier_tracker.persistent();
+
+        let base = self.notches().len();
+        let width = hz_per_pixel.max(AUTO_NOTCH_MIN_WIDTH);
+        // remove the notches for carriers that have gone
+        for j in (0..self.auto_notches.len()).rev() {
+            let id = self.auto_notches[j].0;
+            if !carriers.iter().any(|(carrier_id, _)| *carrier_id == id) {
+                self.auto_notches.remove(j);
+                unsafe {
+                    RXANBPDeleteNotch(self.channel, (base + j) as i32);
+                }
+            }
+        }
+        // follow the carriers that are drifting and notch the new ones
+        for (id, carrier_frequency) in carriers {
+            match self.auto_notches.iter().position(|(notch_id, _)| *notch_id == id) {
+                Some(j) => {
+                    let notch = &mut self.auto_notches[j].1;
+                    if (notch.frequency - carrier_frequency).abs() >= 1.0 || notch.width != width {
+                        notch.frequency = carrier_frequency;
+                        notch.width = width;
+                        unsafe {
+                            RXANBPEditNotch(self.channel, (base + j) as i32, notch.frequency, notch.width, notch.active);
+                        }
+                    }
+                }
+                None => {
+                    let notch = Notch::new(self.channel, carrier_frequency, width, 1);
+                    let index = base + self.auto_notches.len();
+                    self.auto_notches.push((id, notch));
+                    unsafe {
+                        RXANBPAddNotch(self.channel, index as i32, notch.frequency, notch.width, notch.active);
+                    }
+                }
+            }
+        }
+    }
+
+    // WDSP places the notches relative to the DDC and shift, follow the VFO, CTUN and RIT
+    fn update_notch_tracking(&mut self) {
+        if self.notch_band != Some(self.band) {
+            self.load_notches();
+        }
+        let tune_frequency = self.nco_frequency() as f64;
+        if tune_frequency != self.notch_tune_frequency {
+            self.notch_tune_frequency = tune_frequency;
+            unsafe {
+                RXANBPSetTuneFrequency(self.channel, tune_frequency);
+            }
+        }
+        let mut shift = 0.0;
+        if self.ctun || self.rit {
+            shift = self.shift_offset() as f64;
+        }
+        if shift != self.notch_shift {
+            self.notch_shift = shift;
+            unsafe {
+                RXANBPSetShiftFrequency(self.channel, shift);
+            }
+        }
+    }
+
+    pub fn subrx_channel(&self) -> i32 {
+        SUBRX_BASE_CHANNEL + self.channel
+    }
+
+    // the shift from the DDC centre to the sub-RX frequency
+    fn subrx_offset(&self) -> f32 {
+        let mut offset = self.subrx_frequency - self.frequency;
+        if self.mode == Modes::CWL.to_usize() {
+            offset = offset - self.cw_pitch;
+        } else if self.mode == Modes::CWU.to_usize() {
+            offset = offset + self.cw_pitch;
+        }
+        if self.subrx_mode == Modes::CWL.to_usize() {
+            offset = offset + self.cw_pitch;
+        } else if self.subrx_mode == Modes::CWU.to_usize() {
+            offset = offset - self.cw_pitch;
+        }
+        offset
+    }
+
+    // main audio goes to the left ear and the sub-RX to the right
+    pub fn set_subrx(&self) {
+        if self.subrx {
+            unsafe {
+                SetRXAPanelPan(self.channel, 0.0);
+            }
+        } else {
+            self.set_afpan();
+        }
+    }
+
+    pub fn set_subrx_frequency(&mut self, frequency: f32) {
+        self.subrx_frequency = frequency;
+        self.update_subrx_shift();
+    }
+
+    // called when the sub-RX or the main receiver has been retuned,
+    // the sub-RX is pulled back inside the panadapter if the main VFO moves away
+    pub fn update_subrx_shift(&mut self) {
+        let half = (self.sample_rate / 2) as f32;
+        self.subrx_frequency = self.subrx_frequency.clamp(self.frequency - half, self.frequency + half);
+        let offset = self.subrx_offset();
+        if offset != self.subrx_shift {
+            self.subrx_shift = offset;
+            unsafe {
+                SetRXAShiftFreq(self.subrx_channel(), offset.into());
+            }
+        }
+    }
+
+    pub fn set_subrx_mode(&mut self) {
+        unsafe {
+            SetRXAMode(self.subrx_channel(), self.subrx_mode as i32);
+        }
+        self.set_channel_squelch(self.subrx_channel(), self.subrx_mode);
+        self.set_subrx_filter();
+        self.update_subrx_shift();
+    }
+
+    // CW filters are centred on the pitch as for the main receiver
+    pub fn set_subrx_passband(&mut self, low: f32, high: f32) {
+        self.subrx_filter_low = low;
+        self.subrx_filter_high = high;
+        if self.subrx_mode == Modes::CWL.to_usize() {
+            self.subrx_filter_low = -self.cw_pitch - low;
+            self.subrx_filter_high = -self.cw_pitch + high;
+        } else if self.subrx_mode == Modes::CWU.to_usize() {
+            self.subrx_filter_low = self.cw_pitch - low;
+            self.subrx_filter_high = self.cw_pitch + high;
+        }
+        self.set_subrx_filter();
+    }
+
+    pub fn set_subrx_filter(&self) {
+        unsafe {
+            RXASetPassband(self.subrx_channel(), self.subrx_filter_low.into(), self.subrx_filter_high.into());
+        }
+    }
+
     pub fn set_afgain(&self) {
         unsafe {
             SetRXAPanelGain1(self.channel, self.afgain.into());
+            SetRXAPanelGain1(self.subrx_channel(), self.afgain.into());
         }
     }
 
     pub fn set_afpan(&self) {
+        // the sub-RX balance replaces the pan while it is on
+        if self.subrx {
+            return;
+        }
         unsafe {
             SetRXAPanelPan(self.channel, self.afpan.into());
         }
@@ -531,18 +1004,21 @@ impl Receiver {
     pub fn set_agcgain(&self) {
         unsafe {
             SetRXAAGCTop(self.channel, self.agcgain.into());
+            SetRXAAGCTop(self.subrx_channel(), self.agcgain.into());
         }
     }
 
     pub fn set_nr(&self) {
         unsafe {
             SetRXAANRRun(self.channel, self.nr as i32);
+            SetRXAANRRun(self.subrx_channel(), self.nr as i32);
         }  
     }
 
     pub fn set_nr2(&self) {
         unsafe {
             SetRXAEMNRRun(self.channel, self.nr2 as i32);
+            SetRXAEMNRRun(self.subrx_channel(), self.nr2 as i32);
         }  
     }
 
@@ -554,19 +1030,75 @@ impl Receiver {
 
     pub fn set_nb2(&self) {
         unsafe {
-            SetEXTNOBRun(self.channel, self.nb as i32);
+            SetEXTNOBRun(self.channel, self.nb2 as i32);
+        }
+    }
+
+    // NR2 and blanker parameters for the current band
+    pub fn set_noise(&self) {
+        let noise = &self.band_info[self.band.to_usize()].noise;
+        unsafe {
+            SetRXAEMNRgainMethod(self.channel, noise.nr2_gain_method);
+            SetRXAEMNRnpeMethod(self.channel, noise.nr2_npe_method);
+            SetRXAEMNRaeRun(self.channel, noise.nr2_ae as i32);
+            SetRXAEMNRgainMethod(self.subrx_channel(), noise.nr2_gain_method);
+            SetRXAEMNRnpeMethod(self.subrx_channel(), noise.nr2_npe_method);
+            SetRXAEMNRaeRun(self.subrx_channel(), noise.nr2_ae as i32);
+
+            SetEXTANBTau(self.channel, 0.001 * noise.nb_tau as f64);
+            SetEXTANBHangtime(self.channel, 0.001 * noise.nb_hang as f64);
+            SetEXTANBAdvtime(self.channel, 0.001 * noise.nb_advance as f64);
+            SetEXTANBBacktau(self.channel, 0.001 * noise.nb_backtau as f64);
+            SetEXTANBThreshold(self.channel, noise.nb_threshold as f64);
+
+            SetEXTNOBMode(self.channel, noise.nb2_mode);
+            SetEXTNOBTau(self.channel, 0.001 * noise.nb2_tau as f64);
+            SetEXTNOBHangtime(self.channel, 0.001 * noise.nb2_hang as f64);
+            SetEXTNOBAdvtime(self.channel, 0.001 * noise.nb2_advance as f64);
+            SetEXTNOBBacktau(self.channel, 0.001 * noise.nb2_backtau as f64);
+            SetEXTNOBThreshold(self.channel, noise.nb2_threshold as f64);
         }
     }
 
+    // FM uses the noise squelch, all other modes the level squelch
+    pub fn set_squelch(&self) {
+        self.set_channel_squelch(self.channel, self.mode);
+        self.set_channel_squelch(self.subrx_channel(), self.subrx_mode);
+    }
+
+    fn set_channel_squelch(&self, channel: i32, mode: usize) {
+        unsafe {
+            if mode == Modes::FMN.to_usize() {
+                SetRXAAMSQRun(channel, 0);
+                SetRXAFMSQThreshold(channel, 10.0_f64.powf(-2.0 * self.squelch_level as f64 / 100.0));
+                SetRXAFMSQRun(channel, self.squelch as i32);
+            } else {
+                SetRXAFMSQRun(channel, 0);
+                SetRXAAMSQThreshold(channel, ((self.squelch_level as f64 / 100.0) * 160.0) - 160.0);
+                SetRXAAMSQRun(channel, self.squelch as i32);
+            }
+        }
+    }
+
+    // restore the squelch saved for the current band
+    pub fn select_band_squelch(&mut self) {
+        let b = self.band.to_usize();
+        self.squelch = self.band_info[b].squelch;
+        self.squelch_level = self.band_info[b].squelch_level;
+        self.set_squelch();
+    }
+
     pub fn set_anf(&self) {
         unsafe {
             SetRXAANFRun(self.channel, self.anf as i32);
+            SetRXAANFRun(self.subrx_channel(), self.anf as i32);
         }
     }
 
     pub fn set_snb(&self) {
         unsafe {
             SetRXASNBARun(self.channel, self.snb as i32);
+            SetRXASNBARun(self.subrx_channel(), self.snb as i32);
         }
     }
 
@@ -594,6 +1126,8 @@ impl Receiver {
 
     pub fn process_iq_samples(&mut self) {
 
+        self.update_notch_tracking();
+
         let raw_ptr: *mut f64 = self.iq_input_buffer.as_mut_ptr() as *mut f64;
         let audio_ptr: *mut f64 = self.audio_buffer.as_mut_ptr() as *mut f64;
         if self.nb {
@@ -612,14 +1146,67 @@ impl Receiver {
             fexchange0(self.channel, raw_ptr, audio_ptr, &mut result);
             Spectrum0(1, self.channel, 0, 0, raw_ptr);
         }
+
+        // tone squelch
+        if self.ctcss && self.mode == Modes::FMN.to_usize() {
+            let mut offset = 0.0;
+            if self.ctun {
+                offset = self.ctun_frequency - self.frequency;
+            }
+            offset += self.rit_shift();
+            if !self.ctcss_decoder.process(&self.iq_input_buffer, self.buffer_size, self.sample_rate, offset, self.ctcss_frequency) {
+                self.audio_buffer.fill(0.0);
+            }
+        }
+
+        if self.subrx {
+            let sub_ptr: *mut f64 = self.subrx_audio_buffer.as_mut_ptr() as *mut f64;
+            self.update_subrx_shift();
+            unsafe {
+                fexchange0(self.subrx_channel(), raw_ptr, sub_ptr, &mut result);
+            }
+        }
+
+        // a closed squelch outputs silence
+        self.squelch_open = !self.squelch || self.audio_buffer.iter().any(|sample| sample.abs() > SQUELCH_SILENCE);
+
+        // mix in the sub-RX, the balance fades out the opposite ear
+        if self.subrx {
+            let main_gain = (2.0 * (1.0 - self.subrx_balance as f64)).min(1.0);
+            let subrx_gain = (2.0 * self.subrx_balance as f64).min(1.0);
+            for (sample, subrx_sample) in self.audio_buffer.iter_mut().zip(self.subrx_audio_buffer.iter()) {
+                *sample = (*sample * main_gain) + (*subrx_sample * subrx_gain);
+            }
+        }
+
+        // peak audio level for anti-VOX
+        self.audio_level = self.audio_buffer.iter().fold(0.0, |peak: f64, sample| peak.max(sample.abs())) as f32;
+
+        // the CW decoder works on the left channel decimated to a quarter of the output rate
+        if self.cw_decoder && (self.mode == Modes::CWL.to_usize() || self.mode == Modes::CWU.to_usize()) {
+            for i in (0..self.output_samples).step_by(4) {
+                let mut sample = 0.0;
+                for j in i..min(i + 4, self.output_samples) {
+                    sample += self.audio_buffer[j*2];
+                }
+                self.cw_decoder_audio_buffer[self.cw_decoder_audio_buffer_offset] = (sample / 4.0) as f32;
+                self.cw_decoder_audio_buffer_offset += 1;
+                if self.cw_decoder_audio_buffer_offset >= self.cw_decoder_audio_buffer.len() {
+                    self.cw_decoder_audio_buffer_offset = 0;
+                    self.morse_decoder.process(&self.cw_decoder_audio_buffer, self.cw_pitch, self.cw_decoder_threshold / 100.0);
+                }
+            }
+        }
     }
 
     pub fn sample_rate_changed(&mut self, rate: i32) {
         self.sample_rate = rate;
         self.output_samples = self.buffer_size/(self.sample_rate/48000) as usize;
         self.audio_buffer = vec![0.0; (self.output_samples * 2) as usize];
+        self.subrx_audio_buffer = vec![0.0; (self.output_samples * 2) as usize];
         unsafe {
             SetChannelState(self.channel, 0, 1);
+            SetChannelState(self.subrx_channel(), 0, 1);
         }
         self.init_analyzer(self.channel);
         unsafe {
@@ -627,6 +1214,8 @@ impl Receiver {
             SetEXTANBSamplerate(self.channel, rate);
             SetEXTNOBSamplerate(self.channel, rate);
             SetChannelState(self.channel, 1, 0);
+            SetInputSamplerate(self.subrx_channel(), rate);
+            SetChannelState(self.subrx_channel(), 1, 0);
         }
         self.sample_rate_changed = false;
     }
@@ -0,0 +1,376 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use glib::ControlFlow::{Break, Continue};
+use glib::timeout_add_local;
+use gtk::prelude::*;
+use gtk::{Adjustment, ApplicationWindow, Builder, Button, CheckButton, DropDown, Label, StringList, Window};
+use serde::{Deserialize, Serialize};
+
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use crate::bandplan::Region;
+use crate::bands::Bands;
+use crate::radio::RadioMutex;
+use crate::util::*;
+
+// the standard EIA CTCSS tones
+pub const CTCSS_TONES: [f32; 50] = [
+    67.0, 69.3, 71.9, 74.4, 77.0, 79.7, 82.5, 85.4, 88.5, 91.5,
+    94.8, 97.4, 100.0, 103.5, 107.2, 110.9, 114.8, 118.8, 123.0, 127.3,
+    131.8, 136.5, 141.3, 146.2, 150.0, 151.4, 156.7, 159.8, 162.2, 165.5,
+    167.9, 171.3, 173.8, 177.3, 179.9, 183.5, 186.2, 189.9, 192.8, 196.6,
+    199.5, 203.5, 206.5, 210.7, 218.1, 225.7, 229.1, 233.6, 241.8, 250.3,
+];
+
+const DISCRIMINATOR_RATE: i32 = 24000; // rate the FM discriminator runs at, wide enough for 5 kHz deviation
+const CHANNEL_CUTOFF: f64 = 8000.0; // Hz, the FM channel either side of the carrier
+const CHANNEL_TAPS: usize = 12; // channel filter taps for each sample decimated
+const TONE_CUTOFF: f64 = 300.0; // Hz, above the highest CTCSS tone
+const DECODER_RATE: i32 = 8000;  // rate the tone detector runs at
+const DECODER_BLOCK: usize = 4000; // 0.5 second, about 2 Hz resolution
+const DECODER_THRESHOLD: f64 = 0.02; // fraction of the sub audible energy in the tone
+
+pub fn ctcss_index(frequency: f32) -> usize {
+    let mut index = 0;
+    for (i, tone) in CTCSS_TONES.iter().enumerate() {
+        if (tone - frequency).abs() < (CTCSS_TONES[index] - frequency).abs() {
+            index = i;
+        }
+    }
+    index
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Duplex {
+    Simplex,
+    Plus,
+    Minus,
+    Custom,
+}
+
+impl Duplex {
+    pub fn from_u32(i: u32) -> Option<Self> {
+        match i {
+            0 => Some(Duplex::Simplex),
+            1 => Some(Duplex::Plus),
+            2 => Some(Duplex::Minus),
+            3 => Some(Duplex::Custom),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+}
+
+// the usual repeater offset for a band
+pub fn standard_offset(band: Bands, region: Region) -> f32 {
+    match band {
+        Bands::Band10 => 100000.0,
+        Bands::Band6 => match region {
+            Region::Region2 | Region::FCC => 1000000.0,
+            _ => 600000.0,
+        },
+        _ => 600000.0, // 2m through a transverter
+    }
+}
+
+// detects a CTCSS tone on the IQ samples of a receiver, the WDSP FM
+// demodulator filters the sub audible tones out of its audio
+#[derive(Debug, Clone, Default)]
+pub struct CtcssDecoder {
+    sample_rate: i32,
+    decimation: i32,
+    taps: Vec<f64>,
+    history: Vec<(f64, f64)>,
+    position: usize,
+    phase: f64,
+    count: i32,
+    last_i: f64,
+    last_q: f64,
+    tone_low_pass: [f64; 2],
+    audio_count: i32,
+    s1: f64,
+    s2: f64,
+    energy: f64,
+    samples: usize,
+    pub detected: bool,
+}
+
+// windowed sinc low pass filter
+fn low_pass_taps(cutoff: f64, sample_rate: f64, taps: usize) -> Vec<f64> {
+    let m = (taps - 1) as f64;
+    (0..taps).map(|n| {
+        let x = n as f64 - (m / 2.0);
+        let sinc = if x == 0.0 {
+            2.0 * cutoff / sample_rate
+        } else {
+            (2.0 * PI * cutoff * x / sample_rate).sin() / (PI * x)
+        };
+        let window = 0.54 - (0.46 * (2.0 * PI * n as f64 / m).cos()); // Hamming
+        sinc * window
+    }).collect()
+}
+
+impl CtcssDecoder {
+
+    pub fn reset(&mut self) {
+        *self = CtcssDecoder::default();
+    }
+
+    // the channel filter that is applied before decimating to the discriminator rate
+    fn design(&mut self, sample_rate: i32) {
+        self.sample_rate = sample_rate;
+        self.decimation = (sample_rate / DISCRIMINATOR_RATE).max(1);
+        let taps = (CHANNEL_TAPS * self.decimation as usize) + 1;
+        self.taps = low_pass_taps(CHANNEL_CUTOFF.min(sample_rate as f64 * 0.4), sample_rate as f64, taps);
+        self.history = vec![(0.0, 0.0); taps];
+        self.position = 0;
+        self.count = 0;
+    }
+
+    // iq is interleaved I/Q at sample_rate, offset is the frequency of the signal from the center
+    pub fn process(&mut self, iq: &[f64], samples: usize, sample_rate: i32, offset: f32, tone: f32) -> bool {
+        if sample_rate != self.sample_rate {
+            self.design(sample_rate);
+        }
+        let discriminator_rate = sample_rate as f64 / self.decimation as f64;
+        let audio_decimation = (discriminator_rate as i32 / DECODER_RATE).max(1);
+        let decoder_rate = discriminator_rate / audio_decimation as f64;
+        let step = -2.0 * PI * offset as f64 / sample_rate as f64;
+        let alpha = 1.0 - (-2.0 * PI * TONE_CUTOFF / discriminator_rate).exp();
+        let coeff = 2.0 * (2.0 * PI * tone as f64 / decoder_rate).cos();
+        for n in 0..samples.min(iq.len() / 2) {
+            let i = iq[n * 2];
+            let q = iq[(n * 2) + 1];
+            let (s, c) = self.phase.sin_cos();
+            self.phase = self.phase + step;
+            if self.phase > PI {
+                self.phase = self.phase - (2.0 * PI);
+            } else if self.phase < -PI {
+                self.phase = self.phase + (2.0 * PI);
+            }
+            self.history[self.position] = ((i * c) - (q * s), (i * s) + (q * c));
+            self.position = (self.position + 1) % self.history.len();
+            self.count = self.count + 1;
+            if self.count < self.decimation {
+                continue;
+            }
+            self.count = 0;
+
+            // filter the channel, only needed for the samples that are kept
+            let mut channel_i = 0.0;
+            let mut channel_q = 0.0;
+            let history = self.history[self.position..].iter().chain(self.history[..self.position].iter());
+            for ((hi, hq), tap) in history.zip(self.taps.iter()) {
+                channel_i = channel_i + (hi * tap);
+                channel_q = channel_q + (hq * tap);
+            }
+
+            // FM discriminator
+            let d = ((channel_q * self.last_i) - (channel_i * self.last_q)).atan2((channel_i * self.last_i) + (channel_q * self.last_q));
+            self.last_i = channel_i;
+            self.last_q = channel_q;
+
+            // keep only the sub audible tones so voice does not swamp the detector
+            self.tone_low_pass[0] = self.tone_low_pass[0] + (alpha * (d - self.tone_low_pass[0]));
+            self.tone_low_pass[1] = self.tone_low_pass[1] + (alpha * (self.tone_low_pass[0] - self.tone_low_pass[1]));
+            self.audio_count = self.audio_count + 1;
+            if self.audio_count < audio_decimation {
+                continue;
+            }
+            self.audio_count = 0;
+            let audio = self.tone_low_pass[1];
+
+            // Goertzel at the tone frequency
+            let s0 = audio + (coeff * self.s1) - self.s2;
+            self.s2 = self.s1;
+            self.s1 = s0;
+            self.energy = self.energy + (audio * audio);
+            self.samples = self.samples + 1;
+            if self.samples == DECODER_BLOCK {
+                let power = (self.s1 * self.s1) + (self.s2 * self.s2) - (coeff * self.s1 * self.s2);
+                self.detected = self.energy > 0.0 && power / (self.energy * (DECODER_BLOCK as f64 / 2.0)) > DECODER_THRESHOLD;
+                self.s1 = 0.0;
+                self.s2 = 0.0;
+                self.energy = 0.0;
+                self.samples = 0;
+            }
+        }
+        self.detected
+    }
+}
+
+fn tone_list() -> StringList {
+    let tones: Vec<String> = CTCSS_TONES.iter().map(|tone| format!("{:.1} Hz", tone)).collect();
+    let tones: Vec<&str> = tones.iter().map(|tone| tone.as_str()).collect();
+    StringList::new(&tones)
+}
+
+pub fn create_repeater_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMutex) -> Window {
+
+    let ui_xml = include_str!("../ui/repeater.xml");
+    let builder = Builder::from_string(ui_xml);
+
+    let window: Window = builder
+            .object("repeater_window")
+            .expect("Could not get object `repeater_window` from builder.");
+
+    window.set_transient_for(Some(parent)); // keeps it on top
+
+    let r = radio_mutex.radio.lock().unwrap();
+        let mut rx = 0;
+        if r.receiver[1].active {
+            rx = 1;
+        }
+        let tx_ctcss = r.transmitter.ctcss;
+        let tx_ctcss_frequency = r.transmitter.ctcss_frequency;
+        let rx_ctcss = r.receiver[rx].ctcss;
+        let rx_ctcss_frequency = r.receiver[rx].ctcss_frequency;
+        let duplex = r.transmitter.duplex;
+        let repeater_offset = r.transmitter.repeater_offset;
+        let custom_offset = r.transmitter.custom_offset;
+    drop(r);
+
+    let tx_ctcss_check_button: CheckButton = builder
+            .object("repeater_tx_ctcss_check_button")
+            .expect("Could not get object `repeater_tx_ctcss_check_button` from builder.");
+    tx_ctcss_check_button.set_active(tx_ctcss);
+    let radio_mutex_clone = radio_mutex.clone();
+    tx_ctcss_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.ctcss = button.is_active();
+        r.transmitter.set_ctcss();
+    });
+
+    let tx_tone_dropdown: DropDown = builder
+            .object("repeater_tx_tone_dropdown")
+            .expect("Could not get object `repeater_tx_tone_dropdown` from builder.");
+    tx_tone_dropdown.set_model(Some(&tone_list()));
+    tx_tone_dropdown.set_selected(ctcss_index(tx_ctcss_frequency) as u32);
+    let radio_mutex_clone = radio_mutex.clone();
+    tx_tone_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.ctcss_frequency = CTCSS_TONES[dropdown.selected() as usize];
+        r.transmitter.set_ctcss();
+    });
+
+    let rx_ctcss_check_button: CheckButton = builder
+            .object("repeater_rx_ctcss_check_button")
+            .expect("Could not get object `repeater_rx_ctcss_check_button` from builder.");
+    rx_ctcss_check_button.set_label(Some(&format!("RX{} Tone Squelch", rx + 1)));
+    rx_ctcss_check_button.set_active(rx_ctcss);
+    let radio_mutex_clone = radio_mutex.clone();
+    rx_ctcss_check_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.receiver[rx].ctcss = button.is_active();
+        r.receiver[rx].ctcss_decoder.reset();
+    });
+
+    let rx_tone_dropdown: DropDown = builder
+            .object("repeater_rx_tone_dropdown")
+            .expect("Could not get object `repeater_rx_tone_dropdown` from builder.");
+    rx_tone_dropdown.set_model(Some(&tone_list()));
+    rx_tone_dropdown.set_selected(ctcss_index(rx_ctcss_frequency) as u32);
+    let radio_mutex_clone = radio_mutex.clone();
+    rx_tone_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.receiver[rx].ctcss_frequency = CTCSS_TONES[dropdown.selected() as usize];
+        r.receiver[rx].ctcss_decoder.reset();
+    });
+
+    let duplex_dropdown: DropDown = builder
+            .object("repeater_duplex_dropdown")
+            .expect("Could not get object `repeater_duplex_dropdown` from builder.");
+    duplex_dropdown.set_selected(duplex.to_u32());
+    let radio_mutex_clone = radio_mutex.clone();
+    duplex_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.duplex = Duplex::from_u32(dropdown.selected()).expect("Invalid Duplex");
+    });
+
+    let offset_adjustment: Adjustment = builder
+            .object("repeater_offset_adjustment")
+            .expect("Could not get object `repeater_offset_adjustment` from builder.");
+    offset_adjustment.set_value((repeater_offset / 1000.0).into());
+    let radio_mutex_clone = radio_mutex.clone();
+    offset_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.repeater_offset = adjustment.value() as f32 * 1000.0;
+    });
+
+    let standard_button: Button = builder
+            .object("repeater_standard_button")
+            .expect("Could not get object `repeater_standard_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let offset_adjustment_clone = offset_adjustment.clone();
+    standard_button.connect_clicked(move |_| {
+        let r = radio_mutex_clone.radio.lock().unwrap();
+        let offset = standard_offset(r.receiver[rx].band, r.band_plan.region);
+        drop(r);
+        offset_adjustment_clone.set_value((offset / 1000.0).into());
+    });
+
+    let custom_adjustment: Adjustment = builder
+            .object("repeater_custom_adjustment")
+            .expect("Could not get object `repeater_custom_adjustment` from builder.");
+    custom_adjustment.set_value((custom_offset / 1000.0).into());
+    let radio_mutex_clone = radio_mutex.clone();
+    custom_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.transmitter.custom_offset = adjustment.value() as f32 * 1000.0;
+    });
+
+    let status_label: Label = builder
+            .object("repeater_status_label")
+            .expect("Could not get object `repeater_status_label` from builder.");
+
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    timeout_add_local(Duration::from_millis(250), move || {
+        if !window_clone.is_visible() {
+            return Break;
+        }
+        let r = radio_mutex_clone.radio.lock().unwrap();
+        let mut tx = 0;
+        if r.split {
+            tx = 1;
+        }
+        let mut frequency = r.receiver[tx].frequency;
+        if r.receiver[tx].ctun {
+            frequency = r.receiver[tx].ctun_frequency;
+        }
+        let offset = r.transmitter.duplex_offset();
+        let reverse = r.transmitter.reverse;
+        let detected = r.receiver[rx].ctcss && r.receiver[rx].ctcss_decoder.detected;
+        drop(r);
+        let mut status = format!("TX {}", format_u32_with_separators((frequency + offset) as u32));
+        if reverse {
+            status = format!("{} (reverse)", status);
+        }
+        if detected {
+            status = format!("{} - tone detected", status);
+        }
+        status_label.set_label(&status);
+        Continue
+    });
+
+    window
+}
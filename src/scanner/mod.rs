@@ -0,0 +1,557 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use glib::ControlFlow::{Break, Continue};
+use glib::timeout_add_local;
+use gtk::prelude::*;
+use gtk::{Adjustment, ApplicationWindow, Builder, Button, DropDown, Label, ToggleButton, Window};
+use serde::{Deserialize, Serialize};
+
+use std::time::{Duration, Instant};
+
+use crate::memories::Memory;
+use crate::radio::RadioMutex;
+use crate::util::*;
+
+const MAX_PEAKS: usize = 50;
+const HYSTERESIS: f32 = 3.0; // dB below the threshold before the carrier is considered dropped
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ScanMode {
+    Range,
+    Memory,
+    Panoramic,
+}
+
+impl ScanMode {
+    pub fn from_u32(i: u32) -> Option<Self> {
+        match i {
+            0 => Some(ScanMode::Range),
+            1 => Some(ScanMode::Memory),
+            2 => Some(ScanMode::Panoramic),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ScanResume {
+    Timeout,
+    CarrierDrop,
+}
+
+impl ScanResume {
+    pub fn from_u32(i: u32) -> Option<Self> {
+        match i {
+            0 => Some(ScanResume::Timeout),
+            1 => Some(ScanResume::CarrierDrop),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+}
+
+// what the main loop has to do next
+pub enum ScanAction {
+    Tune(f32),
+    Recall(Memory),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+enum ScanState {
+    #[default]
+    Idle,
+    Dwell(Instant),
+    Stopped(Instant),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Scanner {
+    pub mode: ScanMode,
+    pub low: f32,
+    pub high: f32,
+    pub threshold: f32,      // dBm measured in the passband
+    pub peak_threshold: f32, // dBm of a spectrum pixel
+    pub peak_spacing: f32,   // Hz
+    pub dwell: u64,          // ms
+    pub resume: ScanResume,
+    pub resume_time: u64,    // seconds
+    pub hang_time: u64,      // ms
+#[serde(skip_serializing, skip_deserializing)]
+    pub running: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    pub rx: usize,
+#[serde(skip_serializing, skip_deserializing)]
+    pub frequency: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub status: String,
+#[serde(skip_serializing, skip_deserializing)]
+    pub peaks: Vec<f32>,
+#[serde(skip_serializing, skip_deserializing)]
+    pub skip: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    state: ScanState,
+#[serde(skip_serializing, skip_deserializing)]
+    last_signal: Option<Instant>,
+#[serde(skip_serializing, skip_deserializing)]
+    memory_index: Option<usize>,
+#[serde(skip_serializing, skip_deserializing)]
+    peak_index: Option<usize>,
+#[serde(skip_serializing, skip_deserializing)]
+    peaks_needed: bool,
+}
+
+impl Scanner {
+
+    pub fn new() -> Scanner {
+        let mode = ScanMode::Range;
+        let low = 2850000.0;
+        let high = 22000000.0;
+        let threshold = -100.0;
+        let peak_threshold = -110.0;
+        let peak_spacing = 3000.0;
+        let dwell = 150;
+        let resume = ScanResume::CarrierDrop;
+        let resume_time = 10;
+        let hang_time = 2000;
+        let running = false;
+        let rx = 0;
+        let frequency = 0.0;
+        let status = String::from("Idle");
+        let peaks: Vec<f32> = Vec::new();
+        let skip = false;
+        let state = ScanState::Idle;
+        let last_signal = None;
+        let memory_index = None;
+        let peak_index = None;
+        let peaks_needed = false;
+        Scanner {
+            mode,
+            low,
+            high,
+            threshold,
+            peak_threshold,
+            peak_spacing,
+            dwell,
+            resume,
+            resume_time,
+            hang_time,
+            running,
+            rx,
+            frequency,
+            status,
+            peaks,
+            skip,
+            state,
+            last_signal,
+            memory_index,
+            peak_index,
+            peaks_needed,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.running = false;
+        self.status = String::from("Idle");
+        self.peaks.clear();
+        self.state = ScanState::Idle;
+    }
+
+    // start scanning on a receiver from its current frequency
+    pub fn start(&mut self, rx: usize, frequency: f32) {
+        self.running = true;
+        self.rx = rx;
+        self.frequency = frequency;
+        if frequency < self.low || frequency > self.high {
+            self.frequency = self.low;
+        }
+        self.skip = false;
+        self.state = ScanState::Idle;
+        self.last_signal = None;
+        self.memory_index = None;
+        self.peak_index = None;
+        self.peaks.clear();
+        self.peaks_needed = self.mode == ScanMode::Panoramic;
+        self.status = String::from("Scanning");
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+        self.state = ScanState::Idle;
+        self.peaks_needed = false;
+        self.status = String::from("Idle");
+    }
+
+    pub fn is_stopped_on_signal(&self) -> bool {
+        matches!(self.state, ScanState::Stopped(_))
+    }
+
+    // called every time the main loop polls the scanner with the current signal level,
+    // when the receiver squelch is on its state decides if there is a signal
+    pub fn tick(&mut self, level: f32, squelch: Option<bool>, memories: &Vec<Memory>, step: f32) -> Option<ScanAction> {
+        if !self.running {
+            return None;
+        }
+        let now = Instant::now();
+        match self.state {
+            ScanState::Idle => self.next(memories, step),
+            ScanState::Dwell(start) => {
+                if now.duration_since(start) < Duration::from_millis(self.dwell) {
+                    return None;
+                }
+                let signal = match squelch {
+                    Some(open) => open,
+                    None => level >= self.threshold,
+                };
+                if signal {
+                    self.state = ScanState::Stopped(now);
+                    self.last_signal = Some(now);
+                    self.status = format!("Stopped on {} ({:.0} dBm)", format_u32_with_separators(self.frequency as u32), level);
+                    return None;
+                }
+                self.next(memories, step)
+            },
+            ScanState::Stopped(start) => {
+                let carrier = match squelch {
+                    Some(open) => open,
+                    None => level >= self.threshold - HYSTERESIS,
+                };
+                if carrier {
+                    self.last_signal = Some(now);
+                }
+                let resume = match self.resume {
+                    ScanResume::Timeout => now.duration_since(start) >= Duration::from_secs(self.resume_time),
+                    ScanResume::CarrierDrop => match self.last_signal {
+                        Some(last) => now.duration_since(last) >= Duration::from_millis(self.hang_time),
+                        None => true,
+                    },
+                };
+                if resume || self.skip {
+                    self.skip = false;
+                    return self.next(memories, step);
+                }
+                None
+            },
+        }
+    }
+
+    fn next(&mut self, memories: &Vec<Memory>, step: f32) -> Option<ScanAction> {
+        let action = match self.mode {
+            ScanMode::Range => {
+                let mut frequency = self.frequency + step.abs().max(1.0);
+                if frequency < self.low || frequency > self.high {
+                    frequency = self.low;
+                }
+                self.frequency = frequency;
+                Some(ScanAction::Tune(frequency))
+            },
+            ScanMode::Memory => {
+                let start = match self.memory_index {
+                    Some(i) => i + 1,
+                    None => 0,
+                };
+                let index = (0..memories.len())
+                    .map(|i| (start + i) % memories.len())
+                    .find(|i| !memories[*i].skip);
+                match index {
+                    Some(i) => {
+                        self.memory_index = Some(i);
+                        self.frequency = memories[i].frequency;
+                        Some(ScanAction::Recall(memories[i].clone()))
+                    },
+                    None => {
+                        self.stop();
+                        self.status = String::from("No memories to scan");
+                        return None;
+                    },
+                }
+            },
+            ScanMode::Panoramic => {
+                let index = match self.peak_index {
+                    Some(i) => i + 1,
+                    None => 0,
+                };
+                if index < self.peaks.len() {
+                    self.peak_index = Some(index);
+                    self.frequency = self.peaks[index];
+                    Some(ScanAction::Tune(self.frequency))
+                } else {
+                    // wait for the next spectrum
+                    self.peaks.clear();
+                    self.peak_index = None;
+                    self.peaks_needed = true;
+                    self.state = ScanState::Idle;
+                    self.status = String::from("Searching");
+                    return None;
+                }
+            },
+        };
+        self.state = ScanState::Dwell(Instant::now());
+        self.status = format!("Scanning {}", format_u32_with_separators(self.frequency as u32));
+        action
+    }
+
+    pub fn wants_peaks(&self, rx: usize) -> bool {
+        self.running && self.rx == rx && self.mode == ScanMode::Panoramic && self.peaks_needed
+    }
+
+    // find the signals in a spectrum, frequency_low is the frequency of the first pixel
+    pub fn update_peaks(&mut self, pixels: &Vec<f32>, frequency_low: f32, hz_per_pixel: f32) {
+        let mut candidates: Vec<(f32, f32)> = Vec::new();
+        for i in 1..pixels.len().saturating_sub(1) {
+            let level = pixels[i];
+            if level >= self.peak_threshold && level >= pixels[i - 1] && level > pixels[i + 1] {
+                let frequency = frequency_low + (i as f32 * hz_per_pixel);
+                if frequency >= self.low && frequency <= self.high {
+                    candidates.push((frequency, level));
+                }
+            }
+        }
+
+        // keep the strongest signal within the peak spacing
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let mut peaks: Vec<f32> = Vec::new();
+        for (frequency, _) in candidates {
+            if peaks.len() >= MAX_PEAKS {
+                break;
+            }
+            if peaks.iter().all(|p| (p - frequency).abs() >= self.peak_spacing) {
+                peaks.push(frequency);
+            }
+        }
+        peaks.sort_by(|a, b| a.total_cmp(b));
+
+        self.peaks = peaks;
+        self.peak_index = None;
+        if !self.peaks.is_empty() {
+            self.peaks_needed = false;
+        }
+    }
+}
+
+pub fn create_scanner_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMutex) -> Window {
+
+    let ui_xml = include_str!("../ui/scanner.xml");
+    let builder = Builder::from_string(ui_xml);
+
+    let window: Window = builder
+            .object("scanner_window")
+            .expect("Could not get object `scanner_window` from builder.");
+
+    window.set_transient_for(Some(parent)); // keeps it on top
+
+    let r = radio_mutex.radio.lock().unwrap();
+        let mode = r.scanner.mode;
+        let low = r.scanner.low;
+        let high = r.scanner.high;
+        let threshold = r.scanner.threshold;
+        let peak_threshold = r.scanner.peak_threshold;
+        let peak_spacing = r.scanner.peak_spacing;
+        let dwell = r.scanner.dwell;
+        let resume = r.scanner.resume;
+        let resume_time = r.scanner.resume_time;
+        let hang_time = r.scanner.hang_time;
+        let running = r.scanner.running;
+    drop(r);
+
+    let mode_dropdown: DropDown = builder
+            .object("scanner_mode_dropdown")
+            .expect("Could not get object `scanner_mode_dropdown` from builder.");
+    mode_dropdown.set_selected(mode.to_u32());
+    let radio_mutex_clone = radio_mutex.clone();
+    mode_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.mode = ScanMode::from_u32(dropdown.selected()).expect("Invalid Scan Mode");
+    });
+
+    let low_adjustment: Adjustment = builder
+            .object("scanner_low_adjustment")
+            .expect("Could not get object `scanner_low_adjustment` from builder.");
+    low_adjustment.set_value((low / 1000.0).into());
+    let radio_mutex_clone = radio_mutex.clone();
+    low_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.low = adjustment.value() as f32 * 1000.0;
+    });
+
+    let high_adjustment: Adjustment = builder
+            .object("scanner_high_adjustment")
+            .expect("Could not get object `scanner_high_adjustment` from builder.");
+    high_adjustment.set_value((high / 1000.0).into());
+    let radio_mutex_clone = radio_mutex.clone();
+    high_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.high = adjustment.value() as f32 * 1000.0;
+    });
+
+    let band_button: Button = builder
+            .object("scanner_band_button")
+            .expect("Could not get object `scanner_band_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    let low_adjustment_clone = low_adjustment.clone();
+    let high_adjustment_clone = high_adjustment.clone();
+    band_button.connect_clicked(move |_| {
+        let r = radio_mutex_clone.radio.lock().unwrap();
+        let mut rx = 0;
+        if r.receiver[1].active {
+            rx = 1;
+        }
+        let band = r.receiver[rx].band.to_usize();
+        let low = r.receiver[rx].band_info[band].low;
+        let high = r.receiver[rx].band_info[band].high;
+        drop(r);
+        low_adjustment_clone.set_value((low / 1000.0).into());
+        high_adjustment_clone.set_value((high / 1000.0).into());
+    });
+
+    let threshold_adjustment: Adjustment = builder
+            .object("scanner_threshold_adjustment")
+            .expect("Could not get object `scanner_threshold_adjustment` from builder.");
+    threshold_adjustment.set_value(threshold.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    threshold_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.threshold = adjustment.value() as f32;
+    });
+
+    let peak_threshold_adjustment: Adjustment = builder
+            .object("scanner_peak_threshold_adjustment")
+            .expect("Could not get object `scanner_peak_threshold_adjustment` from builder.");
+    peak_threshold_adjustment.set_value(peak_threshold.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    peak_threshold_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.peak_threshold = adjustment.value() as f32;
+    });
+
+    let peak_spacing_adjustment: Adjustment = builder
+            .object("scanner_peak_spacing_adjustment")
+            .expect("Could not get object `scanner_peak_spacing_adjustment` from builder.");
+    peak_spacing_adjustment.set_value(peak_spacing.into());
+    let radio_mutex_clone = radio_mutex.clone();
+    peak_spacing_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.peak_spacing = adjustment.value() as f32;
+    });
+
+    let dwell_adjustment: Adjustment = builder
+            .object("scanner_dwell_adjustment")
+            .expect("Could not get object `scanner_dwell_adjustment` from builder.");
+    dwell_adjustment.set_value(dwell as f64);
+    let radio_mutex_clone = radio_mutex.clone();
+    dwell_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.dwell = adjustment.value() as u64;
+    });
+
+    let resume_dropdown: DropDown = builder
+            .object("scanner_resume_dropdown")
+            .expect("Could not get object `scanner_resume_dropdown` from builder.");
+    resume_dropdown.set_selected(resume.to_u32());
+    let radio_mutex_clone = radio_mutex.clone();
+    resume_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.resume = ScanResume::from_u32(dropdown.selected()).expect("Invalid Scan Resume");
+    });
+
+    let resume_time_adjustment: Adjustment = builder
+            .object("scanner_resume_time_adjustment")
+            .expect("Could not get object `scanner_resume_time_adjustment` from builder.");
+    resume_time_adjustment.set_value(resume_time as f64);
+    let radio_mutex_clone = radio_mutex.clone();
+    resume_time_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.resume_time = adjustment.value() as u64;
+    });
+
+    let hang_time_adjustment: Adjustment = builder
+            .object("scanner_hang_time_adjustment")
+            .expect("Could not get object `scanner_hang_time_adjustment` from builder.");
+    hang_time_adjustment.set_value(hang_time as f64);
+    let radio_mutex_clone = radio_mutex.clone();
+    hang_time_adjustment.connect_value_changed(move |adjustment| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.hang_time = adjustment.value() as u64;
+    });
+
+    let start_button: ToggleButton = builder
+            .object("scanner_start_button")
+            .expect("Could not get object `scanner_start_button` from builder.");
+    start_button.set_active(running);
+    let radio_mutex_clone = radio_mutex.clone();
+    start_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if button.is_active() {
+            if !r.scanner.running {
+                let mut rx = 0;
+                if r.receiver[1].active {
+                    rx = 1;
+                }
+                let mut frequency = r.receiver[rx].frequency;
+                if r.receiver[rx].ctun {
+                    frequency = r.receiver[rx].ctun_frequency;
+                }
+                r.scanner.start(rx, frequency);
+            }
+        } else if r.scanner.running {
+            r.scanner.stop();
+        }
+    });
+
+    let skip_button: Button = builder
+            .object("scanner_skip_button")
+            .expect("Could not get object `scanner_skip_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    skip_button.connect_clicked(move |_| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.scanner.skip = true;
+    });
+
+    let status_label: Label = builder
+            .object("scanner_status_label")
+            .expect("Could not get object `scanner_status_label` from builder.");
+
+    let radio_mutex_clone = radio_mutex.clone();
+    let window_clone = window.clone();
+    timeout_add_local(Duration::from_millis(250), move || {
+        if !window_clone.is_visible() {
+            return Break;
+        }
+        let r = radio_mutex_clone.radio.lock().unwrap();
+        let running = r.scanner.running;
+        let mut status = r.scanner.status.clone();
+        if r.scanner.mode == ScanMode::Panoramic && running {
+            status = format!("{} - {} signals", status, r.scanner.peaks.len());
+        }
+        drop(r);
+        status_label.set_label(&status);
+        if start_button.is_active() != running {
+            start_button.set_active(running);
+        }
+        skip_button.set_sensitive(running);
+        Continue
+    });
+
+    window
+}
@@ -20,11 +20,47 @@ use gtk::cairo::{Context, Format, ImageSurface, LineCap, LineJoin, LinearGradien
 use crate::radio::RadioMutex;
 use crate::modes::*;
 use crate::util::*;
+use crate::dxcluster::Spot;
+use crate::bandplan::SegmentType;
+
+// where a DX spot label was drawn so a click on it can tune to the spot
+#[derive(Clone, Copy)]
+pub struct SpotLabel {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub frequency: f32,
+    pub mode: Modes,
+}
+
+// which part of a notch is under the pointer, the edges resize it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NotchEdge {
+    Left,
+    Right,
+    Body,
+}
+
+// where a notch was drawn so it can be dragged, resized or deleted
+#[derive(Clone, Copy)]
+struct NotchMark {
+    index: usize,
+    left: f64,
+    right: f64,
+}
+
+// how close in pixels the pointer must be to a notch edge to resize it
+const NOTCH_EDGE_PIXELS: f64 = 4.0;
 
 #[derive(Clone)]
 pub struct Spectrum {
     rx: usize,
     surface: ImageSurface,
+    spot_labels: Vec<SpotLabel>,
+    notch_marks: Vec<NotchMark>,
+    display_frequency_low: f32,
+    display_hz_per_pixel: f32,
 }
 
 impl Spectrum {
@@ -32,9 +68,17 @@ impl Spectrum {
     pub fn new(id: usize, width: i32, height: i32) -> Self {
         let rx = id;
         let surface = ImageSurface::create(Format::ARgb32, width, height).expect("Failed to create surface");
+        let spot_labels = Vec::new();
+        let notch_marks = Vec::new();
+        let display_frequency_low = 0.0;
+        let display_hz_per_pixel = 1.0;
         Self {
             rx,
             surface,
+            spot_labels,
+            notch_marks,
+            display_frequency_low,
+            display_hz_per_pixel,
         }
     }
 
@@ -50,6 +94,8 @@ impl Spectrum {
 
         let r = radio_mutex.radio.lock().unwrap();
         let spectrum_height = height - 10; // leave space for the frequency
+        self.spot_labels.clear();
+        self.notch_marks.clear();
         let cr = Context::new(self.surface.clone()).expect("Couldn't create cairo context from surface");
         if r.receiver[self.rx].active {
             cr.set_source_rgb(0.0, 0.0, 1.0); // dark blue
@@ -109,8 +155,10 @@ impl Spectrum {
             cr.set_line_cap(LineCap::Round);
             cr.set_line_join(LineJoin::Round);
 
-            let frequency_low = r.receiver[self.rx].frequency - (r.receiver[self.rx].sample_rate/2) as f32;
-            let frequency_high = r.receiver[self.rx].frequency + (r.receiver[self.rx].sample_rate/2) as f32;
+            // the spectrum is centred on the frequency the DDC is tuned to, notches are placed against it too
+            let nco_frequency = r.receiver[self.rx].nco_frequency();
+            let frequency_low = nco_frequency - (r.receiver[self.rx].sample_rate/2) as f32;
+            let frequency_high = nco_frequency + (r.receiver[self.rx].sample_rate/2) as f32;
             let frequency_range = frequency_high - frequency_low;
    
             let display_frequency_range = frequency_range / r.receiver[self.rx].zoom as f32;
@@ -118,6 +166,8 @@ impl Spectrum {
             let display_frequency_low = frequency_low + display_frequency_offset;
             let display_frequency_high = frequency_high + display_frequency_offset;
             let display_hz_per_pixel = display_frequency_range as f32 / width as f32;
+            self.display_frequency_low = display_frequency_low;
+            self.display_hz_per_pixel = display_hz_per_pixel;
 
             let mut step = 25000.0;
             match r.receiver[self.rx].sample_rate {
@@ -204,14 +254,61 @@ impl Spectrum {
                 cr.line_to(x.into(), spectrum_height.into());
                 cr.stroke().unwrap();
 
-                // draw the filter
+                // draw the filter, moved by any RIT offset
                 cr.set_source_rgba (0.4, 0.4, 0.4, 0.80);
-                let filter_left = ((frequency + r.receiver[self.rx].filter_low) - display_frequency_low) / display_hz_per_pixel;
-                let filter_right = ((frequency + r.receiver[self.rx].filter_high) - display_frequency_low) / display_hz_per_pixel;
+                let rit = r.receiver[self.rx].rit_shift();
+                let filter_left = ((frequency + rit + r.receiver[self.rx].filter_low) - display_frequency_low) / display_hz_per_pixel;
+                let filter_right = ((frequency + rit + r.receiver[self.rx].filter_high) - display_frequency_low) / display_hz_per_pixel;
                 cr.rectangle(filter_left.into(), 0.0, (filter_right-filter_left).into(), spectrum_height.into());
                 let _ = cr.fill();
             }
 
+            // draw the sub-RX marker and passband in green
+            if r.receiver[self.rx].subrx {
+                let mut subrx_frequency = r.receiver[self.rx].subrx_frequency;
+                if r.receiver[self.rx].subrx_mode == Modes::CWL.to_usize() {
+                    subrx_frequency = subrx_frequency + r.receiver[self.rx].cw_pitch;
+                } else if r.receiver[self.rx].subrx_mode == Modes::CWU.to_usize() {
+                    subrx_frequency = subrx_frequency - r.receiver[self.rx].cw_pitch;
+                }
+                if display_frequency_low < subrx_frequency && display_frequency_high > subrx_frequency {
+                    let x = (subrx_frequency - display_frequency_low) / display_hz_per_pixel;
+                    cr.set_source_rgb(0.0, 1.0, 0.0);
+                    cr.set_line_width(1.0);
+                    cr.move_to(x.into(), 0.0);
+                    cr.line_to(x.into(), spectrum_height.into());
+                    cr.stroke().unwrap();
+                    cr.move_to((x + 2.0).into(), 32.0);
+                    let _ = cr.show_text("SUB");
+
+                    cr.set_source_rgba (0.0, 0.5, 0.0, 0.50);
+                    let filter_left = ((subrx_frequency + r.receiver[self.rx].subrx_filter_low) - display_frequency_low) / display_hz_per_pixel;
+                    let filter_right = ((subrx_frequency + r.receiver[self.rx].subrx_filter_high) - display_frequency_low) / display_hz_per_pixel;
+                    cr.rectangle(filter_left.into(), 0.0, (filter_right-filter_left).into(), spectrum_height.into());
+                    let _ = cr.fill();
+                }
+            }
+
+            // draw the transmit frequency marker when split, XIT or a repeater shift moves it
+            let tx_frequency = r.tx_frequency();
+            let mut rx_frequency = r.receiver[self.rx].frequency;
+            if r.receiver[self.rx].ctun {
+                rx_frequency = r.receiver[self.rx].ctun_frequency;
+            }
+            if (tx_frequency - rx_frequency).abs() >= 1.0 && display_frequency_low < tx_frequency && display_frequency_high > tx_frequency {
+                let x = (tx_frequency - display_frequency_low) / display_hz_per_pixel;
+                cr.set_source_rgb(1.0, 0.5, 0.0);
+                cr.set_line_width(2.0);
+                cr.set_dash(&[4.0, 2.0], 0.0);
+                cr.move_to(x.into(), 0.0);
+                cr.line_to(x.into(), spectrum_height.into());
+                cr.stroke().unwrap();
+                cr.set_dash(&[], 0.0);
+                cr.set_line_width(1.0);
+                cr.move_to((x + 2.0).into(), 20.0);
+                let _ = cr.show_text("TX");
+            }
+
             // draw the spectrum
             let spectrum_high = r.receiver[self.rx].band_info[b].spectrum_high;
             let spectrum_width = r.receiver[self.rx].spectrum_width;
@@ -243,6 +340,29 @@ impl Spectrum {
             let _ = cr.fill_preserve();
             cr.stroke().unwrap();
 
+            // shade the band plan segments along the bottom, beacons on top
+            if r.band_plan.show {
+                let mut segments: Vec<_> = r.band_plan.segments().iter()
+                    .filter(|segment| segment.high > display_frequency_low && segment.low < display_frequency_high)
+                    .collect();
+                segments.sort_by_key(|segment| segment.segment_type == SegmentType::Beacon);
+                for segment in segments {
+                    match segment.segment_type {
+                        SegmentType::CW => cr.set_source_rgb(1.0, 0.5, 0.0),
+                        SegmentType::Digital => cr.set_source_rgb(1.0, 0.0, 1.0),
+                        SegmentType::Phone => cr.set_source_rgb(0.0, 0.8, 0.0),
+                        SegmentType::Beacon => cr.set_source_rgb(1.0, 1.0, 1.0),
+                    }
+                    let mut left = (segment.low - display_frequency_low) / display_hz_per_pixel;
+                    let right = (segment.high - display_frequency_low) / display_hz_per_pixel;
+                    if right - left < 2.0 {
+                        left = right - 2.0;
+                    }
+                    cr.rectangle(left.into(), (spectrum_height - 5).into(), (right - left).into(), 5.0);
+                    let _ = cr.fill();
+                }
+            }
+
             // draw the frequency markers
             let mut f: f32 = (((display_frequency_low as i32 + step as i32) / step as i32) * step as i32) as f32;
             while f < display_frequency_high {
@@ -261,21 +381,99 @@ impl Spectrum {
                 f = f + step as f32;
             }
 
-            // draw any active notches
-            for i in 0..r.notch {
-                let notch = r.notches[i as usize];
-                if notch.frequency > display_frequency_low as f64 && notch.frequency < display_frequency_high as f64 {
-                    let x = (notch.frequency - display_frequency_low as f64) / display_hz_per_pixel as f64;
-                    cr.set_source_rgb(1.0, 1.0, 1.0);
-                    cr.move_to( x.into(), 0.0);
-                    cr.line_to( x.into(), spectrum_height.into());
-                    cr.stroke().unwrap();
+            // draw the notches for this band, disabled ones are greyed out
+            for (index, notch) in r.receiver[self.rx].notches().iter().enumerate() {
+                if notch.high() > display_frequency_low as f64 && notch.low() < display_frequency_high as f64 {
+                    let mut left = (notch.low() - display_frequency_low as f64) / display_hz_per_pixel as f64;
+                    let mut right = (notch.high() - display_frequency_low as f64) / display_hz_per_pixel as f64;
+                    if right - left < 2.0 {
+                        let x = (left + right) / 2.0;
+                        left = x - 1.0;
+                        right = x + 1.0;
+                    }
+                    if notch.is_active() {
+                        cr.set_source_rgba(1.0, 0.0, 0.0, 0.3);
+                    } else {
+                        cr.set_source_rgba(0.5, 0.5, 0.5, 0.3);
+                    }
+                    cr.rectangle(left, 0.0, right - left, spectrum_height.into());
+                    let _ = cr.fill();
+                    self.notch_marks.push(NotchMark { index, left, right });
+                }
+            }
+            cr.set_source_rgba(1.0, 1.0, 0.0, 0.3);
+            for (_id, notch) in r.receiver[self.rx].auto_notches.iter() {
+                if notch.high() > display_frequency_low as f64 && notch.low() < display_frequency_high as f64 {
+                    let left = (notch.low() - display_frequency_low as f64) / display_hz_per_pixel as f64;
+                    let right = (notch.high() - display_frequency_low as f64) / display_hz_per_pixel as f64;
+                    cr.rectangle(left, 0.0, (right - left).max(2.0), spectrum_height.into());
+                    let _ = cr.fill();
+                }
+            }
+
+            // draw any DX cluster spots, stacking the labels so they do not overlap
+            let band_info = &r.receiver[self.rx].band_info;
+            let mut spots: Vec<&Spot> = r.dxcluster.spots.iter()
+                .filter(|spot| spot.frequency > display_frequency_low && spot.frequency < display_frequency_high && r.dxcluster.show_spot(spot, band_info, &r.band_plan))
+                .collect();
+            spots.sort_by(|a, b| a.frequency.total_cmp(&b.frequency));
+            let mut rows = [f64::MIN; 4];
+            cr.set_line_width(1.0);
+            for spot in spots {
+                let x = ((spot.frequency - display_frequency_low) / display_hz_per_pixel) as f64;
+                let text_width = match cr.text_extents(&spot.call) {
+                    Ok(extents) => extents.x_advance(),
+                    Err(_) => 0.0,
+                };
+                let mut row = 0;
+                while row < rows.len() - 1 && rows[row] > x {
+                    row = row + 1;
                 }
+                rows[row] = x + text_width + 4.0;
+                let y = 20.0 + (row as f64 * 12.0);
+                cr.set_source_rgba(0.0, 1.0, 1.0, 0.4);
+                cr.move_to(x, y + 2.0);
+                cr.line_to(x, spectrum_height.into());
+                cr.stroke().unwrap();
+                cr.set_source_rgb(0.0, 1.0, 1.0);
+                cr.move_to(x, y);
+                let _ = cr.show_text(&spot.call);
+                self.spot_labels.push(SpotLabel { x, y: y - 10.0, width: text_width, height: 12.0, frequency: spot.frequency, mode: spot.mode(band_info, &r.band_plan) });
             }
 
         }
     }
 
+    // the spot whose label is at x,y
+    pub fn spot_at(&self, x: f64, y: f64) -> Option<SpotLabel> {
+        self.spot_labels.iter().rev().find(|label| x >= label.x && x <= label.x + label.width && y >= label.y && y <= label.y + label.height).copied()
+    }
+
+    // the notch at x, and whether x is on one of its edges
+    pub fn notch_at(&self, x: f64) -> Option<(usize, NotchEdge)> {
+        for mark in self.notch_marks.iter().rev() {
+            if x >= mark.left - NOTCH_EDGE_PIXELS && x <= mark.right + NOTCH_EDGE_PIXELS {
+                if (x - mark.left).abs() <= NOTCH_EDGE_PIXELS && mark.right - mark.left > NOTCH_EDGE_PIXELS * 2.0 {
+                    return Some((mark.index, NotchEdge::Left));
+                } else if (x - mark.right).abs() <= NOTCH_EDGE_PIXELS && mark.right - mark.left > NOTCH_EDGE_PIXELS * 2.0 {
+                    return Some((mark.index, NotchEdge::Right));
+                } else if x >= mark.left && x <= mark.right {
+                    return Some((mark.index, NotchEdge::Body));
+                }
+            }
+        }
+        None
+    }
+
+    // the frequency at x when the spectrum was last drawn
+    pub fn frequency_at(&self, x: f64) -> f64 {
+        self.display_frequency_low as f64 + (x * self.display_hz_per_pixel as f64)
+    }
+
+    pub fn hz_per_pixel(&self) -> f64 {
+        self.display_hz_per_pixel as f64
+    }
+
     pub fn draw(&self, cr: &Context, width: i32, height: i32) {
         cr.set_source_surface(&self.surface, 0.0, 0.0).expect("failed to set source surface");
         cr.paint().expect("Failed to pant surface");
@@ -17,6 +17,7 @@
 
 
 use std::cmp::{max, min};
+use std::f64::consts::PI;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_int};
 
@@ -25,8 +26,38 @@ use serde::{Deserialize, Serialize};
 use crate::alex::*;
 use crate::discovery::Boards;
 use crate::modes::Modes;
+use crate::repeater::Duplex;
+use crate::txtest::{TestSignal, TxTest};
 use crate::wdsp::*;
 
+// pulsed tune, milliseconds
+const TUNE_PULSE_PERIOD: usize = 100;
+const TUNE_PULSE_RAMP: usize = 5;
+const TUNE_PULSE_DUTY: f64 = 0.25;
+
+// what to do when the SWR or reflected power is too high
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum SwrProtection {
+    Off,
+    ReduceDrive,
+    DropTx,
+}
+
+impl SwrProtection {
+    pub fn from_u32(i: u32) -> Option<Self> {
+        match i {
+            0 => Some(SwrProtection::Off),
+            1 => Some(SwrProtection::ReduceDrive),
+            2 => Some(SwrProtection::DropTx),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Transmitter {
     pub protocol: u8,
@@ -73,8 +104,57 @@ pub struct Transmitter {
 #[serde(skip_serializing, skip_deserializing)]
     pub alex_reverse_power: u16,
     pub pa_calibration: Vec<f32>,
+    // watts, the top of the drive slider
+    pub max_power: f32,
+    pub tune_drive: Vec<f32>,
+    pub tune_pulsed: bool,
+    // seconds, 0 to tune until stopped
+    pub tune_timeout: u32,
+    pub tune_atu: bool,
+    pub swr_protection: SwrProtection,
+    // per band trip levels, reflected power in watts
+    pub swr_limit: Vec<f32>,
+    pub reflected_limit: Vec<f32>,
+    // watts, the drive while a ReduceDrive alarm is latched
+    pub swr_reduced_drive: f32,
     pub c1: f32,
     pub c2: f32,
+    pub ctcss: bool,
+    pub ctcss_frequency: f32,
+    pub duplex: Duplex,
+    pub repeater_offset: f32,
+    pub custom_offset: f32,
+    pub reverse: bool,
+    pub equalizer_enabled: bool,
+    pub equalizer_preamp: f32,
+    pub equalizer_low: f32,
+    pub equalizer_mid: f32,
+    pub equalizer_high: f32,
+    pub compressor: bool,
+    pub compressor_gain: f32,
+    pub leveler: bool,
+    pub leveler_top: f32,
+    pub leveler_decay: i32,
+    pub alc_attack: i32,
+    pub alc_decay: i32,
+    pub alc_hang: i32,
+    pub cessb: bool,
+    pub am_carrier_level: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub cw_keyer: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    pub cw_pitch: f32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub cw_envelope: Vec<f64>,
+#[serde(skip_serializing, skip_deserializing)]
+    cw_phase: f64,
+#[serde(skip_serializing, skip_deserializing)]
+    tune_pulse: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    tune_pulse_count: usize,
+// a test signal is being sent, the keyer must not replace it
+#[serde(skip_serializing, skip_deserializing)]
+    pub test_signal: bool,
 }
 
 impl Transmitter {
@@ -134,6 +214,18 @@ impl Transmitter {
         let alex_forward_power:u16 = 0;
         let alex_reverse_power:u16 = 0;
         let pa_calibration: Vec<f32> =  vec![38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8, 38.8]; // 15 bands
+        let mut max_power = 100.0;
+        if matches!(board, Boards::HermesLite | Boards::HermesLite2) {
+            max_power = 5.0;
+        }
+        let tune_drive: Vec<f32> = vec![max_power * 0.1; 15]; // 15 bands, watts
+        let tune_pulsed = false;
+        let tune_timeout = 0;
+        let tune_atu = true; // the HL2 always asked the ATU to tune
+        let swr_protection = SwrProtection::DropTx;
+        let swr_limit: Vec<f32> = vec![3.0; 15]; // 15 bands
+        let reflected_limit: Vec<f32> = vec![max_power * 0.1; 15]; // 15 bands
+        let swr_reduced_drive = max_power * 0.1;
 
         let mut c1 = 3.3;     // METIS
         let mut c2 = 0.09;
@@ -182,6 +274,35 @@ impl Transmitter {
         }
 
 
+        let ctcss = false;
+        let ctcss_frequency = 88.5;
+        let duplex = Duplex::Simplex;
+        let repeater_offset = 600000.0;
+        let custom_offset = 0.0;
+        let reverse = false;
+        let equalizer_enabled = false;
+        let equalizer_preamp = 0.0;
+        let equalizer_low = 0.0;
+        let equalizer_mid = 0.0;
+        let equalizer_high = 0.0;
+        let compressor = false;
+        let compressor_gain = 0.0;
+        let leveler = false;
+        let leveler_top = 5.0;
+        let leveler_decay = 500;
+        let alc_attack = 1;
+        let alc_decay = 10;
+        let alc_hang = 500;
+        let cessb = false;
+        let am_carrier_level = 0.5;
+        let cw_keyer = false;
+        let cw_pitch = 0.0;
+        let cw_envelope = vec![0.0f64; microphone_buffer_size];
+        let cw_phase = 0.0;
+        let tune_pulse = false;
+        let tune_pulse_count = 0;
+        let test_signal = false;
+
         let tx = Transmitter{ protocol,
             board,
             channel,
@@ -218,8 +339,45 @@ impl Transmitter {
             alex_forward_power,
             alex_reverse_power,
             pa_calibration,
+            max_power,
+            tune_drive,
+            tune_pulsed,
+            tune_timeout,
+            tune_atu,
+            swr_protection,
+            swr_limit,
+            reflected_limit,
+            swr_reduced_drive,
             c1,
             c2,
+            ctcss,
+            ctcss_frequency,
+            duplex,
+            repeater_offset,
+            custom_offset,
+            reverse,
+            equalizer_enabled,
+            equalizer_preamp,
+            equalizer_low,
+            equalizer_mid,
+            equalizer_high,
+            compressor,
+            compressor_gain,
+            leveler,
+            leveler_top,
+            leveler_decay,
+            alc_attack,
+            alc_decay,
+            alc_hang,
+            cessb,
+            am_carrier_level,
+            cw_keyer,
+            cw_pitch,
+            cw_envelope,
+            cw_phase,
+            tune_pulse,
+            tune_pulse_count,
+            test_signal,
         };
 
         tx
@@ -229,6 +387,13 @@ impl Transmitter {
         //self.local_microphone_buffer = vec![0u8; self.local_microphone_buffer_size * 2 as usize];
         self.microphone_buffer = vec![0.0f64; (self.microphone_buffer_size * 2) as usize];
         self.iq_buffer = vec![0.0f64; (self.output_samples * 2) as usize];
+        self.cw_keyer = false;
+        self.cw_pitch = 0.0;
+        self.test_signal = false;
+        self.cw_envelope = vec![0.0f64; self.microphone_buffer_size];
+        self.cw_phase = 0.0;
+        self.tune_pulse = false;
+        self.tune_pulse_count = 0;
         self.init_wdsp();
 
         let id_string = String::from("TX");
@@ -311,17 +476,7 @@ impl Transmitter {
             } else {
                 SetTXACFIRRun(self.channel, 1);
             }
-            SetTXAEQRun(self.channel, 0);
             SetTXAAMSQRun(self.channel, 0);
-            SetTXAosctrlRun(self.channel, 0);
-            SetTXAALCAttack(self.channel, 1);
-            SetTXAALCDecay(self.channel, 10);
-            SetTXAALCSt(self.channel, 1); // turn it on (always on)
-
-            SetTXALevelerAttack(self.channel, 1);
-            SetTXALevelerDecay(self.channel, 500);
-            SetTXALevelerTop(self.channel, 5.0);
-            SetTXALevelerSt(self.channel, false as i32);
 
             SetTXAPreGenMode(self.channel, 0);
             SetTXAPreGenToneMag(self.channel, 0.0);
@@ -341,14 +496,17 @@ impl Transmitter {
             SetTXAPanelRun(self.channel, 1);
 
             SetTXAFMDeviation(self.channel, 2500.0);
-            SetTXAAMCarrierLevel(self.channel, 0.5);
-
-            SetTXACompressorGain(self.channel, 0.0);
-            SetTXACompressorRun(self.channel, false as i32);
         }
 
         self.set_mode();
         self.set_filter();
+        self.set_ctcss();
+        self.enable_equalizer();
+        self.set_compressor();
+        self.set_leveler();
+        self.set_alc();
+        self.set_cessb();
+        self.set_am_carrier_level();
 
     }
 
@@ -365,19 +523,18 @@ impl Transmitter {
     }
 
 
-    pub fn set_tuning(&self, state: bool, cw_keyer_sidetone_frequency: i32) {
+    pub fn set_tuning(&mut self, state: bool, pulsed: bool, cw_keyer_sidetone_frequency: i32) {
+        self.tune_pulse = state && pulsed;
+        self.tune_pulse_count = 0;
         unsafe {
             if state {
-                let frequency = (self.filter_low + ((self.filter_high - self.filter_low) / 2.0)) as f64;
-                if self.mode == Modes::CWL.to_usize() {
-                    let frequency = -cw_keyer_sidetone_frequency as f64;
-                } else if self.mode == Modes::CWU.to_usize() {
-                    let frequency = cw_keyer_sidetone_frequency as f64;
-                } else if self.mode == Modes::LSB.to_usize() {
-                    let frequency = (-self.filter_low - ((self.filter_high - self.filter_low) / 2.0)) as f64;
-                } else if self.mode == Modes::USB.to_usize() {
-                    let frequency = (self.filter_low + ((self.filter_high - self.filter_low) / 2.0)) as f64;
-                }
+                // the tone offset from the carrier, the middle of the passband for the sideband modes
+                let frequency = match Modes::from_usize(self.mode) {
+                    Some(Modes::CWL) => -cw_keyer_sidetone_frequency as f64,
+                    Some(Modes::CWU) => cw_keyer_sidetone_frequency as f64,
+                    Some(Modes::AM) | Some(Modes::SAM) | Some(Modes::DSB) | Some(Modes::FMN) => 0.0,
+                    _ => ((self.filter_low + self.filter_high) / 2.0) as f64,
+                };
                 SetTXAPostGenToneFreq(self.channel, frequency);
                 SetTXAPostGenToneMag(self.channel, 0.99999);
                 SetTXAPostGenMode(self.channel, 0); // Tone
@@ -388,6 +545,183 @@ impl Transmitter {
         }
     }
 
+    // the test signal generators, the post generator follows the bandpass filter
+    // so its frequencies are negative on the lower sideband
+    pub fn set_test_signal(&self, test: &TxTest, state: bool) {
+        let mut sideband = 1.0;
+        if self.mode == Modes::LSB.to_usize() || self.mode == Modes::DIGL.to_usize() || self.mode == Modes::CWL.to_usize() {
+            sideband = -1.0;
+        }
+        let level = |db: f32| 10.0_f64.powf(db as f64 / 20.0);
+        unsafe {
+            SetTXAPreGenRun(self.channel, 0);
+            SetTXAPostGenRun(self.channel, 0);
+            if !state {
+                return;
+            }
+            match test.signal {
+                TestSignal::TwoTone => {
+                    SetTXAPostGenTTFreq(self.channel, sideband * test.tone1_frequency as f64, sideband * test.tone2_frequency as f64);
+                    SetTXAPostGenTTMag(self.channel, level(test.tone1_level), level(test.tone2_level));
+                    SetTXAPostGenMode(self.channel, 1); // Two Tone
+                    SetTXAPostGenRun(self.channel, 1);
+                },
+                TestSignal::Sweep => {
+                    SetTXAPostGenSweepFreq(self.channel, sideband * test.sweep_low as f64, sideband * test.sweep_high as f64);
+                    SetTXAPostGenSweepRate(self.channel, test.sweep_rate.into());
+                    SetTXAPostGenSweepMag(self.channel, level(test.sweep_level));
+                    SetTXAPostGenMode(self.channel, 3); // Sweep
+                    SetTXAPostGenRun(self.channel, 1);
+                },
+                TestSignal::Noise => {
+                    SetTXAPreGenNoiseMag(self.channel, level(test.noise_level));
+                    SetTXAPreGenMode(self.channel, 2); // Noise
+                    SetTXAPreGenRun(self.channel, 1);
+                },
+                TestSignal::Pulse => {
+                    SetTXAPreGenPulseFreq(self.channel, test.pulse_frequency.into());
+                    SetTXAPreGenPulseDutyCycle(self.channel, test.pulse_duty_cycle.into());
+                    SetTXAPreGenPulseToneFreq(self.channel, test.pulse_tone_frequency.into());
+                    SetTXAPreGenPulseTransition(self.channel, 0.002);
+                    SetTXAPreGenPulseMag(self.channel, level(test.pulse_level));
+                    SetTXAPreGenMode(self.channel, 6); // Pulse
+                    SetTXAPreGenRun(self.channel, 1);
+                },
+            }
+        }
+    }
+
+    // the drive byte for an output power in watts, gbb is the band's PA calibration in dB
+    pub fn drive_level(power: f32, gbb: f32) -> u8 {
+        if power <= 0.0 {
+            return 0;
+        }
+        let target_dbm = 10.0 * ((power * 1000.0).log10()) - gbb;
+        let target_volts = (10.0_f32.powf(target_dbm * 0.1) * 0.05).sqrt();
+        let volts = (target_volts / 0.8).min(1.0);
+        let actual_volts = (volts * (1.0 / 0.98)).clamp(0.0, 1.0);
+        (actual_volts * 255.0) as u8
+    }
+
+    // the PA calibration that gives the measured power at a drive byte, the inverse of drive_level
+    pub fn pa_gain(level: u8, power: f32) -> f32 {
+        let volts = (level as f32 / 255.0) * 0.98;
+        let target_volts = volts * 0.8;
+        let target_dbm = 10.0 * ((target_volts * target_volts) / 0.05).log10();
+        10.0 * ((power * 1000.0).log10()) - target_dbm
+    }
+
+    pub fn forward_power(&self) -> f32 {
+        let v_fwd = (self.alex_forward_power as f32 / 4095.0) * self.c1;
+        (v_fwd * v_fwd) / self.c2
+    }
+
+    pub fn reverse_power(&self) -> f32 {
+        let v_rev = (self.alex_reverse_power as f32 / 4095.0) * self.c1;
+        (v_rev * v_rev) / self.c2
+    }
+
+    pub fn set_ctcss(&self) {
+        unsafe {
+            SetTXACTCSSFreq(self.channel, self.ctcss_frequency.into());
+            SetTXACTCSSRun(self.channel, self.ctcss as i32);
+        }
+    }
+
+    // the repeater shift from the receive frequency, ignoring reverse
+    pub fn repeater_shift(&self) -> f32 {
+        if self.mode != Modes::FMN.to_usize() {
+            return 0.0;
+        }
+        match self.duplex {
+            Duplex::Simplex => 0.0,
+            Duplex::Plus => self.repeater_offset,
+            Duplex::Minus => -self.repeater_offset,
+            Duplex::Custom => self.custom_offset,
+        }
+    }
+
+    // added to the receive frequency to get the transmit frequency
+    pub fn duplex_offset(&self) -> f32 {
+        if self.reverse {
+            -self.repeater_shift()
+        } else {
+            self.repeater_shift()
+        }
+    }
+
+    pub fn enable_equalizer(&self) {
+        if self.equalizer_enabled {
+            self.set_equalizer_values();
+        }
+        unsafe {
+            SetTXAEQRun(self.channel, self.equalizer_enabled.into());
+        }
+    }
+
+    pub fn set_equalizer_values(&self) {
+        let mut values: Vec<i32> = vec![
+            self.equalizer_preamp as i32,
+            self.equalizer_low as i32,
+            self.equalizer_mid as i32,
+            self.equalizer_high as i32,
+        ];
+        unsafe {
+            SetTXAGrphEQ(self.channel, values.as_mut_ptr());
+        }
+    }
+
+    pub fn set_compressor(&self) {
+        unsafe {
+            SetTXACompressorGain(self.channel, self.compressor_gain.into());
+            SetTXACompressorRun(self.channel, self.compressor.into());
+        }
+    }
+
+    pub fn set_leveler(&self) {
+        unsafe {
+            SetTXALevelerAttack(self.channel, 1);
+            SetTXALevelerDecay(self.channel, self.leveler_decay);
+            SetTXALevelerTop(self.channel, self.leveler_top.into());
+            SetTXALevelerSt(self.channel, self.leveler.into());
+        }
+    }
+
+    pub fn set_alc(&self) {
+        unsafe {
+            SetTXAALCAttack(self.channel, self.alc_attack);
+            SetTXAALCDecay(self.channel, self.alc_decay);
+            SetTXAALCHang(self.channel, self.alc_hang);
+            SetTXAALCSt(self.channel, 1); // turn it on (always on)
+        }
+    }
+
+    // controlled envelope SSB, limits the overshoot after the bandpass filter
+    pub fn set_cessb(&self) {
+        unsafe {
+            SetTXAosctrlRun(self.channel, self.cessb.into());
+        }
+    }
+
+    pub fn set_am_carrier_level(&self) {
+        unsafe {
+            SetTXAAMCarrierLevel(self.channel, self.am_carrier_level.into());
+        }
+    }
+
+    // meters in dB, only meaningful while transmitting
+    pub fn alc_meter(&self) -> f64 {
+        unsafe {
+            GetTXAMeter(self.channel, txaMeterType_TXA_ALC_GAIN as i32)
+        }
+    }
+
+    pub fn compressor_meter(&self) -> f64 {
+        unsafe {
+            GetTXAMeter(self.channel, txaMeterType_TXA_COMP_PK as i32)
+        }
+    }
+
     pub fn set_micgain(&self) {
         unsafe {
             SetTXAPanelGain1(self.channel,10.0_f32.powf(self.micgain / 20.0) as f64);
@@ -403,8 +737,55 @@ impl Transmitter {
         let mut result: c_int = 0;
         unsafe {
             fexchange0(self.channel, raw_ptr, iq_ptr, &mut result);
+        }
+        if self.cw_keyer && !self.test_signal {
+            self.cw_iq();
+        }
+        if self.tune_pulse {
+            self.tune_pulse_iq();
+        }
+        unsafe {
             Spectrum0(1, self.channel, 0, 0, iq_ptr);
         }
     }
 
+    // key the tune carrier on and off with shaped edges to keep the average power down
+    fn tune_pulse_iq(&mut self) {
+        let period = self.output_rate as usize * TUNE_PULSE_PERIOD / 1000;
+        let on = (period as f64 * TUNE_PULSE_DUTY) as usize;
+        let ramp = self.output_rate as usize * TUNE_PULSE_RAMP / 1000;
+        for i in 0..self.output_samples as usize {
+            let position = self.tune_pulse_count;
+            let envelope = if position >= on {
+                0.0
+            } else if position < ramp {
+                0.5 - 0.5 * (PI * position as f64 / ramp as f64).cos()
+            } else if position > on - ramp {
+                0.5 - 0.5 * (PI * (on - position) as f64 / ramp as f64).cos()
+            } else {
+                1.0
+            };
+            self.iq_buffer[i*2] *= envelope;
+            self.iq_buffer[(i*2)+1] *= envelope;
+            self.tune_pulse_count = (self.tune_pulse_count + 1) % period;
+        }
+    }
+
+    // replace the transmit IQ with a tone at the CW pitch keyed by the software keyer envelope
+    fn cw_iq(&mut self) {
+        let ratio = self.output_samples as usize / self.microphone_buffer_size;
+        let step = 2.0 * PI * self.cw_pitch as f64 / self.output_rate as f64;
+        for i in 0..self.output_samples as usize {
+            let envelope = self.cw_envelope[i / ratio];
+            self.iq_buffer[i*2] = envelope * self.cw_phase.cos();
+            self.iq_buffer[(i*2)+1] = envelope * self.cw_phase.sin();
+            self.cw_phase += step;
+            if self.cw_phase > PI {
+                self.cw_phase -= 2.0 * PI;
+            } else if self.cw_phase < -PI {
+                self.cw_phase += 2.0 * PI;
+            }
+        }
+    }
+
 }
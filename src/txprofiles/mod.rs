@@ -0,0 +1,237 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::modes::Modes;
+use crate::transmitter::Transmitter;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxProfile {
+    pub name: String,
+    // audio passband, positive for both sidebands
+    pub filter_low: f32,
+    pub filter_high: f32,
+    pub micgain: f32,
+    pub equalizer_enabled: bool,
+    pub equalizer_preamp: f32,
+    pub equalizer_low: f32,
+    pub equalizer_mid: f32,
+    pub equalizer_high: f32,
+    pub compressor: bool,
+    pub compressor_gain: f32,
+    pub leveler: bool,
+    pub leveler_top: f32,
+    pub leveler_decay: i32,
+}
+
+impl TxProfile {
+
+    pub fn new(name: &str, filter_low: f32, filter_high: f32) -> TxProfile {
+        TxProfile {
+            name: String::from(name),
+            filter_low,
+            filter_high,
+            micgain: 0.0,
+            equalizer_enabled: false,
+            equalizer_preamp: 0.0,
+            equalizer_low: 0.0,
+            equalizer_mid: 0.0,
+            equalizer_high: 0.0,
+            compressor: false,
+            compressor_gain: 0.0,
+            leveler: false,
+            leveler_top: 5.0,
+            leveler_decay: 500,
+        }
+    }
+
+    // capture the current transmitter settings
+    pub fn from_transmitter(tx: &Transmitter, name: &str) -> TxProfile {
+        let mut filter_low = tx.filter_low;
+        let mut filter_high = tx.filter_high;
+        if filter_high <= 0.0 {
+            // lower sideband
+            filter_low = -tx.filter_high;
+            filter_high = -tx.filter_low;
+        } else if filter_low < 0.0 {
+            // both sidebands
+            filter_low = 0.0;
+        }
+        TxProfile {
+            name: String::from(name),
+            filter_low,
+            filter_high,
+            micgain: tx.micgain,
+            equalizer_enabled: tx.equalizer_enabled,
+            equalizer_preamp: tx.equalizer_preamp,
+            equalizer_low: tx.equalizer_low,
+            equalizer_mid: tx.equalizer_mid,
+            equalizer_high: tx.equalizer_high,
+            compressor: tx.compressor,
+            compressor_gain: tx.compressor_gain,
+            leveler: tx.leveler,
+            leveler_top: tx.leveler_top,
+            leveler_decay: tx.leveler_decay,
+        }
+    }
+
+    // the transmit filter for the mode
+    pub fn filter(&self, mode: usize) -> (f32, f32) {
+        match Modes::from_usize(mode) {
+            Some(Modes::LSB) | Some(Modes::DIGL) | Some(Modes::CWL) => (-self.filter_high, -self.filter_low),
+            Some(Modes::DSB) | Some(Modes::FMN) | Some(Modes::AM) | Some(Modes::SAM) | Some(Modes::SPEC) | Some(Modes::DRM) => (-self.filter_high, self.filter_high),
+            _ => (self.filter_low, self.filter_high),
+        }
+    }
+
+    pub fn apply(&self, tx: &mut Transmitter) {
+        let (low, high) = self.filter(tx.mode);
+        tx.filter_low = low;
+        tx.filter_high = high;
+        tx.set_filter();
+        self.apply_processing(tx);
+    }
+
+    // everything except the transmit filter
+    pub fn apply_processing(&self, tx: &mut Transmitter) {
+        tx.micgain = self.micgain;
+        tx.equalizer_enabled = self.equalizer_enabled;
+        tx.equalizer_preamp = self.equalizer_preamp;
+        tx.equalizer_low = self.equalizer_low;
+        tx.equalizer_mid = self.equalizer_mid;
+        tx.equalizer_high = self.equalizer_high;
+        tx.compressor = self.compressor;
+        tx.compressor_gain = self.compressor_gain;
+        tx.leveler = self.leveler;
+        tx.leveler_top = self.leveler_top;
+        tx.leveler_decay = self.leveler_decay;
+        tx.set_micgain();
+        tx.enable_equalizer();
+        tx.set_compressor();
+        tx.set_leveler();
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TxProfiles {
+    pub profiles: Vec<TxProfile>,
+    // the default profile name for each mode, empty for none
+    pub mode_profiles: Vec<String>,
+    // the profile in use, empty when the transmit filter follows the receiver
+    pub current: String,
+    // the settings in use before a profile was selected, put back when there is no profile
+    pub baseline: Option<TxProfile>,
+#[serde(skip_serializing, skip_deserializing)]
+    pub changed: bool,
+}
+
+impl TxProfiles {
+
+    pub fn new() -> TxProfiles {
+        let mut ssb_dx = TxProfile::new("SSB DX", 300.0, 2400.0);
+        ssb_dx.equalizer_enabled = true;
+        ssb_dx.equalizer_low = -6.0;
+        ssb_dx.equalizer_mid = 3.0;
+        ssb_dx.equalizer_high = 6.0;
+        ssb_dx.compressor = true;
+        ssb_dx.compressor_gain = 10.0;
+        ssb_dx.leveler = true;
+
+        let mut essb = TxProfile::new("ESSB", 50.0, 3500.0);
+        essb.equalizer_enabled = true;
+        essb.equalizer_low = 3.0;
+        essb.compressor = true;
+        essb.compressor_gain = 3.0;
+        essb.leveler = true;
+
+        let digital = TxProfile::new("Digital", 100.0, 3000.0);
+
+        let profiles = vec![ssb_dx, essb, digital];
+        // the examples are not assigned to any mode until the user picks them
+        let mode_profiles = vec![String::new(); Modes::DRM.to_usize() + 1];
+        let current = String::new();
+        let baseline = None;
+        let changed = true;
+        TxProfiles {
+            profiles,
+            mode_profiles,
+            current,
+            baseline,
+            changed,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.mode_profiles.resize(Modes::DRM.to_usize() + 1, String::new());
+        self.changed = true;
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.profiles.iter().map(|p| p.name.clone()).collect()
+    }
+
+    pub fn find(&self, name: &str) -> Option<&TxProfile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    // add a profile, replacing any with the same name
+    pub fn save(&mut self, profile: TxProfile) {
+        match self.profiles.iter().position(|p| p.name == profile.name) {
+            Some(i) => self.profiles[i] = profile,
+            None => self.profiles.push(profile),
+        }
+        self.changed = true;
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.profiles.retain(|p| p.name != name);
+        for mode_profile in self.mode_profiles.iter_mut() {
+            if mode_profile == name {
+                mode_profile.clear();
+            }
+        }
+        if self.current == name {
+            self.current.clear();
+        }
+        self.changed = true;
+    }
+
+    pub fn export_json(&self, path: &Path) -> Result<(), std::io::Error> {
+        let mut file = File::create(path)?;
+        let json = serde_json::to_string_pretty(&self.profiles)?;
+        file.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    // returns the number of profiles added or replaced
+    pub fn import_json(&mut self, path: &Path) -> Result<usize, std::io::Error> {
+        let mut file = File::open(path)?;
+        let mut s = String::new();
+        file.read_to_string(&mut s)?;
+        let profiles: Vec<TxProfile> = serde_json::from_str(&s)?;
+        let count = profiles.len();
+        for profile in profiles {
+            self.save(profile);
+        }
+        Ok(count)
+    }
+}
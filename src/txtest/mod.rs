@@ -0,0 +1,294 @@
+/*
+    Copyright (C) 2025  John Melton G0ORX/N6LYT
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use glib::ControlFlow::{Break, Continue};
+use glib::timeout_add_local;
+use gtk::glib::Propagation;
+use gtk::prelude::*;
+use gtk::{Adjustment, ApplicationWindow, Builder, DropDown, Label, ToggleButton, Window};
+use serde::{Deserialize, Serialize};
+
+use std::time::{Duration, Instant};
+
+use crate::radio::RadioMutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestSignal {
+    TwoTone,
+    Sweep,
+    Noise,
+    Pulse,
+}
+
+impl TestSignal {
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(TestSignal::TwoTone),
+            1 => Some(TestSignal::Sweep),
+            2 => Some(TestSignal::Noise),
+            3 => Some(TestSignal::Pulse),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        *self as u32
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TxTest {
+    pub signal: TestSignal,
+    // frequencies in Hz from the carrier, levels in dB below full scale
+    pub tone1_frequency: f32,
+    pub tone2_frequency: f32,
+    pub tone1_level: f32,
+    pub tone2_level: f32,
+    pub sweep_low: f32,
+    pub sweep_high: f32,
+    // Hz per second
+    pub sweep_rate: f32,
+    pub sweep_level: f32,
+    pub noise_level: f32,
+    // pulses per second
+    pub pulse_frequency: f32,
+    pub pulse_duty_cycle: f32,
+    pub pulse_tone_frequency: f32,
+    pub pulse_level: f32,
+    // the drive is limited to this while the test is running
+    pub power_cap: f32,
+    // seconds before the test stops itself
+    pub time_limit: u32,
+#[serde(skip_serializing, skip_deserializing)]
+    pub running: bool,
+#[serde(skip_serializing, skip_deserializing)]
+    started: Option<Instant>,
+}
+
+impl TxTest {
+
+    pub fn new() -> TxTest {
+        let signal = TestSignal::TwoTone;
+        let tone1_frequency = 700.0;
+        let tone2_frequency = 1900.0;
+        let tone1_level = -7.0;
+        let tone2_level = -7.0;
+        let sweep_low = 100.0;
+        let sweep_high = 3000.0;
+        let sweep_rate = 500.0;
+        let sweep_level = -6.0;
+        let noise_level = -6.0;
+        let pulse_frequency = 25.0;
+        let pulse_duty_cycle = 0.25;
+        let pulse_tone_frequency = 1000.0;
+        let pulse_level = -6.0;
+        let power_cap = 10.0;
+        let time_limit = 60;
+        let running = false;
+        let started = None;
+        TxTest {
+            signal,
+            tone1_frequency,
+            tone2_frequency,
+            tone1_level,
+            tone2_level,
+            sweep_low,
+            sweep_high,
+            sweep_rate,
+            sweep_level,
+            noise_level,
+            pulse_frequency,
+            pulse_duty_cycle,
+            pulse_tone_frequency,
+            pulse_level,
+            power_cap,
+            time_limit,
+            running,
+            started,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.running = false;
+        self.started = None;
+    }
+
+    pub fn start(&mut self) {
+        self.running = true;
+        self.started = Some(Instant::now());
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+        self.started = None;
+    }
+
+    // seconds left before the test stops
+    pub fn remaining(&self) -> u64 {
+        match self.started {
+            Some(started) => (self.time_limit as u64).saturating_sub(started.elapsed().as_secs()),
+            None => 0,
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        match self.started {
+            Some(started) => started.elapsed() >= Duration::from_secs(self.time_limit as u64),
+            None => false,
+        }
+    }
+}
+
+pub fn create_tx_test_dialog(parent: &ApplicationWindow, radio_mutex: &RadioMutex) -> Window {
+
+    let ui_xml = include_str!("../ui/txtest.xml");
+    let builder = Builder::from_string(ui_xml);
+
+    let window: Window = builder
+            .object("tx_test_window")
+            .expect("Could not get object `tx_test_window` from builder.");
+
+    window.set_transient_for(Some(parent)); // keeps it on top
+
+    let r = radio_mutex.radio.lock().unwrap();
+        let signal = r.tx_test.signal;
+        let max_power = r.transmitter.max_power;
+        let values = [
+            ("tone1_frequency_adjustment", r.tx_test.tone1_frequency),
+            ("tone2_frequency_adjustment", r.tx_test.tone2_frequency),
+            ("tone1_level_adjustment", r.tx_test.tone1_level),
+            ("tone2_level_adjustment", r.tx_test.tone2_level),
+            ("sweep_low_adjustment", r.tx_test.sweep_low),
+            ("sweep_high_adjustment", r.tx_test.sweep_high),
+            ("sweep_rate_adjustment", r.tx_test.sweep_rate),
+            ("sweep_level_adjustment", r.tx_test.sweep_level),
+            ("noise_level_adjustment", r.tx_test.noise_level),
+            ("pulse_frequency_adjustment", r.tx_test.pulse_frequency),
+            ("pulse_duty_cycle_adjustment", r.tx_test.pulse_duty_cycle),
+            ("pulse_tone_frequency_adjustment", r.tx_test.pulse_tone_frequency),
+            ("pulse_level_adjustment", r.tx_test.pulse_level),
+            ("power_cap_adjustment", r.tx_test.power_cap),
+            ("time_limit_adjustment", r.tx_test.time_limit as f32),
+        ];
+    drop(r);
+
+    let signal_dropdown: DropDown = builder
+            .object("tx_test_signal_dropdown")
+            .expect("Could not get object `tx_test_signal_dropdown` from builder.");
+    signal_dropdown.set_selected(signal.to_u32());
+    let radio_mutex_clone = radio_mutex.clone();
+    signal_dropdown.connect_selected_notify(move |dropdown| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        r.tx_test.signal = TestSignal::from_u32(dropdown.selected()).unwrap_or(TestSignal::TwoTone);
+        if r.tx_test.running {
+            r.transmitter.set_test_signal(&r.tx_test, true);
+        }
+    });
+
+    for (id, value) in values {
+        let adjustment: Adjustment = builder
+                .object(id)
+                .expect("Could not get object `tx_test adjustment` from builder.");
+        if id == "power_cap_adjustment" {
+            // watts, up to the most the radio can give
+            adjustment.set_upper(max_power.into());
+        }
+        adjustment.set_value(value.into());
+        let radio_mutex_clone = radio_mutex.clone();
+        adjustment.connect_value_changed(move |adjustment| {
+            let mut r = radio_mutex_clone.radio.lock().unwrap();
+            let value = adjustment.value() as f32;
+            match id {
+                "tone1_frequency_adjustment" => r.tx_test.tone1_frequency = value,
+                "tone2_frequency_adjustment" => r.tx_test.tone2_frequency = value,
+                "tone1_level_adjustment" => r.tx_test.tone1_level = value,
+                "tone2_level_adjustment" => r.tx_test.tone2_level = value,
+                "sweep_low_adjustment" => r.tx_test.sweep_low = value,
+                "sweep_high_adjustment" => r.tx_test.sweep_high = value,
+                "sweep_rate_adjustment" => r.tx_test.sweep_rate = value,
+                "sweep_level_adjustment" => r.tx_test.sweep_level = value,
+                "noise_level_adjustment" => r.tx_test.noise_level = value,
+                "pulse_frequency_adjustment" => r.tx_test.pulse_frequency = value,
+                "pulse_duty_cycle_adjustment" => r.tx_test.pulse_duty_cycle = value,
+                "pulse_tone_frequency_adjustment" => r.tx_test.pulse_tone_frequency = value,
+                "pulse_level_adjustment" => r.tx_test.pulse_level = value,
+                "power_cap_adjustment" => r.tx_test.power_cap = value,
+                "time_limit_adjustment" => r.tx_test.time_limit = value as u32,
+                _ => {},
+            }
+            if r.tx_test.running {
+                r.transmitter.set_test_signal(&r.tx_test, true);
+            }
+        });
+    }
+
+    let remaining_label: Label = builder
+            .object("tx_test_remaining_label")
+            .expect("Could not get object `tx_test_remaining_label` from builder.");
+
+    let start_button: ToggleButton = builder
+            .object("tx_test_start_button")
+            .expect("Could not get object `tx_test_start_button` from builder.");
+    let radio_mutex_clone = radio_mutex.clone();
+    start_button.connect_toggled(move |button| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if button.is_active() {
+            if !r.start_tx_test() {
+                drop(r);
+                button.set_active(false);
+            }
+        } else if r.tx_test.running {
+            r.stop_tx_test();
+        }
+    });
+
+    // never leave the test running without the dialog
+    let radio_mutex_clone = radio_mutex.clone();
+    window.connect_close_request(move |_| {
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if r.tx_test.running {
+            r.stop_tx_test();
+        }
+        Propagation::Proceed
+    });
+
+    let window_clone = window.clone();
+    let radio_mutex_clone = radio_mutex.clone();
+    timeout_add_local(Duration::from_millis(100), move || {
+        if !window_clone.is_visible() {
+            return Break;
+        }
+        let mut r = radio_mutex_clone.radio.lock().unwrap();
+        if r.tx_test.running && r.tx_test.expired() {
+            r.stop_tx_test();
+        }
+        let running = r.tx_test.running;
+        let remaining = r.tx_test.remaining();
+        drop(r);
+        if running {
+            remaining_label.set_label(&format!("Stops in {}s", remaining));
+        } else {
+            remaining_label.set_label("Stopped");
+            if start_button.is_active() {
+                start_button.set_active(false);
+            }
+        }
+        Continue
+    });
+
+    window
+}
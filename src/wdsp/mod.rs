@@ -270,6 +270,15 @@ unsafe extern "C" {
 unsafe extern "C" {
     pub fn RXANBPAddNotch(channel: ::std::os::raw::c_int, notch: ::std::os::raw::c_int, fcenter: f64, fwidth: f64, active: ::std::os::raw::c_int) -> ::std::os::raw::c_int;
 }
+unsafe extern "C" {
+    pub fn RXANBPEditNotch(channel: ::std::os::raw::c_int, notch: ::std::os::raw::c_int, fcenter: f64, fwidth: f64, active: ::std::os::raw::c_int) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn RXANBPDeleteNotch(channel: ::std::os::raw::c_int, notch: ::std::os::raw::c_int) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn RXANBPGetNumNotches(channel: ::std::os::raw::c_int, nnotches: *mut ::std::os::raw::c_int);
+}
 unsafe extern "C" {
     pub fn RXANBPSetNotchesRun(channel: ::std::os::raw::c_int, run: ::std::os::raw::c_int);
 }